@@ -0,0 +1,282 @@
+//! Auto-type sequences for vault items.
+//!
+//! A template such as `{USERNAME}{TAB}{PASSWORD}{ENTER}` is parsed into
+//! tokens, the item's secrets are filled in, and the resulting steps are
+//! typed with `wtype`: text through its stdin (`wtype -`), keys as
+//! `wtype -k <keysym>`. Nothing goes through a shell, so no secret or
+//! literal can be read as an option or a command.
+//!
+//! Placeholders follow KeePass: `{USERNAME}`, `{PASSWORD}`, `{TOTP}`,
+//! `{S:label}` for any other field, the keys in [`KEYS`], `{DELAY n}` to
+//! pause `n` milliseconds, and `{{}` / `{}}` for literal braces.
+
+use super::dry_run;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Template used when neither the item nor the config names one.
+pub const DEFAULT_TEMPLATE: &str = "{PASSWORD}";
+
+/// Longest `{DELAY n}` accepted, in milliseconds.
+const MAX_DELAY_MS: u64 = 10_000;
+
+/// Key placeholders and the keysyms wtype presses for them.
+const KEYS: &[(&str, &str)] = &[
+    ("TAB", "Tab"),
+    ("ENTER", "Return"),
+    ("SPACE", "space"),
+    ("BACKSPACE", "BackSpace"),
+    ("ESC", "Escape"),
+    ("UP", "Up"),
+    ("DOWN", "Down"),
+    ("LEFT", "Left"),
+    ("RIGHT", "Right"),
+    ("HOME", "Home"),
+    ("END", "End"),
+];
+
+/// One piece of a parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Literal(String),
+    Username,
+    Password,
+    Totp,
+    Field(String),
+    Key(&'static str),
+    Delay(u64),
+}
+
+/// Something to do while typing, secrets already filled in.
+pub enum Step {
+    Text(Zeroizing<String>),
+    Key(&'static str),
+    Delay(Duration),
+}
+
+/// Parse a template. Unknown placeholders and unbalanced braces are errors,
+/// so a typo never types its own text into a login form.
+pub fn parse(template: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        literal.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("{{}") {
+            literal.push('{');
+            rest = after;
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("{}}") {
+            literal.push('}');
+            rest = after;
+            continue;
+        }
+        if rest.starts_with('}') {
+            return Err(format!(
+                "Unmatched '}}' in auto-type template \"{template}\""
+            ));
+        }
+        let end = rest
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in auto-type template \"{template}\""))?;
+        let name = &rest[1..end];
+        rest = &rest[end + 1..];
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(std::mem::take(&mut literal)));
+        }
+        tokens.push(placeholder(name)?);
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+    Ok(tokens)
+}
+
+fn placeholder(name: &str) -> Result<Token, String> {
+    if let Some(label) = name.strip_prefix("S:") {
+        if label.trim().is_empty() {
+            return Err("{S:} needs a field label".into());
+        }
+        return Ok(Token::Field(label.trim().to_string()));
+    }
+    if let Some(ms) = name.strip_prefix("DELAY ") {
+        return ms
+            .trim()
+            .parse()
+            .ok()
+            .filter(|ms| *ms <= MAX_DELAY_MS)
+            .map(Token::Delay)
+            .ok_or_else(|| format!("{{DELAY n}} takes 0 to {MAX_DELAY_MS} milliseconds"));
+    }
+    match name.to_ascii_uppercase().as_str() {
+        "USERNAME" => Ok(Token::Username),
+        "PASSWORD" => Ok(Token::Password),
+        "TOTP" => Ok(Token::Totp),
+        upper => KEYS
+            .iter()
+            .find(|(key, _)| *key == upper)
+            .map(|(_, keysym)| Token::Key(keysym))
+            .ok_or_else(|| format!("Unknown auto-type placeholder {{{name}}}")),
+    }
+}
+
+/// Fill in the item's values. `value` is asked once per secret placeholder;
+/// any error aborts before anything is typed.
+pub fn steps(
+    tokens: &[Token],
+    mut value: impl FnMut(&Token) -> Result<Zeroizing<String>, String>,
+) -> Result<Vec<Step>, String> {
+    let mut steps: Vec<Step> = Vec::new();
+    for token in tokens {
+        let text = match token {
+            Token::Key(keysym) => {
+                steps.push(Step::Key(keysym));
+                continue;
+            }
+            Token::Delay(ms) => {
+                steps.push(Step::Delay(Duration::from_millis(*ms)));
+                continue;
+            }
+            Token::Literal(text) => Zeroizing::new(text.clone()),
+            secret => value(secret)?,
+        };
+        // Adjacent text is typed in one go
+        match steps.last_mut() {
+            Some(Step::Text(typed)) => typed.push_str(&text),
+            _ => steps.push(Step::Text(text)),
+        }
+    }
+    Ok(steps)
+}
+
+/// Run `steps` after `delay`, which gives focus time to return to the
+/// window Burrow was opened over. Blocks; call from a background thread.
+pub fn run(steps: &[Step], delay: Duration) -> Result<(), String> {
+    if dry_run::is_enabled() {
+        return dry_run::autotype(steps.len());
+    }
+    std::thread::sleep(delay);
+    for step in steps {
+        match step {
+            Step::Text(text) => type_text(text)?,
+            Step::Key(keysym) => press(keysym)?,
+            Step::Delay(pause) => std::thread::sleep(*pause),
+        }
+    }
+    Ok(())
+}
+
+fn type_text(text: &str) -> Result<(), String> {
+    let mut child = Command::new("wtype")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("wtype failed (is wtype installed?): {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("wtype failed: {e}"))?;
+    }
+    let status = child.wait().map_err(|e| format!("wtype failed: {e}"))?;
+    if !status.success() {
+        return Err(format!("wtype exited with {status}"));
+    }
+    Ok(())
+}
+
+fn press(keysym: &str) -> Result<(), String> {
+    let status = Command::new("wtype")
+        .args(["-k", keysym])
+        .status()
+        .map_err(|e| format!("wtype failed (is wtype installed?): {e}"))?;
+    if !status.success() {
+        return Err(format!("wtype -k {keysym} exited with {status}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_placeholders_keys_and_literals() {
+        assert_eq!(
+            parse("{USERNAME}{TAB}{password}{DELAY 250}{ENTER}").unwrap(),
+            vec![
+                Token::Username,
+                Token::Key("Tab"),
+                Token::Password,
+                Token::Delay(250),
+                Token::Key("Return"),
+            ]
+        );
+        assert_eq!(
+            parse("pin {S: PIN }{{}x{}}{{}{TOTP}").unwrap(),
+            vec![
+                Token::Literal("pin ".into()),
+                Token::Field("PIN".into()),
+                Token::Literal("{x}{".into()),
+                Token::Totp,
+            ]
+        );
+        assert_eq!(parse("").unwrap(), vec![]);
+        assert_eq!(parse(DEFAULT_TEMPLATE).unwrap(), vec![Token::Password]);
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        for bad in [
+            "{USER}",
+            "{PASSWORD",
+            "PASSWORD}",
+            "{S:}",
+            "{DELAY soon}",
+            "{DELAY 60000}",
+        ] {
+            assert!(parse(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn steps_fill_in_secrets_and_merge_text() {
+        let tokens = parse("{USERNAME}@{S:domain}{TAB}{PASSWORD}{ENTER}").unwrap();
+        let filled = steps(&tokens, |token| {
+            Ok(Zeroizing::new(
+                match token {
+                    Token::Username => "alice",
+                    Token::Password => "-rf hunter2",
+                    Token::Field(label) if label == "domain" => "corp",
+                    _ => unreachable!(),
+                }
+                .to_string(),
+            ))
+        })
+        .unwrap();
+        let plain: Vec<String> = filled
+            .iter()
+            .map(|step| match step {
+                Step::Text(text) => format!("text:{}", text.as_str()),
+                Step::Key(keysym) => format!("key:{keysym}"),
+                Step::Delay(pause) => format!("delay:{}", pause.as_millis()),
+            })
+            .collect();
+        assert_eq!(
+            plain,
+            vec![
+                "text:alice@corp",
+                "key:Tab",
+                "text:-rf hunter2",
+                "key:Return"
+            ]
+        );
+
+        let missing = steps(&[Token::Totp], |_| Err("no one-time password".into()));
+        assert!(missing.is_err());
+    }
+}
@@ -27,6 +27,16 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub fn copy_secret(clear_after: Option<std::time::Duration>) -> Result<(), String> {
+    tracing::debug!(?clear_after, "[dry-run] copy_secret");
+    Ok(())
+}
+
+pub fn autotype(steps: usize) -> Result<(), String> {
+    tracing::debug!(steps, "[dry-run] autotype");
+    Ok(())
+}
+
 pub fn type_text_wayland(_text: &str, _app: &tauri::AppHandle) -> Result<(), String> {
     tracing::debug!("[dry-run] type_text_wayland");
     Ok(())
@@ -51,17 +61,38 @@ pub fn open_in_vscode(path: &str) -> Result<(), String> {
     Ok(())
 }
 
+pub fn write_new_file(path: &std::path::Path, content: &str) -> Result<(), String> {
+    tracing::debug!(path = %path.display(), bytes = content.len(), "[dry-run] write_new_file");
+    Ok(())
+}
+
 pub fn launch_app(exec: &str) -> Result<(), String> {
     tracing::debug!(exec, "[dry-run] launch_app");
     Ok(())
 }
 
-pub fn exec_ssh(host: &str, user: Option<&str>) -> Result<(), String> {
-    let target = match user {
-        Some(u) => format!("{}@{}", u, host),
-        None => host.to_string(),
-    };
-    tracing::debug!(target, "[dry-run] exec_ssh");
+pub fn spawn_command(argv: &[String], working_dir: Option<&std::path::Path>) -> Result<(), String> {
+    tracing::debug!(?argv, ?working_dir, "[dry-run] spawn_command");
+    Ok(())
+}
+
+pub fn focus_window(class: &str, window_id: &str) -> Result<bool, String> {
+    tracing::debug!(class, window_id, "[dry-run] focus_window");
+    Ok(true)
+}
+
+pub fn window_action(
+    action: crate::commands::windows::WindowAction,
+    class: &str,
+    window_id: &str,
+) -> Result<(), String> {
+    tracing::debug!(?action, class, window_id, "[dry-run] window_action");
+    Ok(())
+}
+
+pub fn exec_ssh(host: &str, user: Option<&str>, options: &[&str]) -> Result<(), String> {
+    let target = super::utils::ssh_destination(host, user);
+    tracing::debug!(target, ?options, "[dry-run] exec_ssh");
     Ok(())
 }
 
@@ -1,9 +1,10 @@
 use crate::actions::modifier::Modifier;
-use crate::actions::{output_window, utils};
-use crate::commands::{apps, onepass, special};
+use crate::actions::{autotype, output_window, secret_clipboard, utils};
+use crate::commands::{apps, clipboard_ai, files, onepass, special, ssh, windows};
 use crate::context::AppContext;
 use crate::router::{Category, OutputMode, SearchResult};
 use serde::Serialize;
+use zeroize::Zeroizing;
 
 /// Check whether a category has a handler in the action dispatcher.
 /// Note: Chat category is handled separately by the frontend and is intentionally excluded.
@@ -19,12 +20,18 @@ pub fn is_valid_category(category: Category) -> bool {
             | Category::Math
             | Category::Info
             | Category::Special
+            | Category::Window
     )
 }
 
 /// Resolve user-provided results to trusted, canonical command payloads.
 /// This prevents untrusted clients from injecting arbitrary `exec` values.
-fn resolve_trusted_result(result: &SearchResult, ctx: &AppContext) -> Result<SearchResult, String> {
+/// Results that can no longer be resolved (uninstalled apps, removed hosts,
+/// deleted files) are errors.
+pub(crate) fn resolve_trusted_result(
+    result: &SearchResult,
+    ctx: &AppContext,
+) -> Result<SearchResult, String> {
     match result.category {
         Category::App | Category::History => {
             let exec = apps::resolve_app_exec(&result.id, ctx)
@@ -38,6 +45,9 @@ fn resolve_trusted_result(result: &SearchResult, ctx: &AppContext) -> Result<Sea
         }
         Category::Special => special::resolve_special_by_id(&result.id)
             .ok_or_else(|| format!("Unknown special command id: {}", result.id)),
+        Category::Ssh => resolve_trusted_ssh(result).map(|(trusted, _)| trusted),
+        Category::File | Category::Vector => files::resolve_file_by_id(&result.id, result.category)
+            .ok_or_else(|| format!("File no longer exists: {}", result.id)),
         Category::Onepass => {
             let mut trusted = result.clone();
             trusted.input_spec = None;
@@ -63,12 +73,22 @@ fn resolve_trusted_result(result: &SearchResult, ctx: &AppContext) -> Result<Sea
     }
 }
 
+/// Rebuild an SSH result, keeping the host it came from so SSH actions
+/// don't parse the config again.
+fn resolve_trusted_ssh(result: &SearchResult) -> Result<(SearchResult, ssh::SshHost), String> {
+    ssh::resolve_ssh_by_id(&result.id).ok_or_else(|| format!("Unknown SSH host: {}", result.id))
+}
+
 pub fn handle_action(
     result: &SearchResult,
     modifier: Modifier,
     secondary_input: Option<&str>,
     ctx: &AppContext,
 ) -> Result<(), String> {
+    if result.category == Category::Ssh {
+        let (trusted, ssh_host) = resolve_trusted_ssh(result)?;
+        return handle_ssh(&trusted, ssh_host, modifier, secondary_input, ctx);
+    }
     let trusted = resolve_trusted_result(result, ctx)?;
 
     if trusted.category == Category::Special && trusted.id == "special-refresh" {
@@ -76,14 +96,40 @@ pub fn handle_action(
         return Ok(());
     }
 
+    if trusted.category == Category::Special && trusted.exec.starts_with(clipboard_ai::EXEC_PREFIX)
+    {
+        // Answered through clipboard_ai so the frontend can show the result.
+        return Err(format!("{} must be run through clipboard_ai", trusted.name));
+    }
+
     match trusted.category {
-        Category::Onepass => handle_onepass(&trusted, modifier, ctx),
+        Category::Onepass => handle_onepass(&trusted, modifier, secondary_input, ctx),
         Category::File | Category::Vector => handle_file(&trusted, modifier, ctx),
-        Category::App | Category::History | Category::Special => {
-            handle_launch(&trusted, ctx, secondary_input)
+        Category::App | Category::History => {
+            ctx.hide_window();
+            // Enter focuses a running instance; any modifier launches a new one
+            if modifier == Modifier::None {
+                match crate::wm::focus_app(crate::wm::detect().as_ref(), &trusted.id, ctx) {
+                    Ok(true) => return Ok(()),
+                    Ok(false) => {}
+                    Err(e) => {
+                        tracing::warn!(id = %trusted.id, error = %e, "focus failed, launching")
+                    }
+                }
+            }
+            apps::launch_app_by_id(&trusted.id, &[], ctx)
         }
-        Category::Ssh => handle_ssh(&trusted, modifier),
+        Category::Special => handle_launch(&trusted, ctx, secondary_input),
+        Category::Ssh => unreachable!("SSH results are handled above"),
         Category::Math => handle_math(&trusted, modifier),
+        Category::Window => {
+            ctx.hide_window();
+            windows::act_on_window(
+                crate::wm::detect().as_ref(),
+                &trusted.id,
+                windows::WindowAction::from_modifier(modifier),
+            )
+        }
         Category::Info => Ok(()),
         Category::Chat => Ok(()), // Handled by frontend
     }
@@ -117,19 +163,24 @@ impl VaultLoadResult {
 fn handle_onepass(
     result: &SearchResult,
     modifier: Modifier,
+    secondary_input: Option<&str>,
     ctx: &AppContext,
 ) -> Result<(), String> {
     if result.exec == "op-load-vault" {
-        // Spawn in a thread because load_vault does blocking I/O + stdin prompts
+        // The secondary input is the master password, if one was asked for
+        let password = secondary_input
+            .filter(|p| !p.is_empty())
+            .map(|p| Zeroizing::new(p.to_string()));
+        // Spawn in a thread because load_vault does blocking I/O
         let app_handle = ctx.clone_app_handle();
         std::thread::spawn(move || {
-            let payload = match onepass::load_vault() {
+            let payload = match onepass::load_vault(password.as_deref().map(String::as_str)) {
                 Ok(msg) => {
-                    tracing::info!(message = %msg, "1Password vault loaded");
+                    tracing::info!(message = %msg, "password vault loaded");
                     VaultLoadResult::success(msg)
                 }
                 Err(e) => {
-                    tracing::warn!(error = %e, "1Password vault load failed");
+                    tracing::warn!(error = %e, "password vault load failed");
                     VaultLoadResult::failure(e)
                 }
             };
@@ -150,14 +201,29 @@ fn handle_onepass(
         .strip_prefix("op-vault-item:")
         .ok_or_else(|| "Could not extract 1Password item ID".to_string())?;
 
-    ctx.hide_window();
+    if let Some(query) = secondary_input.map(str::trim).filter(|q| !q.is_empty()) {
+        // Resolved before hiding so an unknown or ambiguous label is reported
+        let (label, value) = onepass::get_field(item_id, query)?;
+        ctx.hide_window();
+        if modifier == Modifier::Shift {
+            std::thread::spawn(move || {
+                if let Err(e) = copy_vault_secret(&value) {
+                    tracing::warn!(field = %label, error = %e, "vault copy field failed");
+                }
+            });
+        } else {
+            type_vault_steps(vec![autotype::Step::Text(value)]);
+        }
+        return Ok(());
+    }
 
     match modifier {
         Modifier::Shift => {
+            ctx.hide_window();
             let id = item_id.to_string();
             std::thread::spawn(move || match onepass::get_password(&id) {
                 Ok(pw) => {
-                    if let Err(e) = utils::copy_to_clipboard(&pw) {
+                    if let Err(e) = copy_vault_secret(&pw) {
                         tracing::warn!(error = %e, "1Password copy password failed");
                     }
                 }
@@ -166,10 +232,11 @@ fn handle_onepass(
             Ok(())
         }
         Modifier::Ctrl => {
+            ctx.hide_window();
             let id = item_id.to_string();
             std::thread::spawn(move || match onepass::get_username(&id) {
                 Ok(user) => {
-                    if let Err(e) = utils::copy_to_clipboard(&user) {
+                    if let Err(e) = copy_vault_secret(&user) {
                         tracing::warn!(error = %e, "1Password copy username failed");
                     }
                 }
@@ -177,26 +244,67 @@ fn handle_onepass(
             });
             Ok(())
         }
-        _ => {
+        Modifier::Alt => {
+            ctx.hide_window();
             let id = item_id.to_string();
-            std::thread::spawn(move || match onepass::get_password(&id) {
-                Ok(pw) => {
-                    std::thread::sleep(std::time::Duration::from_secs(1));
-                    if let Err(e) = std::process::Command::new("wtype")
-                        .arg("--")
-                        .arg(&pw)
-                        .status()
-                    {
-                        tracing::warn!(error = %e, "wtype failed (is wtype installed?)");
+            std::thread::spawn(move || match onepass::get_totp(&id) {
+                Ok(code) => {
+                    if let Err(e) = copy_vault_secret(&code) {
+                        tracing::warn!(error = %e, "vault copy one-time password failed");
                     }
                 }
-                Err(e) => tracing::warn!(error = %e, "1Password get password failed"),
+                Err(e) => tracing::warn!(error = %e, "vault one-time password failed"),
             });
             Ok(())
         }
+        _ => {
+            // Filled in before hiding so a missing field or one-time password is reported
+            let config = &crate::config::get_config().onepass;
+            let template = config
+                .autotype_items
+                .get(item_id)
+                .or_else(|| config.autotype_items.get(&result.name))
+                .unwrap_or(&config.autotype);
+            let tokens = autotype::parse(template)?;
+            let steps = autotype::steps(&tokens, |token| vault_value(item_id, token))?;
+            ctx.hide_window();
+            type_vault_steps(steps);
+            Ok(())
+        }
+    }
+}
+
+/// The vault value an auto-type placeholder stands for.
+fn vault_value(item_id: &str, token: &autotype::Token) -> Result<Zeroizing<String>, String> {
+    match token {
+        autotype::Token::Username => onepass::get_username(item_id),
+        autotype::Token::Password => onepass::get_password(item_id),
+        autotype::Token::Totp => onepass::get_totp(item_id),
+        autotype::Token::Field(label) => onepass::get_field(item_id, label).map(|(_, v)| v),
+        other => Err(format!("{other:?} is not a vault value")),
     }
 }
 
+/// Type into the window that regains focus once Burrow hides, after
+/// `onepass.autotype_delay_ms`.
+fn type_vault_steps(steps: Vec<autotype::Step>) {
+    let delay =
+        std::time::Duration::from_millis(crate::config::get_config().onepass.autotype_delay_ms);
+    std::thread::spawn(move || {
+        if let Err(e) = autotype::run(&steps, delay) {
+            tracing::warn!(error = %e, "vault auto-type failed");
+        }
+    });
+}
+
+/// Copy a vault secret, leaving the clipboard after `onepass.clipboard_clear_secs`.
+fn copy_vault_secret(secret: &str) -> Result<(), String> {
+    let config = &crate::config::get_config().onepass;
+    let clear_after = (config.clipboard_clear_secs > 0)
+        .then(|| std::time::Duration::from_secs(config.clipboard_clear_secs.into()));
+    secret_clipboard::copy_secret(secret, clear_after, config.clipboard_restore)
+}
+
 fn handle_file(result: &SearchResult, modifier: Modifier, ctx: &AppContext) -> Result<(), String> {
     let path = &result.id; // file/vector results use id as the path
     ctx.hide_window();
@@ -261,28 +369,65 @@ fn handle_launch(
                 Err("OutputMode::Window requires a Tauri AppHandle (not available in test-server mode)".to_string())
             }
         }
-        _ => utils::exec_shell(&cmd),
+        _ => utils::exec_shell(&result.id, &cmd),
     }
 }
 
-fn handle_ssh(result: &SearchResult, modifier: Modifier) -> Result<(), String> {
+fn handle_ssh(
+    result: &SearchResult,
+    ssh_host: ssh::SshHost,
+    modifier: Modifier,
+    secondary_input: Option<&str>,
+    ctx: &AppContext,
+) -> Result<(), String> {
     // Data contract: exec = Host alias only, description = "user@hostname" or "hostname"
     // This avoids shell interpolation by passing the alias directly to Command::arg()
     let host = &result.exec;
     let user = extract_user_from_description(&result.description);
 
+    if let Some(input) = secondary_input.map(str::trim).filter(|i| !i.is_empty()) {
+        if let Some(forward) = input.strip_prefix("-L") {
+            // Port forward: stays up in the terminal until it is closed
+            let spec = ssh::forward_spec(forward)?;
+            ctx.hide_window();
+            return utils::exec_ssh(host, user.as_deref(), &["-N", "-L", &spec]);
+        }
+        // One-off command through the host's input_spec, output in a Burrow window
+        let command = SearchResult {
+            output_mode: Some(OutputMode::Window),
+            ..result.clone()
+        };
+        return handle_launch(&command, ctx, Some(input));
+    }
+
     match modifier {
+        Modifier::Shift => {
+            // Browse the host in the file manager
+            ctx.hide_window();
+            utils::xdg_open(&ssh_host.sftp_uri())
+        }
         Modifier::Ctrl => {
             // Copy "ssh user@host" to clipboard
-            let target = match &user {
-                Some(u) => format!("{}@{}", u, host),
-                None => host.clone(),
-            };
+            let target = utils::ssh_destination(host, user.as_deref());
             utils::copy_to_clipboard(&format!("ssh {target}"))
         }
+        Modifier::Alt => {
+            ssh_host.ensure_direct()?;
+            // The DNS lookup can block for seconds
+            std::thread::spawn(move || {
+                let copied = ssh_host
+                    .ip_address()
+                    .and_then(|ip| utils::copy_to_clipboard(&ip.to_string()));
+                if let Err(e) = copied {
+                    tracing::warn!(host = %ssh_host.name, error = %e, "copy SSH host IP failed");
+                }
+            });
+            Ok(())
+        }
         _ => {
-            // Default + Shift: launch SSH connection safely (no shell interpolation)
-            utils::exec_ssh(host, user.as_deref())
+            // Launch SSH connection safely (no shell interpolation)
+            ctx.hide_window();
+            utils::exec_ssh(host, user.as_deref(), &[])
         }
     }
 }
@@ -301,7 +446,10 @@ fn handle_math(result: &SearchResult, modifier: Modifier) -> Result<(), String>
 /// Extract the user from SSH description (format: "user@hostname" or "hostname").
 /// Returns Some(user) if present, None otherwise.
 fn extract_user_from_description(description: &str) -> Option<String> {
+    // Only the destination part: "deploy@db:2222 via admin@bastion"
     description
+        .split_whitespace()
+        .next()?
         .split_once('@')
         .map(|(user, _)| user.to_string())
 }
@@ -359,6 +507,18 @@ mod tests {
         assert_eq!(extract_user_from_description(desc), None);
     }
 
+    #[test]
+    fn extract_user_from_description_ignores_jump_host() {
+        assert_eq!(
+            extract_user_from_description("db.internal:2222 via admin@bastion"),
+            None
+        );
+        assert_eq!(
+            extract_user_from_description("deploy@db via admin@bastion"),
+            Some("deploy".to_string())
+        );
+    }
+
     #[test]
     fn extract_user_from_description_hostname() {
         let desc = "deploy@example.com";
@@ -402,6 +562,7 @@ mod tests {
             Category::Math,
             Category::Info,
             Category::Special,
+            Category::Window,
         ];
         for cat in categories {
             assert!(is_valid_category(cat), "{cat:?} should be valid");
@@ -426,6 +587,36 @@ mod tests {
         assert!(err.contains("Unknown app id"));
     }
 
+    #[test]
+    fn resolve_trusted_result_derives_desktop_action_exec() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("firefox.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Firefox\nExec=firefox %u\n\
+             Actions=new-private-window;\n\n\
+             [Desktop Action new-private-window]\nName=New Private Window\n\
+             Exec=firefox --private-window %u\n",
+        )
+        .unwrap();
+        let apps = Arc::new(apps::AppIndexState::new_for_test(vec![dir
+            .path()
+            .to_path_buf()]));
+        let ctx = ctx_with_apps(apps);
+        let forged = SearchResult {
+            id: "firefox#new-private-window".into(),
+            name: "Firefox: New Private Window".into(),
+            description: "".into(),
+            icon: "".into(),
+            category: Category::History,
+            exec: "rm -rf /".into(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        };
+        let trusted = resolve_trusted_result(&forged, &ctx).expect("desktop action should resolve");
+        assert_eq!(trusted.exec, "firefox --private-window");
+    }
+
     #[test]
     fn resolve_trusted_result_overrides_special_exec() {
         let ctx = in_memory_ctx();
@@ -439,6 +630,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "p".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -463,6 +656,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "p".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -472,6 +667,56 @@ mod tests {
         assert!(trusted.input_spec.is_none());
     }
 
+    #[test]
+    fn resolve_trusted_result_rebuilds_files_and_rejects_missing_ones() {
+        let ctx = in_memory_ctx();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("notes.md");
+        fs::write(&path, "notes").unwrap();
+        let forged = SearchResult {
+            id: path.display().to_string(),
+            name: "Forged".into(),
+            description: "".into(),
+            icon: "".into(),
+            category: Category::Vector,
+            exec: "rm -rf /".into(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        };
+        let trusted = resolve_trusted_result(&forged, &ctx).expect("existing file should resolve");
+        assert_eq!(trusted.exec, "");
+        assert_eq!(trusted.name, "notes.md");
+        assert_eq!(trusted.category, Category::Vector);
+
+        fs::remove_file(&path).unwrap();
+        let err = resolve_trusted_result(&forged, &ctx).unwrap_err();
+        assert!(err.contains("no longer exists"), "{err}");
+        let relative = SearchResult {
+            id: "notes.md".into(),
+            ..forged
+        };
+        assert!(resolve_trusted_result(&relative, &ctx).is_err());
+    }
+
+    #[test]
+    fn resolve_trusted_result_rejects_unknown_ssh_host() {
+        let ctx = in_memory_ctx();
+        let forged = SearchResult {
+            id: "ssh-burrow-test-no-such-host".into(),
+            name: "box".into(),
+            description: "root@evil.example".into(),
+            icon: "".into(),
+            category: Category::Ssh,
+            exec: "-oProxyCommand=sh".into(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        };
+        let err = resolve_trusted_result(&forged, &ctx).unwrap_err();
+        assert!(err.contains("Unknown SSH host"), "{err}");
+    }
+
     #[test]
     fn handle_action_refreshes_app_cache_for_special_refresh() {
         let dir = tempdir().unwrap();
@@ -503,6 +748,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn handle_action_rejects_clipboard_ai_special() {
+        let ctx = in_memory_ctx();
+        let result = SearchResult {
+            id: "special-summarize".into(),
+            name: "summarize".into(),
+            description: "".into(),
+            icon: "".into(),
+            category: Category::Special,
+            exec: "rm -rf /".into(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        };
+
+        let err = handle_action(&result, Modifier::None, None, &ctx).unwrap_err();
+        assert!(err.contains("clipboard_ai"), "{err}");
+    }
+
     #[test]
     fn vault_load_result_success_constructs_correctly() {
         let result = VaultLoadResult::success("Loaded 42 items from vault");
@@ -610,6 +874,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "Enter value".into(),
                 template: "templated-command {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -638,6 +904,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "Enter value".into(),
                 template: "templated-command {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -662,6 +930,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -687,6 +957,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -711,6 +983,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -732,6 +1006,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -753,6 +1029,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "".into(),
                 template: "echo {}".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -774,6 +1052,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "Enter input".into(),
                 template: "broken-template-no-placeholder".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -0,0 +1,323 @@
+//! Launch backends for apps and shell commands (`[launch] backend` in config).
+//!
+//! - `direct`: a plain child of Burrow (shares its cgroup and session).
+//! - `detached`: double fork + `setsid`, so the app is reparented away from Burrow.
+//! - `systemd`: each launch runs in its own transient `systemd-run --user --scope`
+//!   unit named after the app id, so it is accounted separately and can be
+//!   listed with [`running_app_ids`]. Falls back to `detached` when there is no
+//!   systemd user manager.
+
+use std::collections::HashSet;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::OnceLock;
+
+/// Launcher name in unit names, per systemd's `app-<launcher>-<app id>-<random>.scope`
+/// convention for desktop environments.
+const UNIT_PREFIX: &str = "app-burrow-";
+const UNIT_SUFFIX: &str = ".scope";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Direct,
+    Detached,
+    Systemd,
+}
+
+impl Backend {
+    fn from_config() -> Self {
+        let backend = crate::config::try_get_config()
+            .map(|c| c.launch.backend.as_str())
+            .unwrap_or("direct");
+        match backend {
+            "detached" => Self::Detached,
+            "systemd" => Self::Systemd,
+            _ => Self::Direct,
+        }
+    }
+}
+
+/// Start `argv` (no shell) for `app_id` with the configured backend.
+pub fn spawn(app_id: &str, argv: &[String], working_dir: Option<&Path>) -> Result<(), String> {
+    spawn_with(Backend::from_config(), app_id, argv, working_dir)
+}
+
+fn spawn_with(
+    backend: Backend,
+    app_id: &str,
+    argv: &[String],
+    working_dir: Option<&Path>,
+) -> Result<(), String> {
+    let (program, _) = argv.split_first().ok_or("Empty command")?;
+    let home = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/"));
+    let cwd = working_dir.unwrap_or(&home);
+
+    let backend = if backend == Backend::Systemd && !systemd_user_available() {
+        tracing::debug!(app_id, "no systemd user manager, launching detached");
+        Backend::Detached
+    } else {
+        backend
+    };
+
+    let full_argv = match backend {
+        Backend::Systemd => systemd_run_argv(&unit_name(app_id, &unique_suffix()), argv),
+        Backend::Direct | Backend::Detached => argv.to_vec(),
+    };
+    let mut cmd = Command::new(&full_argv[0]);
+    cmd.args(&full_argv[1..]).current_dir(cwd);
+    tracing::debug!(app_id, ?backend, argv = ?full_argv, "spawning");
+
+    let result = match backend {
+        Backend::Direct => cmd.spawn().map(drop),
+        Backend::Detached | Backend::Systemd => spawn_detached(&mut cmd),
+    };
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if backend == Backend::Systemd && e.kind() == std::io::ErrorKind::NotFound => {
+            tracing::warn!(app_id, "systemd-run not found, launching detached");
+            spawn_with(Backend::Detached, app_id, argv, working_dir)
+        }
+        Err(e) => Err(format!("Failed to launch {program}: {e}")),
+    }
+}
+
+/// Double fork: the intermediate child starts a new session, forks the process
+/// that execs `cmd`, and exits immediately so the app is reparented to init (or
+/// the nearest subreaper) instead of staying a child of Burrow.
+fn spawn_detached(cmd: &mut Command) -> std::io::Result<()> {
+    // SAFETY: only async-signal-safe calls (setsid, fork, _exit) between fork and exec.
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            match libc::fork() {
+                -1 => Err(std::io::Error::last_os_error()),
+                0 => Ok(()),
+                _ => libc::_exit(0),
+            }
+        });
+    }
+    // spawn() returns once the grandchild has exec'd (or reports its exec error);
+    // reap the intermediate child right away.
+    let mut child = cmd.spawn()?;
+    child.wait()?;
+    Ok(())
+}
+
+fn systemd_run_argv(unit: &str, argv: &[String]) -> Vec<String> {
+    let mut full = vec![
+        "systemd-run".to_string(),
+        "--user".into(),
+        "--scope".into(),
+        "--quiet".into(),
+        "--collect".into(),
+        format!("--unit={unit}"),
+        "--".into(),
+    ];
+    full.extend(argv.iter().cloned());
+    full
+}
+
+/// A user manager is reachable when its private socket exists.
+fn systemd_user_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        std::env::var_os("XDG_RUNTIME_DIR")
+            .map(|dir| Path::new(&dir).join("systemd/private").exists())
+            .unwrap_or(false)
+    })
+}
+
+fn unique_suffix() -> String {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    format!("{:x}{nanos:x}{count:x}", std::process::id())
+}
+
+/// `app-burrow-<escaped app id>-<suffix>.scope`. `-` separates the name parts,
+/// so it is escaped inside the app id like any other non-unit character.
+fn unit_name(app_id: &str, suffix: &str) -> String {
+    format!(
+        "{UNIT_PREFIX}{}-{suffix}{UNIT_SUFFIX}",
+        escape_unit_part(app_id)
+    )
+}
+
+/// systemd-style escaping: keep `[A-Za-z0-9:_.]`, encode everything else as `\xNN`.
+fn escape_unit_part(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b':' | b'_' | b'.') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("\\x{byte:02x}"));
+        }
+    }
+    out
+}
+
+fn unescape_unit_part(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'\\' {
+            let hex = tail.get(1..3).filter(|_| tail.first() == Some(&b'x'))?;
+            bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+            rest = &tail[3..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Recover the app id from a unit started by Burrow.
+fn app_id_from_unit(unit: &str) -> Option<String> {
+    let middle = unit.strip_prefix(UNIT_PREFIX)?.strip_suffix(UNIT_SUFFIX)?;
+    let (escaped_id, _suffix) = middle.rsplit_once('-')?;
+    unescape_unit_part(escaped_id).filter(|id| !id.is_empty())
+}
+
+/// App ids with a running scope unit (only populated by the `systemd` backend).
+pub fn running_app_ids() -> HashSet<String> {
+    if !systemd_user_available() {
+        return HashSet::new();
+    }
+    let output = Command::new("systemctl")
+        .args([
+            "--user",
+            "list-units",
+            "--type=scope",
+            "--state=running",
+            "--plain",
+            "--no-legend",
+        ])
+        .arg(format!("{UNIT_PREFIX}*"))
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_running_units(&String::from_utf8_lossy(&output.stdout))
+        }
+        Ok(output) => {
+            tracing::debug!(status = %output.status, "systemctl list-units failed");
+            HashSet::new()
+        }
+        Err(e) => {
+            tracing::debug!(error = %e, "systemctl unavailable");
+            HashSet::new()
+        }
+    }
+}
+
+/// Parse `systemctl list-units --plain --no-legend` lines (unit name first).
+fn parse_running_units(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter_map(app_id_from_unit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_names_escape_app_ids() {
+        assert_eq!(
+            unit_name("firefox", "1a2b"),
+            "app-burrow-firefox-1a2b.scope"
+        );
+        assert_eq!(
+            unit_name("org.gnome-Nautilus", "ff"),
+            "app-burrow-org.gnome\\x2dNautilus-ff.scope"
+        );
+        assert_eq!(
+            unit_name("firefox#new window", "1"),
+            "app-burrow-firefox\\x23new\\x20window-1.scope"
+        );
+    }
+
+    #[test]
+    fn app_ids_round_trip_through_unit_names() {
+        for id in [
+            "firefox",
+            "org.gnome-Nautilus",
+            "firefox#new-window",
+            "caf\u{e9}",
+        ] {
+            assert_eq!(app_id_from_unit(&unit_name(id, "abc")).as_deref(), Some(id));
+        }
+        assert_eq!(app_id_from_unit("app-gnome-firefox-123.scope"), None);
+        assert_eq!(app_id_from_unit("app-burrow-bad\\x2-1.scope"), None);
+        assert_eq!(app_id_from_unit("app-burrow--1.scope"), None);
+    }
+
+    #[test]
+    fn parses_running_units() {
+        let output = "\
+app-burrow-firefox-4d2a.scope        loaded active running firefox
+app-burrow-org.gnome\\x2dNautilus-11.scope loaded active running Nautilus
+session-2.scope                      loaded active running Session 2
+";
+        let ids = parse_running_units(output);
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains("firefox"));
+        assert!(ids.contains("org.gnome-Nautilus"));
+    }
+
+    #[test]
+    fn systemd_run_wraps_argv_after_separator() {
+        let argv = vec!["foot".to_string(), "--title".into(), "x".into()];
+        assert_eq!(
+            systemd_run_argv("app-burrow-foot-1.scope", &argv),
+            vec![
+                "systemd-run",
+                "--user",
+                "--scope",
+                "--quiet",
+                "--collect",
+                "--unit=app-burrow-foot-1.scope",
+                "--",
+                "foot",
+                "--title",
+                "x"
+            ]
+        );
+    }
+
+    #[test]
+    fn detached_spawn_runs_and_reports_missing_programs() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        let argv = vec![
+            "sh".to_string(),
+            "-c".into(),
+            format!("touch '{}'", marker.display()),
+        ];
+        spawn_with(Backend::Detached, "sh", &argv, Some(dir.path())).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !marker.exists() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(marker.exists(), "detached command should have run");
+
+        let err = spawn_with(
+            Backend::Detached,
+            "missing",
+            &["__nonexistent_binary_12345__".to_string()],
+            None,
+        )
+        .unwrap_err();
+        assert!(err.contains("__nonexistent_binary_12345__"), "{err}");
+        assert!(spawn_with(Backend::Direct, "x", &[], None).is_err());
+    }
+}
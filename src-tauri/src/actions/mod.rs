@@ -1,7 +1,10 @@
+pub mod autotype;
 pub mod dry_run;
 pub mod handlers;
+pub mod launch;
 pub mod modifier;
 pub mod output_window;
+pub mod secret_clipboard;
 pub mod utils;
 
 use crate::context::AppContext;
@@ -0,0 +1,215 @@
+//! Copying secrets to the Wayland clipboard.
+//!
+//! Secrets go to `wl-copy` on stdin rather than argv, marked sensitive when
+//! the installed wl-copy supports `--sensitive` (clipboard managers then skip
+//! them). A timer later puts back the text the clipboard held before, or
+//! clears it, but only while the clipboard still holds the secret: anything
+//! copied in the meantime is left alone.
+
+use super::dry_run;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// A copied secret waiting for its timer, and the text to put back.
+struct Pending {
+    generation: u64,
+    secret: Zeroizing<String>,
+    previous: Zeroizing<String>,
+}
+
+static PENDING: Mutex<Option<Pending>> = Mutex::new(None);
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static SENSITIVE_FLAG: OnceLock<bool> = OnceLock::new();
+
+/// What the timer does with the clipboard.
+#[derive(Debug, PartialEq, Eq)]
+enum Settle {
+    /// Something else was copied since; leave it
+    Keep,
+    Restore,
+    Clear,
+}
+
+fn settle(current: &str, secret: &str, previous: &str, restore: bool) -> Settle {
+    if current != secret {
+        Settle::Keep
+    } else if restore && !previous.is_empty() {
+        Settle::Restore
+    } else {
+        Settle::Clear
+    }
+}
+
+/// The text to put back once a new secret replaces `current`. When `current`
+/// is an earlier secret still waiting for its timer, the text that one saved
+/// is kept instead, so the timers never restore a secret.
+fn previous_text(current: Zeroizing<String>, pending: Option<Pending>) -> Zeroizing<String> {
+    match pending {
+        Some(pending) if *pending.secret == *current => pending.previous,
+        _ => current,
+    }
+}
+
+fn supports_sensitive() -> bool {
+    *SENSITIVE_FLAG.get_or_init(|| {
+        Command::new("wl-copy")
+            .arg("--help")
+            .output()
+            .map(|out| {
+                String::from_utf8_lossy(&out.stdout).contains("--sensitive")
+                    || String::from_utf8_lossy(&out.stderr).contains("--sensitive")
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Put `text` on the clipboard through wl-copy's stdin.
+fn offer(text: &str, sensitive: bool) -> Result<(), String> {
+    let mut cmd = Command::new("wl-copy");
+    if sensitive && supports_sensitive() {
+        cmd.arg("--sensitive");
+    }
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to copy to clipboard: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to copy to clipboard: {e}"))?;
+    }
+    // wl-copy forks to serve the selection; the foreground process exits
+    // as soon as it has read stdin
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to copy to clipboard: {e}"))?;
+    if !status.success() {
+        return Err(format!("wl-copy exited with {status}"));
+    }
+    Ok(())
+}
+
+/// The clipboard's text, or empty when it holds none (images, nothing).
+fn read_text() -> Zeroizing<String> {
+    let output = Command::new("wl-paste")
+        .args(["--no-newline", "--type", "text"])
+        .stderr(Stdio::null())
+        .output();
+    match output {
+        Ok(out) if out.status.success() => {
+            let bytes = Zeroizing::new(out.stdout);
+            Zeroizing::new(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        _ => Zeroizing::new(String::new()),
+    }
+}
+
+fn clear() -> Result<(), String> {
+    let status = Command::new("wl-copy")
+        .arg("--clear")
+        .status()
+        .map_err(|e| format!("Failed to clear clipboard: {e}"))?;
+    if !status.success() {
+        return Err(format!("wl-copy --clear exited with {status}"));
+    }
+    Ok(())
+}
+
+/// Copy a secret. With `clear_after`, the clipboard is restored (`restore`)
+/// or cleared that long afterwards if it still holds the secret.
+pub fn copy_secret(
+    secret: &str,
+    clear_after: Option<Duration>,
+    restore: bool,
+) -> Result<(), String> {
+    if dry_run::is_enabled() {
+        return dry_run::copy_secret(clear_after);
+    }
+    let Some(delay) = clear_after else {
+        return offer(secret, true);
+    };
+
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let current = if restore {
+        read_text()
+    } else {
+        Zeroizing::new(String::new())
+    };
+    offer(secret, true)?;
+    let generation = GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    let previous = previous_text(current, pending.take());
+    *pending = Some(Pending {
+        generation,
+        secret: Zeroizing::new(secret.to_string()),
+        previous,
+    });
+    drop(pending);
+
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+        // A later copy took over this one's timer
+        let Some(entry) = pending.take_if(|p| p.generation == generation) else {
+            return;
+        };
+        let result = match settle(&read_text(), &entry.secret, &entry.previous, restore) {
+            Settle::Keep => Ok(()),
+            Settle::Restore => offer(&entry.previous, false),
+            Settle::Clear => clear(),
+        };
+        match result {
+            Ok(()) => tracing::debug!("secret clipboard timer done"),
+            Err(e) => tracing::warn!(error = %e, "failed to clear copied secret"),
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(secret: &str, previous: &str) -> Option<Pending> {
+        Some(Pending {
+            generation: 1,
+            secret: Zeroizing::new(secret.into()),
+            previous: Zeroizing::new(previous.into()),
+        })
+    }
+
+    #[test]
+    fn timer_only_touches_its_own_secret() {
+        assert_eq!(settle("hunter2", "hunter2", "notes", true), Settle::Restore);
+        assert_eq!(settle("hunter2", "hunter2", "notes", false), Settle::Clear);
+        // Nothing to restore: clear rather than leave the secret
+        assert_eq!(settle("hunter2", "hunter2", "", true), Settle::Clear);
+        assert_eq!(
+            settle("copied later", "hunter2", "notes", true),
+            Settle::Keep
+        );
+        assert_eq!(settle("", "hunter2", "notes", false), Settle::Keep);
+    }
+
+    #[test]
+    fn back_to_back_secrets_restore_the_original_text() {
+        let current = Zeroizing::new("hunter2".to_string());
+        assert_eq!(
+            *previous_text(current, pending("hunter2", "notes")),
+            "notes"
+        );
+        // Text copied after the first secret is what the second one restores
+        let current = Zeroizing::new("copied later".to_string());
+        assert_eq!(
+            *previous_text(current, pending("hunter2", "notes")),
+            "copied later"
+        );
+        let current = Zeroizing::new("notes".to_string());
+        assert_eq!(*previous_text(current, None), "notes");
+    }
+}
@@ -1,18 +1,27 @@
-use super::dry_run;
+use super::{dry_run, launch};
 use std::process::Command;
 
-/// Run a shell command string via `sh -c`.
-pub fn exec_shell(cmd: &str) -> Result<(), String> {
+/// Run a shell command string via `sh -c` with the configured launch backend.
+/// `id` names the launch (e.g. the result id) for the systemd unit.
+pub fn exec_shell(id: &str, cmd: &str) -> Result<(), String> {
     if dry_run::is_enabled() {
         return dry_run::exec_shell(cmd);
     }
-    Command::new("sh")
-        .arg("-c")
-        .arg(cmd)
-        .current_dir(dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/")))
-        .spawn()
-        .map_err(|e| format!("Failed to exec: {e}"))?;
-    Ok(())
+    let argv = ["sh".to_string(), "-c".into(), cmd.into()];
+    launch::spawn(id, &argv, None)
+}
+
+/// Spawn a command directly (no shell) in `working_dir`, defaulting to the home
+/// directory, with the configured launch backend.
+pub fn spawn_command(
+    app_id: &str,
+    argv: &[String],
+    working_dir: Option<&std::path::Path>,
+) -> Result<(), String> {
+    if dry_run::is_enabled() {
+        return dry_run::spawn_command(argv, working_dir);
+    }
+    launch::spawn(app_id, argv, working_dir)
 }
 
 /// Copy text to clipboard using wl-copy.
@@ -28,6 +37,24 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Read the clipboard (or the primary selection) using wl-paste.
+/// Reading has no side effects, so this is not gated by dry-run.
+pub fn read_clipboard(primary: bool) -> Result<String, String> {
+    let mut cmd = Command::new("wl-paste");
+    cmd.arg("--no-newline");
+    if primary {
+        cmd.arg("--primary");
+    }
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to read clipboard: {e}"))?;
+    if !output.status.success() {
+        // wl-paste exits non-zero when the selection is empty
+        return Ok(String::new());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 /// Type text via wtype (Wayland). Hides window first, sleeps 1s, then types.
 pub fn type_text_wayland(text: &str, app: &tauri::AppHandle) -> Result<(), String> {
     if dry_run::is_enabled() {
@@ -62,22 +89,27 @@ pub fn hide_window(app: &tauri::AppHandle) {
     }
 }
 
-/// Launch SSH connection safely without shell interpolation.
-/// Uses Command::arg() to prevent shell injection.
-pub fn exec_ssh(host: &str, user: Option<&str>) -> Result<(), String> {
+/// The ssh destination for `host`: `user@host`, or `ssh://user@[host]:port` for an `ssh://` host.
+pub fn ssh_destination(host: &str, user: Option<&str>) -> String {
+    match (user, host.strip_prefix("ssh://")) {
+        (Some(u), Some(rest)) => format!("ssh://{u}@{rest}"),
+        (Some(u), None) => format!("{u}@{host}"),
+        (None, _) => host.to_string(),
+    }
+}
+
+/// Run `ssh [options] -- destination` in the terminal.
+pub fn exec_ssh(host: &str, user: Option<&str>, options: &[&str]) -> Result<(), String> {
     if dry_run::is_enabled() {
-        return dry_run::exec_ssh(host, user);
+        return dry_run::exec_ssh(host, user, options);
     }
     let terminal = get_terminal_cmd();
     let mut cmd = Command::new(&terminal);
     cmd.current_dir(dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("/")));
     cmd.arg("ssh");
+    cmd.args(options);
     cmd.arg("--"); // Prevent option injection (e.g., host starting with "-")
-    if let Some(u) = user {
-        cmd.arg(format!("{}@{}", u, host));
-    } else {
-        cmd.arg(host);
-    }
+    cmd.arg(ssh_destination(host, user));
     cmd.spawn()
         .map_err(|e| format!("Failed to launch SSH: {e}"))?;
     Ok(())
@@ -125,10 +157,48 @@ pub fn open_in_vscode(path: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Write `content` to a new file. Refuses to overwrite existing files or create
+/// missing parent directories.
+pub fn write_new_file(path: &std::path::Path, content: &str) -> Result<(), String> {
+    if dry_run::is_enabled() {
+        return dry_run::write_new_file(path, content);
+    }
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn ssh_destination_adds_user_to_hosts_and_uris() {
+        assert_eq!(ssh_destination("box", None), "box");
+        assert_eq!(ssh_destination("box", Some("me")), "me@box");
+        assert_eq!(
+            ssh_destination("ssh://[10.0.0.5]:2222", Some("me")),
+            "ssh://me@[10.0.0.5]:2222"
+        );
+    }
+
+    #[test]
+    fn write_new_file_refuses_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snippet.sh");
+        write_new_file(&path, "echo hi\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "echo hi\n");
+
+        let err = write_new_file(&path, "echo bye\n").unwrap_err();
+        assert!(err.contains("Failed to create"), "{err}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "echo hi\n");
+    }
+
     // Note: Environment variable tests are inherently flaky when run in parallel
     // because env vars are process-global. Run with --test-threads=1 for reliability.
     #[test]
@@ -0,0 +1,299 @@
+//! Splits chat answers into text and fenced code blocks so the frontend can offer
+//! per-block actions (copy, run in output window, save to file).
+//!
+//! Blocks are remembered server-side by id. Run/save requests reference a block id,
+//! so clients can never hand the backend an arbitrary command or file body.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use serde::Serialize;
+
+/// How many recent code blocks stay resolvable for actions.
+const MAX_REMEMBERED_BLOCKS: usize = 64;
+
+const SHELL_LANGUAGES: &[&str] = &["sh", "bash", "zsh", "fish", "shell", "console", "terminal"];
+
+static NEXT_BLOCK_ID: AtomicU64 = AtomicU64::new(1);
+static RECENT_BLOCKS: LazyLock<Mutex<VecDeque<CodeBlock>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// A piece of a chat answer.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnswerPart {
+    Text {
+        text: String,
+    },
+    Code {
+        id: String,
+        language: Option<String>,
+        code: String,
+        /// Shell command that can be run in an output window
+        runnable: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    pub id: String,
+    pub language: Option<String>,
+    pub code: String,
+    pub runnable: bool,
+}
+
+impl CodeBlock {
+    /// File extension for "save to file", derived from the fence language.
+    pub fn extension(&self) -> &str {
+        match self.language.as_deref() {
+            Some(lang) if SHELL_LANGUAGES.contains(&lang) => "sh",
+            Some("python" | "py") => "py",
+            Some("rust" | "rs") => "rs",
+            Some("javascript" | "js") => "js",
+            Some("typescript" | "ts") => "ts",
+            Some("json") => "json",
+            Some("toml") => "toml",
+            Some("yaml" | "yml") => "yaml",
+            Some("markdown" | "md") => "md",
+            _ => "txt",
+        }
+    }
+}
+
+/// Parse an answer into parts and remember its code blocks for later actions.
+pub fn parse_answer(answer: &str) -> Vec<AnswerPart> {
+    let parts = split_answer(answer);
+    let blocks: Vec<CodeBlock> = parts
+        .iter()
+        .filter_map(|part| match part {
+            AnswerPart::Code {
+                id,
+                language,
+                code,
+                runnable,
+            } => Some(CodeBlock {
+                id: id.clone(),
+                language: language.clone(),
+                code: code.clone(),
+                runnable: *runnable,
+            }),
+            AnswerPart::Text { .. } => None,
+        })
+        .collect();
+    if !blocks.is_empty() {
+        let mut recent = RECENT_BLOCKS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for block in blocks {
+            if recent.len() == MAX_REMEMBERED_BLOCKS {
+                recent.pop_front();
+            }
+            recent.push_back(block);
+        }
+    }
+    parts
+}
+
+/// Look up a code block from a recent answer.
+pub fn find_block(id: &str) -> Option<CodeBlock> {
+    RECENT_BLOCKS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .iter()
+        .find(|block| block.id == id)
+        .cloned()
+}
+
+fn split_answer(answer: &str) -> Vec<AnswerPart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut lines = answer.lines();
+
+    while let Some(line) = lines.next() {
+        let Some(info) = line.trim_start().strip_prefix("```") else {
+            text.push_str(line);
+            text.push('\n');
+            continue;
+        };
+
+        let mut body: Vec<&str> = Vec::new();
+        let mut closed = false;
+        for inner in lines.by_ref() {
+            if inner.trim_start().starts_with("```") {
+                closed = true;
+                break;
+            }
+            body.push(inner);
+        }
+        if !closed {
+            // Unterminated fence: keep it as plain text rather than guessing
+            text.push_str(line);
+            text.push('\n');
+            for inner in body {
+                text.push_str(inner);
+                text.push('\n');
+            }
+            continue;
+        }
+
+        push_text(&mut parts, &mut text);
+        let language = info
+            .split_whitespace()
+            .next()
+            .map(str::to_lowercase)
+            .filter(|l| !l.is_empty());
+        let (code, runnable) = shell_command(language.as_deref(), &body)
+            .map(|cmd| (cmd, true))
+            .unwrap_or_else(|| (body.join("\n"), false));
+        parts.push(AnswerPart::Code {
+            id: format!("blk-{}", NEXT_BLOCK_ID.fetch_add(1, Ordering::Relaxed)),
+            language,
+            code,
+            runnable,
+        });
+    }
+    push_text(&mut parts, &mut text);
+    parts
+}
+
+fn push_text(parts: &mut Vec<AnswerPart>, text: &mut String) {
+    let trimmed = text.trim();
+    if !trimmed.is_empty() {
+        parts.push(AnswerPart::Text {
+            text: trimmed.to_string(),
+        });
+    }
+    text.clear();
+}
+
+/// Detect a shell command block. Shell-tagged fences are runnable as-is (minus `$ `
+/// prompts); untagged fences only when every line is a `$ ` prompt line.
+fn shell_command(language: Option<&str>, body: &[&str]) -> Option<String> {
+    let lines: Vec<&str> = body
+        .iter()
+        .copied()
+        .filter(|l| !l.trim().is_empty())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    let all_prompts = lines.iter().all(|l| l.trim_start().starts_with("$ "));
+    let is_shell = match language {
+        Some(lang) => SHELL_LANGUAGES.contains(&lang),
+        None => all_prompts,
+    };
+    if !is_shell {
+        return None;
+    }
+    if language == Some("console") || all_prompts {
+        // Console transcripts mix commands and output — keep only prompt lines
+        let commands: Vec<&str> = lines
+            .iter()
+            .filter_map(|l| l.trim_start().strip_prefix("$ "))
+            .collect();
+        return (!commands.is_empty()).then(|| commands.join("\n"));
+    }
+    Some(body.join("\n").trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_parts(parts: &[AnswerPart]) -> Vec<(Option<String>, String, bool)> {
+        parts
+            .iter()
+            .filter_map(|p| match p {
+                AnswerPart::Code {
+                    language,
+                    code,
+                    runnable,
+                    ..
+                } => Some((language.clone(), code.clone(), *runnable)),
+                AnswerPart::Text { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plain_answer_is_single_text_part() {
+        let parts = parse_answer("Rust is a systems language.\n\nIt is fast.");
+        assert_eq!(
+            parts,
+            vec![AnswerPart::Text {
+                text: "Rust is a systems language.\n\nIt is fast.".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn splits_text_and_code_blocks() {
+        let answer =
+            "List files:\n```bash\nls -la\n```\nThen in Rust:\n```rust\nfn main() {}\n```\nDone.";
+        let parts = parse_answer(answer);
+        assert_eq!(parts.len(), 5);
+        assert_eq!(
+            parts[0],
+            AnswerPart::Text {
+                text: "List files:".into()
+            }
+        );
+        assert_eq!(
+            code_parts(&parts),
+            vec![
+                (Some("bash".into()), "ls -la".into(), true),
+                (Some("rust".into()), "fn main() {}".into(), false),
+            ]
+        );
+        assert_eq!(
+            parts[4],
+            AnswerPart::Text {
+                text: "Done.".into()
+            }
+        );
+    }
+
+    #[test]
+    fn console_blocks_keep_only_commands() {
+        let answer = "```console\n$ echo hi\nhi\n$ date\n```";
+        assert_eq!(
+            code_parts(&parse_answer(answer)),
+            vec![(Some("console".into()), "echo hi\ndate".into(), true)]
+        );
+    }
+
+    #[test]
+    fn untagged_prompt_block_is_runnable() {
+        let answer = "```\n$ cargo build\n```";
+        assert_eq!(
+            code_parts(&parse_answer(answer)),
+            vec![(None, "cargo build".into(), true)]
+        );
+
+        let plain = "```\nsome output\n```";
+        assert_eq!(
+            code_parts(&parse_answer(plain)),
+            vec![(None, "some output".into(), false)]
+        );
+    }
+
+    #[test]
+    fn unterminated_fence_stays_text() {
+        let parts = parse_answer("Try:\n```sh\nrm -rf build");
+        assert!(code_parts(&parts).is_empty());
+        assert_eq!(parts.len(), 1);
+    }
+
+    #[test]
+    fn blocks_are_resolvable_by_id() {
+        let parts = parse_answer("```python\nprint('hi')\n```");
+        let AnswerPart::Code { id, .. } = &parts[0] else {
+            panic!("expected code part");
+        };
+        let block = find_block(id).expect("block should be remembered");
+        assert_eq!(block.code, "print('hi')");
+        assert_eq!(block.extension(), "py");
+        assert!(find_block("blk-does-not-exist").is_none());
+    }
+}
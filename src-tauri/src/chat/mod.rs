@@ -0,0 +1,617 @@
+use serde::Serialize;
+
+use crate::config::{self, ModelOptions, ModelSpec};
+use crate::context::AppContext;
+
+pub mod answer;
+pub mod persona;
+pub mod tools;
+
+use answer::AnswerPart;
+use persona::Persona;
+use tools::{PendingAction, ToolCall, ToolOutcome};
+
+const OPENROUTER_CHAT_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
+
+#[derive(Debug, Clone)]
+pub struct ContextSnippet {
+    pub path: String,
+    pub preview: String,
+}
+
+/// Chat answer plus any side-effectful actions the model proposed via tools.
+/// Actions are only executed once the user confirms them.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ChatReply {
+    pub answer: String,
+    /// The answer split into text and code blocks
+    pub parts: Vec<AnswerPart>,
+    pub actions: Vec<PendingAction>,
+}
+
+impl ChatReply {
+    pub fn new(answer: String, actions: Vec<PendingAction>) -> Self {
+        let parts = answer::parse_answer(&answer);
+        Self {
+            answer,
+            parts,
+            actions,
+        }
+    }
+}
+
+/// One model turn: either a final answer or a batch of tool calls.
+#[derive(Debug, Clone, PartialEq)]
+enum ModelTurn {
+    Answer(String),
+    ToolCalls {
+        /// Raw assistant message, echoed back into the conversation.
+        message: serde_json::Value,
+        calls: Vec<ToolCall>,
+    },
+}
+
+/// Generate chat response using configured model and provider
+pub async fn generate_chat(
+    query: &str,
+    context_snippets: &[ContextSnippet],
+    model_spec: &ModelSpec,
+) -> Result<String, String> {
+    generate_persona_chat(
+        query,
+        context_snippets,
+        &Persona::default_with(model_spec, !context_snippets.is_empty()),
+    )
+    .await
+}
+
+/// Generate chat response with a persona's system prompt, model and temperature
+pub async fn generate_persona_chat(
+    query: &str,
+    context_snippets: &[ContextSnippet],
+    persona: &Persona,
+) -> Result<String, String> {
+    let model_spec = &persona.model;
+    if crate::actions::dry_run::is_enabled() {
+        tracing::debug!(
+            query = %crate::actions::dry_run::truncate(query, 80),
+            model = %model_spec.name,
+            provider = %model_spec.provider,
+            persona = ?persona.name,
+            "[dry-run] generate_chat"
+        );
+        return Ok("[dry-run] Chat disabled during testing".into());
+    }
+
+    let messages = [
+        serde_json::json!({
+            "role": "system",
+            "content": persona.render_system_prompt(context_snippets)
+        }),
+        serde_json::json!({ "role": "user", "content": query }),
+    ];
+    match send_turn(model_spec, &persona.options(), &messages, None).await? {
+        ModelTurn::Answer(answer) => Ok(answer),
+        ModelTurn::ToolCalls { .. } => Err("Model requested tools but none were offered".into()),
+    }
+}
+
+/// Chat with large model (uses config routing)
+#[allow(dead_code)] // Reserved for future use
+pub async fn chat_large(query: &str, context: &[ContextSnippet]) -> Result<String, String> {
+    let cfg = config::get_config();
+    generate_chat(query, context, &cfg.models.chat_large).await
+}
+
+/// Chat with small model (uses config routing)
+#[allow(dead_code)] // Reserved for future use
+pub async fn chat_small(query: &str, context: &[ContextSnippet]) -> Result<String, String> {
+    let cfg = config::get_config();
+    generate_chat(query, context, &cfg.models.chat).await
+}
+
+/// Generate a chat response, letting the model call Burrow tools for up to
+/// `chat.max_tool_steps` round-trips. Launch/type tools are collected as
+/// pending actions instead of being executed.
+pub async fn generate_chat_with_tools(
+    query: &str,
+    context_snippets: &[ContextSnippet],
+    persona: &Persona,
+    ctx: &AppContext,
+) -> Result<ChatReply, String> {
+    let model_spec = &persona.model;
+    if crate::actions::dry_run::is_enabled() {
+        tracing::debug!(
+            query = %crate::actions::dry_run::truncate(query, 80),
+            model = %model_spec.name,
+            provider = %model_spec.provider,
+            "[dry-run] generate_chat_with_tools"
+        );
+        return Ok(ChatReply::new(
+            "[dry-run] Chat disabled during testing".into(),
+            vec![],
+        ));
+    }
+
+    let cfg = config::get_config();
+    let provider = model_spec.provider.as_str();
+    let options = persona.options();
+
+    let system_prompt = format!(
+        "{}\n\n{TOOL_PROMPT}",
+        persona.render_system_prompt(context_snippets)
+    );
+    let mut messages = vec![
+        serde_json::json!({ "role": "system", "content": system_prompt }),
+        serde_json::json!({ "role": "user", "content": query }),
+    ];
+    let definitions = tools::definitions();
+    let mut actions: Vec<PendingAction> = Vec::new();
+
+    for step in 0..cfg.chat.max_tool_steps {
+        let turn = send_turn(model_spec, &options, &messages, Some(&definitions)).await?;
+        let (message, calls) = match turn {
+            ModelTurn::Answer(answer) => return Ok(ChatReply::new(answer, actions)),
+            ModelTurn::ToolCalls { message, calls } => (message, calls),
+        };
+        tracing::debug!(step, calls = calls.len(), "chat model requested tools");
+        messages.push(message);
+
+        for call in &calls {
+            let output = match tools::execute(call, ctx).await {
+                ToolOutcome::Output(text) => text,
+                ToolOutcome::Pending(action) => {
+                    if !actions.contains(&action) {
+                        actions.push(action);
+                    }
+                    tools::PENDING_TOOL_OUTPUT.to_string()
+                }
+            };
+            messages.push(tool_result_message(provider, call, output));
+        }
+    }
+
+    tracing::debug!(
+        max_steps = cfg.chat.max_tool_steps,
+        "chat tool step limit reached, requesting final answer"
+    );
+    match send_turn(model_spec, &options, &messages, None).await? {
+        ModelTurn::Answer(answer) => Ok(ChatReply::new(answer, actions)),
+        ModelTurn::ToolCalls { .. } => Err("Model kept calling tools past the step limit".into()),
+    }
+}
+
+const TOOL_PROMPT: &str = "You can call tools to search the user's files, read indexed files, \
+     calculate, and look up installed apps or launch history. Launching apps and typing text \
+     only propose the action; the user confirms it afterwards.";
+
+async fn send_turn(
+    model: &ModelSpec,
+    options: &ModelOptions,
+    messages: &[serde_json::Value],
+    tools: Option<&[serde_json::Value]>,
+) -> Result<ModelTurn, String> {
+    let cfg = config::get_config();
+    let client = reqwest::Client::new();
+    let provider = model.provider.as_str();
+    let body = request_body(provider, &model.name, options, messages, tools);
+
+    let request = match provider {
+        "ollama" => client
+            .post(format!("{}/api/chat", cfg.ollama.url))
+            .timeout(std::time::Duration::from_secs(cfg.ollama.chat_timeout_secs)),
+        "openrouter" => {
+            if cfg.openrouter.api_key.is_empty() {
+                return Err(
+                    "OpenRouter API key not configured. Set BURROW_OPENROUTER_API_KEY or add api_key under [openrouter] in config.toml".into()
+                );
+            }
+            client
+                .post(OPENROUTER_CHAT_URL)
+                .bearer_auth(&cfg.openrouter.api_key)
+                .timeout(std::time::Duration::from_secs(
+                    cfg.openrouter.chat_timeout_secs,
+                ))
+        }
+        "openai_compatible" => {
+            let endpoint = &cfg.openai_compatible;
+            let mut request = client
+                .post(format!("{}/chat/completions", endpoint.base_url))
+                .timeout(std::time::Duration::from_secs(endpoint.timeout_secs));
+            if let Some(key) = endpoint.api_key() {
+                request = request.bearer_auth(key);
+            }
+            for (name, value) in &endpoint.headers {
+                request = request.header(name, value);
+            }
+            request
+        }
+        other => return Err(format!("Unknown provider: {other}")),
+    };
+
+    let response = request
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Chat request failed: {e}"))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Chat error ({status}): {body}"));
+    }
+
+    let json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse chat response: {e}"))?;
+
+    match provider {
+        "ollama" => parse_ollama_turn(&json),
+        _ => parse_openai_turn(&json),
+    }
+}
+
+/// Build the request body for a provider. Ollama takes sampling options under
+/// `options` plus a top-level `keep_alive`; OpenAI-style APIs take `temperature`
+/// and `max_tokens` at the top level and have no context-length knob.
+fn request_body(
+    provider: &str,
+    model: &str,
+    options: &ModelOptions,
+    messages: &[serde_json::Value],
+    tools: Option<&[serde_json::Value]>,
+) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "model": model,
+        "messages": messages,
+        "stream": false
+    });
+    if let Some(tools) = tools {
+        body["tools"] = serde_json::Value::from(tools.to_vec());
+    }
+    if provider == "ollama" {
+        let mut ollama_options = serde_json::Map::new();
+        if let Some(temperature) = options.temperature {
+            ollama_options.insert("temperature".into(), temperature.into());
+        }
+        if let Some(max_tokens) = options.max_tokens {
+            ollama_options.insert("num_predict".into(), max_tokens.into());
+        }
+        if let Some(context_length) = options.context_length {
+            ollama_options.insert("num_ctx".into(), context_length.into());
+        }
+        if !ollama_options.is_empty() {
+            body["options"] = ollama_options.into();
+        }
+        if let Some(keep_alive) = &options.keep_alive {
+            // Ollama accepts durations ("10m") or seconds as a number ("-1" = forever)
+            body["keep_alive"] = keep_alive
+                .parse::<i64>()
+                .map(serde_json::Value::from)
+                .unwrap_or_else(|_| keep_alive.as_str().into());
+        }
+    } else {
+        if let Some(temperature) = options.temperature {
+            body["temperature"] = temperature.into();
+        }
+        if let Some(max_tokens) = options.max_tokens {
+            body["max_tokens"] = max_tokens.into();
+        }
+    }
+    body
+}
+
+/// Parse an Ollama `/api/chat` response. Tool arguments arrive as JSON objects
+/// and calls carry no id.
+fn parse_ollama_turn(json: &serde_json::Value) -> Result<ModelTurn, String> {
+    let message = &json["message"];
+    let calls: Vec<ToolCall> = message["tool_calls"]
+        .as_array()
+        .map(|calls| {
+            calls
+                .iter()
+                .enumerate()
+                .filter_map(|(i, call)| {
+                    Some(ToolCall {
+                        id: format!("call_{i}"),
+                        name: call["function"]["name"].as_str()?.to_string(),
+                        arguments: call["function"]["arguments"].clone(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if !calls.is_empty() {
+        return Ok(ModelTurn::ToolCalls {
+            message: message.clone(),
+            calls,
+        });
+    }
+
+    message["content"]
+        .as_str()
+        .map(|s| ModelTurn::Answer(s.to_string()))
+        .ok_or_else(|| "No content in Ollama response".into())
+}
+
+/// Parse an OpenAI-compatible chat completion. Tool arguments arrive as a JSON
+/// string that has to be decoded.
+fn parse_openai_turn(json: &serde_json::Value) -> Result<ModelTurn, String> {
+    let message = &json["choices"][0]["message"];
+    if message.is_null() {
+        return Err("No message in chat response".into());
+    }
+
+    let calls: Vec<ToolCall> = message["tool_calls"]
+        .as_array()
+        .map(|calls| {
+            calls
+                .iter()
+                .enumerate()
+                .filter_map(|(i, call)| {
+                    let arguments = match &call["function"]["arguments"] {
+                        serde_json::Value::String(raw) => serde_json::from_str(raw)
+                            .unwrap_or(serde_json::Value::Object(Default::default())),
+                        other => other.clone(),
+                    };
+                    Some(ToolCall {
+                        id: call["id"]
+                            .as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| format!("call_{i}")),
+                        name: call["function"]["name"].as_str()?.to_string(),
+                        arguments,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if !calls.is_empty() {
+        return Ok(ModelTurn::ToolCalls {
+            message: message.clone(),
+            calls,
+        });
+    }
+
+    message["content"]
+        .as_str()
+        .map(|s| ModelTurn::Answer(s.to_string()))
+        .ok_or_else(|| "No text in chat response".into())
+}
+
+fn tool_result_message(provider: &str, call: &ToolCall, output: String) -> serde_json::Value {
+    match provider {
+        "ollama" => serde_json::json!({
+            "role": "tool",
+            "tool_name": call.name,
+            "content": output
+        }),
+        _ => serde_json::json!({
+            "role": "tool",
+            "tool_call_id": call.id,
+            "content": output
+        }),
+    }
+}
+
+fn build_system_prompt(context_snippets: &[ContextSnippet]) -> String {
+    if context_snippets.is_empty() {
+        return "You are a helpful assistant integrated into Burrow, a desktop application launcher. Answer the user's question concisely.".into();
+    }
+
+    format!(
+        "You are a helpful assistant integrated into Burrow, a desktop application launcher. \
+         Answer the user's question using the following file context. Be concise.\n\n{}",
+        format_context_block(context_snippets)
+    )
+}
+
+/// Render RAG snippets as a delimited context block for system prompts.
+fn format_context_block(context_snippets: &[ContextSnippet]) -> String {
+    let mut block = String::from("--- Context ---\n");
+    for snippet in context_snippets {
+        block.push_str(&format!("\n[{}]\n{}\n", snippet.path, snippet.preview));
+    }
+    block.push_str("\n--- End Context ---\n");
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_system_prompt_empty_context() {
+        let prompt = build_system_prompt(&[]);
+        assert!(prompt.contains("helpful assistant"));
+        assert!(!prompt.contains("Context"));
+    }
+
+    #[test]
+    fn build_system_prompt_with_context() {
+        let snippets = vec![
+            ContextSnippet {
+                path: "/home/user/doc.md".into(),
+                preview: "Rust is great".into(),
+            },
+            ContextSnippet {
+                path: "/home/user/notes.txt".into(),
+                preview: "Setup instructions".into(),
+            },
+        ];
+        let prompt = build_system_prompt(&snippets);
+        assert!(prompt.contains("[/home/user/doc.md]"));
+        assert!(prompt.contains("Rust is great"));
+        assert!(prompt.contains("[/home/user/notes.txt]"));
+        assert!(prompt.contains("End Context"));
+    }
+
+    #[test]
+    fn build_system_prompt_preserves_all_snippets() {
+        let snippets: Vec<ContextSnippet> = (0..5)
+            .map(|i| ContextSnippet {
+                path: format!("/path/{i}.txt"),
+                preview: format!("content {i}"),
+            })
+            .collect();
+        let prompt = build_system_prompt(&snippets);
+        for i in 0..5 {
+            assert!(prompt.contains(&format!("/path/{i}.txt")));
+            assert!(prompt.contains(&format!("content {i}")));
+        }
+    }
+
+    #[test]
+    fn parse_ollama_turn_answer() {
+        let json = serde_json::json!({
+            "message": { "role": "assistant", "content": "Hello" }
+        });
+        assert_eq!(
+            parse_ollama_turn(&json).unwrap(),
+            ModelTurn::Answer("Hello".into())
+        );
+    }
+
+    #[test]
+    fn parse_ollama_turn_tool_calls() {
+        let json = serde_json::json!({
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [
+                    { "function": { "name": "calculate", "arguments": { "expression": "2+2" } } }
+                ]
+            }
+        });
+        match parse_ollama_turn(&json).unwrap() {
+            ModelTurn::ToolCalls { message, calls } => {
+                assert_eq!(message["role"], "assistant");
+                assert_eq!(calls.len(), 1);
+                assert_eq!(calls[0].id, "call_0");
+                assert_eq!(calls[0].name, "calculate");
+                assert_eq!(calls[0].arguments["expression"], "2+2");
+            }
+            other => panic!("expected tool calls, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_openai_turn_decodes_string_arguments() {
+        let json = serde_json::json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_abc",
+                        "type": "function",
+                        "function": { "name": "find_apps", "arguments": "{\"query\":\"firefox\"}" }
+                    }]
+                }
+            }]
+        });
+        match parse_openai_turn(&json).unwrap() {
+            ModelTurn::ToolCalls { calls, .. } => {
+                assert_eq!(calls[0].id, "call_abc");
+                assert_eq!(calls[0].name, "find_apps");
+                assert_eq!(calls[0].arguments["query"], "firefox");
+            }
+            other => panic!("expected tool calls, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_openai_turn_answer_and_errors() {
+        let json = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "Done" } }]
+        });
+        assert_eq!(
+            parse_openai_turn(&json).unwrap(),
+            ModelTurn::Answer("Done".into())
+        );
+        assert!(parse_openai_turn(&serde_json::json!({ "choices": [] })).is_err());
+    }
+
+    #[test]
+    fn tool_result_message_per_provider() {
+        let call = ToolCall {
+            id: "call_7".into(),
+            name: "calculate".into(),
+            arguments: serde_json::json!({}),
+        };
+        let ollama = tool_result_message("ollama", &call, "4".into());
+        assert_eq!(ollama["tool_name"], "calculate");
+        assert!(ollama.get("tool_call_id").is_none());
+
+        let openrouter = tool_result_message("openrouter", &call, "4".into());
+        assert_eq!(openrouter["tool_call_id"], "call_7");
+        assert_eq!(openrouter["content"], "4");
+    }
+
+    #[test]
+    fn request_body_maps_ollama_options() {
+        let options = ModelOptions {
+            temperature: Some(0.5),
+            max_tokens: Some(256),
+            context_length: Some(8192),
+            keep_alive: Some("-1".into()),
+        };
+        let body = request_body("ollama", "qwen", &options, &[], None);
+        assert_eq!(body["options"]["temperature"], 0.5);
+        assert_eq!(body["options"]["num_predict"], 256);
+        assert_eq!(body["options"]["num_ctx"], 8192);
+        assert_eq!(body["keep_alive"], -1);
+        assert!(body.get("max_tokens").is_none());
+
+        let keep = ModelOptions {
+            keep_alive: Some("10m".into()),
+            ..Default::default()
+        };
+        let body = request_body("ollama", "qwen", &keep, &[], None);
+        assert_eq!(body["keep_alive"], "10m");
+        assert!(body.get("options").is_none());
+    }
+
+    #[test]
+    fn request_body_maps_openai_options() {
+        let options = ModelOptions {
+            temperature: Some(0.25),
+            max_tokens: Some(1024),
+            context_length: Some(8192),
+            keep_alive: Some("5m".into()),
+        };
+        let tools = [serde_json::json!({ "type": "function" })];
+        for provider in ["openrouter", "openai_compatible"] {
+            let body = request_body(provider, "m", &options, &[], Some(&tools));
+            assert_eq!(body["temperature"], 0.25);
+            assert_eq!(body["max_tokens"], 1024);
+            assert_eq!(body["tools"].as_array().unwrap().len(), 1);
+            assert!(body.get("options").is_none());
+            assert!(body.get("keep_alive").is_none());
+        }
+
+        let body = request_body(
+            "openai_compatible",
+            "m",
+            &ModelOptions::default(),
+            &[],
+            None,
+        );
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("tools").is_none());
+    }
+
+    #[test]
+    fn model_spec_helpers() {
+        let ollama = ModelSpec::ollama("llama3:8b");
+        assert_eq!(ollama.name, "llama3:8b");
+        assert_eq!(ollama.provider, "ollama");
+
+        let openrouter = ModelSpec::openrouter("anthropic/claude-sonnet-4");
+        assert_eq!(openrouter.name, "anthropic/claude-sonnet-4");
+        assert_eq!(openrouter.provider, "openrouter");
+    }
+}
@@ -0,0 +1,359 @@
+//! Named chat personas and system prompt templating.
+//!
+//! Personas come from `[chat.personas.<name>]` and are selected with `?@name ...`
+//! in the launcher or `burrow chat --persona name`. Their system prompt is a
+//! template with `{{date}}`, `{{clipboard}}` and `{{snippets}}` variables.
+
+use super::{build_system_prompt, format_context_block, ContextSnippet};
+use crate::config::{AppConfig, ModelOptions, ModelSpec};
+
+/// Effective chat settings for a request, after applying any persona overrides.
+#[derive(Debug, Clone)]
+pub struct Persona {
+    /// Persona name, `None` for the built-in default assistant
+    pub name: Option<String>,
+    /// System prompt template (`None` uses the built-in prompt)
+    pub system_prompt: Option<String>,
+    pub model: ModelSpec,
+    pub temperature: Option<f32>,
+    /// Whether to fetch RAG snippets for this request
+    pub rag: bool,
+}
+
+impl Persona {
+    /// The built-in assistant with the given model and RAG setting.
+    pub fn default_with(model: &ModelSpec, rag: bool) -> Self {
+        Self {
+            name: None,
+            system_prompt: None,
+            model: model.clone(),
+            temperature: None,
+            rag,
+        }
+    }
+
+    /// Look up a configured persona. Unset fields fall back to `default_model` and `default_rag`.
+    pub fn resolve(
+        name: &str,
+        cfg: &AppConfig,
+        default_model: &ModelSpec,
+        default_rag: bool,
+    ) -> Result<Self, String> {
+        let persona = cfg.chat.personas.get(name).ok_or_else(|| {
+            let known: Vec<&str> = cfg.chat.personas.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                format!("Unknown persona \"{name}\" — none configured under [chat.personas]")
+            } else {
+                format!(
+                    "Unknown persona \"{name}\" — available: {}",
+                    known.join(", ")
+                )
+            }
+        })?;
+        let system_prompt = Some(persona.system_prompt.trim())
+            .filter(|p| !p.is_empty())
+            .map(str::to_string);
+        Ok(Self {
+            name: Some(name.to_string()),
+            system_prompt,
+            model: persona
+                .model
+                .clone()
+                .unwrap_or_else(|| default_model.clone()),
+            temperature: persona.temperature,
+            rag: persona.rag.unwrap_or(default_rag),
+        })
+    }
+
+    /// Generation options for the model, with the persona's temperature override applied.
+    pub fn options(&self) -> ModelOptions {
+        let mut options = self.model.options.clone();
+        if self.temperature.is_some() {
+            options.temperature = self.temperature;
+        }
+        options
+    }
+
+    /// Render the system prompt for this request. The clipboard is only read
+    /// when the template actually references it.
+    pub fn render_system_prompt(&self, context_snippets: &[ContextSnippet]) -> String {
+        let Some(template) = self.system_prompt.as_deref() else {
+            return build_system_prompt(context_snippets);
+        };
+        let clipboard = if template.contains("{{clipboard}}") {
+            crate::actions::utils::read_clipboard(false).unwrap_or_else(|e| {
+                tracing::debug!(error = %e, "clipboard unavailable for persona template");
+                String::new()
+            })
+        } else {
+            String::new()
+        };
+        let vars = TemplateVars {
+            date: today(),
+            clipboard,
+            snippets: context_snippets,
+        };
+        render_template(template, &vars)
+    }
+}
+
+/// Values substituted into persona prompt templates.
+pub struct TemplateVars<'a> {
+    pub date: String,
+    pub clipboard: String,
+    pub snippets: &'a [ContextSnippet],
+}
+
+/// Substitute `{{date}}`, `{{clipboard}}` and `{{snippets}}` in one pass, so
+/// variables inside substituted text (a copied template, say) stay literal.
+/// Unknown variables are left as-is. If the template doesn't place
+/// `{{snippets}}` itself, any RAG snippets are appended so they are never
+/// silently dropped.
+pub fn render_template(template: &str, vars: &TemplateVars) -> String {
+    let context = if vars.snippets.is_empty() {
+        String::new()
+    } else {
+        format_context_block(vars.snippets)
+    };
+    let values = [
+        ("{{date}}", vars.date.as_str()),
+        ("{{clipboard}}", vars.clipboard.as_str()),
+        ("{{snippets}}", context.as_str()),
+    ];
+    let mut rendered = String::with_capacity(template.len());
+    let mut placed_snippets = false;
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match values.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) => {
+                placed_snippets |= *name == "{{snippets}}";
+                rendered.push_str(value);
+                rest = &rest[name.len()..];
+            }
+            None => {
+                // Step one brace so `{{{date}}` still finds the variable
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    if !placed_snippets && !context.is_empty() {
+        rendered.push_str("\n\n");
+        rendered.push_str(&context);
+    }
+    rendered
+}
+
+/// Split a `@name rest` query into the persona name and the remaining question.
+/// Returns `(None, query)` when the query doesn't start with `@name`.
+pub fn split_persona(query: &str) -> (Option<&str>, &str) {
+    let Some(rest) = query.strip_prefix('@') else {
+        return (None, query);
+    };
+    let (name, question) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    if name.is_empty() {
+        return (None, query);
+    }
+    (Some(name), question.trim())
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`.
+fn today() -> String {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0);
+    let (year, month, day) = civil_date(days as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Year, month and day of `days` since 1970-01-01, in the proleptic
+/// Gregorian calendar (Howard Hinnant's `civil_from_days`).
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PersonaConfig;
+
+    fn cfg_with_reviewer() -> AppConfig {
+        let mut cfg = AppConfig::default();
+        cfg.chat.personas.insert(
+            "reviewer".into(),
+            PersonaConfig {
+                system_prompt: "You review code.".into(),
+                model: Some(ModelSpec::openrouter("anthropic/claude-sonnet-4")),
+                temperature: Some(0.2),
+                rag: Some(false),
+            },
+        );
+        cfg.chat
+            .personas
+            .insert("plain".into(), PersonaConfig::default());
+        cfg
+    }
+
+    #[test]
+    fn split_persona_extracts_name_and_question() {
+        assert_eq!(
+            split_persona("@reviewer is this safe?"),
+            (Some("reviewer"), "is this safe?")
+        );
+        assert_eq!(split_persona("@reviewer"), (Some("reviewer"), ""));
+        assert_eq!(split_persona("what is rust"), (None, "what is rust"));
+        assert_eq!(split_persona("@ hello"), (None, "@ hello"));
+    }
+
+    #[test]
+    fn resolve_applies_overrides() {
+        let cfg = cfg_with_reviewer();
+        let default_model = ModelSpec::ollama("gpt-oss:120b");
+        let persona = Persona::resolve("reviewer", &cfg, &default_model, true).unwrap();
+        assert_eq!(persona.name.as_deref(), Some("reviewer"));
+        assert_eq!(persona.model.provider, "openrouter");
+        assert_eq!(persona.temperature, Some(0.2));
+        assert!(!persona.rag);
+    }
+
+    #[test]
+    fn persona_temperature_overrides_model_options() {
+        let mut model = ModelSpec::ollama("m");
+        model.options.temperature = Some(0.9);
+        model.options.max_tokens = Some(128);
+        let mut persona = Persona::default_with(&model, false);
+        assert_eq!(persona.options().temperature, Some(0.9));
+
+        persona.temperature = Some(0.1);
+        let options = persona.options();
+        assert_eq!(options.temperature, Some(0.1));
+        assert_eq!(options.max_tokens, Some(128));
+    }
+
+    #[test]
+    fn resolve_falls_back_to_defaults() {
+        let cfg = cfg_with_reviewer();
+        let default_model = ModelSpec::ollama("gpt-oss:120b");
+        let persona = Persona::resolve("plain", &cfg, &default_model, true).unwrap();
+        assert_eq!(persona.model.name, "gpt-oss:120b");
+        assert!(persona.system_prompt.is_none());
+        assert!(persona.temperature.is_none());
+        assert!(persona.rag);
+    }
+
+    #[test]
+    fn resolve_unknown_persona_lists_available() {
+        let cfg = cfg_with_reviewer();
+        let err = Persona::resolve("nope", &cfg, &ModelSpec::ollama("m"), true).unwrap_err();
+        assert!(err.contains("plain, reviewer"), "{err}");
+
+        let err = Persona::resolve("nope", &AppConfig::default(), &ModelSpec::ollama("m"), true)
+            .unwrap_err();
+        assert!(err.contains("none configured"), "{err}");
+    }
+
+    #[test]
+    fn render_template_substitutes_variables() {
+        let snippets = vec![ContextSnippet {
+            path: "/notes/rust.md".into(),
+            preview: "Ownership rules".into(),
+        }];
+        let vars = TemplateVars {
+            date: "2026-01-02".into(),
+            clipboard: "fn main() {}".into(),
+            snippets: &snippets,
+        };
+        let rendered = render_template(
+            "Date: {{date}}\nClipboard: {{clipboard}}\n{{snippets}}\nKeep {{unknown}}",
+            &vars,
+        );
+        assert!(rendered.contains("Date: 2026-01-02"));
+        assert!(rendered.contains("Clipboard: fn main() {}"));
+        assert!(rendered.contains("[/notes/rust.md]"));
+        assert!(rendered.contains("Ownership rules"));
+        assert!(rendered.contains("{{unknown}}"));
+    }
+
+    #[test]
+    fn render_template_appends_snippets_when_not_placed() {
+        let snippets = vec![ContextSnippet {
+            path: "/a.txt".into(),
+            preview: "alpha".into(),
+        }];
+        let vars = TemplateVars {
+            date: String::new(),
+            clipboard: String::new(),
+            snippets: &snippets,
+        };
+        let rendered = render_template("Be brief.", &vars);
+        assert!(rendered.starts_with("Be brief."));
+        assert!(rendered.contains("[/a.txt]"));
+
+        let empty = TemplateVars {
+            date: String::new(),
+            clipboard: String::new(),
+            snippets: &[],
+        };
+        assert_eq!(
+            render_template("Be brief. {{snippets}}", &empty),
+            "Be brief. "
+        );
+    }
+
+    #[test]
+    fn default_persona_uses_builtin_prompt() {
+        let persona = Persona::default_with(&ModelSpec::ollama("m"), false);
+        let prompt = persona.render_system_prompt(&[]);
+        assert!(prompt.contains("helpful assistant"));
+    }
+
+    #[test]
+    fn substituted_text_is_not_expanded_again() {
+        let snippets = vec![ContextSnippet {
+            path: "/a.txt".into(),
+            preview: "alpha {{date}}".into(),
+        }];
+        let vars = TemplateVars {
+            date: "2026-01-02".into(),
+            clipboard: "use {{snippets}} on {{date}}".into(),
+            snippets: &snippets,
+        };
+        let rendered = render_template("{{{date}} {{clipboard}}", &vars);
+        assert!(
+            rendered.starts_with("{2026-01-02 use {{snippets}} on {{date}}\n\n"),
+            "{rendered}"
+        );
+        // Snippets are appended once, their own text left as-is
+        assert_eq!(rendered.matches("[/a.txt]").count(), 1);
+        assert!(rendered.contains("alpha {{date}}"));
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(20_819), (2027, 1, 1));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn today_is_iso_date() {
+        let date = today();
+        assert_eq!(date.len(), 10, "{date}");
+        assert_eq!(&date[4..5], "-");
+        assert_eq!(&date[7..8], "-");
+    }
+}
@@ -0,0 +1,493 @@
+//! Burrow capabilities exposed to chat models as callable tools.
+//!
+//! Read-only tools (content search, file read, calculator, app/history lookup) run
+//! immediately and their output is fed back to the model. Tools that launch or type
+//! never run from the model loop — they become [`PendingAction`]s that the user has
+//! to confirm explicitly.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::commands::{apps, history, math, vectors};
+use crate::config::{self, AppConfig};
+use crate::context::AppContext;
+use crate::indexer;
+
+/// Maximum number of list entries returned by lookup tools.
+const MAX_TOOL_RESULTS: usize = 8;
+
+/// A tool invocation requested by the model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    /// Provider-assigned call id (OpenAI-style). Ollama omits it, so we synthesize one.
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// A side-effectful action proposed by the model. Only executed after the user confirms.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PendingAction {
+    /// Launch an installed application by desktop entry id.
+    LaunchApp { app_id: String, name: String },
+    /// Type text into the focused window via wtype.
+    TypeText { text: String },
+    /// Copy text to the clipboard (offered on generated answers, not a model tool).
+    CopyText { text: String },
+    /// Run a shell code block from a chat answer in an output window.
+    RunBlock { block_id: String },
+    /// Save a code block from a chat answer to a new file.
+    SaveBlock { block_id: String, path: String },
+}
+
+impl PendingAction {
+    /// Human-readable label for the confirmation prompt.
+    pub fn label(&self) -> String {
+        match self {
+            PendingAction::LaunchApp { name, .. } => format!("Launch {name}"),
+            PendingAction::TypeText { text } => {
+                format!("Type \"{}\"", crate::actions::dry_run::truncate(text, 60))
+            }
+            PendingAction::CopyText { text } => {
+                format!("Copy \"{}\"", crate::actions::dry_run::truncate(text, 60))
+            }
+            PendingAction::RunBlock { block_id } => format!("Run {block_id}"),
+            PendingAction::SaveBlock { block_id, path } => format!("Save {block_id} to {path}"),
+        }
+    }
+}
+
+/// Outcome of executing a single tool call.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolOutcome {
+    /// Text returned to the model as the tool result.
+    Output(String),
+    /// The call needs user confirmation; the model is told it was proposed.
+    Pending(PendingAction),
+}
+
+/// Tool message fed back to the model when an action awaits confirmation.
+pub const PENDING_TOOL_OUTPUT: &str =
+    "Proposed to the user. It will only run after they confirm; do not call this tool again for the same action.";
+
+/// JSON schema definitions in the OpenAI function-calling format (also accepted by Ollama).
+pub fn definitions() -> Vec<serde_json::Value> {
+    vec![
+        function(
+            "search_content",
+            "Semantic search over the user's indexed files. Returns matching file paths with a preview.",
+            json!({
+                "query": { "type": "string", "description": "What to look for" }
+            }),
+            &["query"],
+        ),
+        function(
+            "read_file",
+            "Read the text content of a file inside the indexed directories.",
+            json!({
+                "path": { "type": "string", "description": "Absolute path or ~/ path of the file" }
+            }),
+            &["path"],
+        ),
+        function(
+            "calculate",
+            "Evaluate a math expression, e.g. \"(2+3)*4\" or \"sqrt(16)\".",
+            json!({
+                "expression": { "type": "string", "description": "The math expression" }
+            }),
+            &["expression"],
+        ),
+        function(
+            "find_apps",
+            "Find installed desktop applications by name.",
+            json!({
+                "query": { "type": "string", "description": "Application name to search for" }
+            }),
+            &["query"],
+        ),
+        function(
+            "recent_history",
+            "List the user's most frequently and recently launched items.",
+            json!({}),
+            &[],
+        ),
+        function(
+            "launch_app",
+            "Ask the user to confirm launching an installed application. Use an id returned by find_apps.",
+            json!({
+                "app_id": { "type": "string", "description": "Application id from find_apps" }
+            }),
+            &["app_id"],
+        ),
+        function(
+            "type_text",
+            "Ask the user to confirm typing text into their focused window.",
+            json!({
+                "text": { "type": "string", "description": "The text to type" }
+            }),
+            &["text"],
+        ),
+    ]
+}
+
+fn function(
+    name: &str,
+    description: &str,
+    properties: serde_json::Value,
+    required: &[&str],
+) -> serde_json::Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": name,
+            "description": description,
+            "parameters": {
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }
+        }
+    })
+}
+
+/// Execute a tool call. Errors are reported back to the model as tool output
+/// so it can recover, rather than aborting the whole chat.
+pub async fn execute(call: &ToolCall, ctx: &AppContext) -> ToolOutcome {
+    tracing::debug!(tool = %call.name, "executing chat tool");
+    let result = match call.name.as_str() {
+        "search_content" => search_content(call, ctx).await,
+        "read_file" => string_arg(call, "path")
+            .and_then(|path| read_file(&path, config::get_config()))
+            .map(ToolOutcome::Output),
+        "calculate" => string_arg(call, "expression")
+            .and_then(|expr| calculate(&expr))
+            .map(ToolOutcome::Output),
+        "find_apps" => string_arg(call, "query").and_then(|q| find_apps(&q, ctx)),
+        "recent_history" => recent_history(ctx),
+        "launch_app" => string_arg(call, "app_id").and_then(|id| propose_launch(&id, ctx)),
+        "type_text" => string_arg(call, "text")
+            .map(|text| ToolOutcome::Pending(PendingAction::TypeText { text })),
+        other => Err(format!("Unknown tool: {other}")),
+    };
+    result.unwrap_or_else(|e| {
+        tracing::debug!(tool = %call.name, error = %e, "chat tool failed");
+        ToolOutcome::Output(format!("Error: {e}"))
+    })
+}
+
+fn string_arg(call: &ToolCall, key: &str) -> Result<String, String> {
+    call.arguments[key]
+        .as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| format!("Missing required argument \"{key}\" for {}", call.name))
+}
+
+async fn search_content(call: &ToolCall, ctx: &AppContext) -> Result<ToolOutcome, String> {
+    let query = string_arg(call, "query")?;
+    let results = vectors::search_by_content(&query, ctx).await?;
+    if results.is_empty() {
+        return Ok(ToolOutcome::Output("No matching files".into()));
+    }
+    let lines: Vec<String> = results
+        .iter()
+        .take(MAX_TOOL_RESULTS)
+        .map(|r| format!("{} ({})", r.id, r.description))
+        .collect();
+    Ok(ToolOutcome::Output(lines.join("\n")))
+}
+
+/// Read a file, but only if it lives inside the configured search directories,
+/// is not hidden or excluded, and is a type the indexer would index. Keeps the
+/// model away from dotfiles such as `~/.ssh`.
+fn read_file(raw_path: &str, cfg: &AppConfig) -> Result<String, String> {
+    let path = resolve_readable_path(raw_path, cfg)?;
+    let content = crate::text_extract::extract_text(&path, cfg.indexer.max_content_chars)?;
+    if content.trim().is_empty() {
+        return Ok("(file contains no text)".into());
+    }
+    Ok(content)
+}
+
+fn resolve_readable_path(raw_path: &str, cfg: &AppConfig) -> Result<PathBuf, String> {
+    let path = indexer::expand_tilde(raw_path);
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("Cannot read {raw_path}: {e}"))?;
+
+    let root = indexer::get_search_directories(cfg)
+        .into_iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .find(|dir| canonical.starts_with(dir))
+        .ok_or_else(|| format!("{raw_path} is outside the indexed directories"))?;
+
+    if has_hidden_component(&canonical, &root) || indexer::is_excluded_path(&canonical, cfg) {
+        return Err(format!("{raw_path} is excluded from indexing"));
+    }
+
+    if !indexer::is_indexable_file(
+        &canonical,
+        cfg.vector_search.max_file_size_bytes,
+        &cfg.indexer.file_extensions,
+    ) {
+        return Err(format!("{raw_path} is not a readable document"));
+    }
+
+    Ok(canonical)
+}
+
+fn has_hidden_component(path: &Path, root: &Path) -> bool {
+    path.strip_prefix(root)
+        .map(|rel| {
+            rel.components()
+                .any(|c| c.as_os_str().to_string_lossy().starts_with('.'))
+        })
+        .unwrap_or(true)
+}
+
+fn calculate(expression: &str) -> Result<String, String> {
+    math::try_calculate(expression)
+        .map(|r| r.description)
+        .ok_or_else(|| format!("Could not evaluate \"{expression}\""))
+}
+
+fn find_apps(query: &str, ctx: &AppContext) -> Result<ToolOutcome, String> {
+    let results = apps::search_apps(query, ctx)?;
+    if results.is_empty() {
+        return Ok(ToolOutcome::Output(format!("No apps matching \"{query}\"")));
+    }
+    let lines: Vec<String> = results
+        .iter()
+        .take(MAX_TOOL_RESULTS)
+        .map(|r| format!("id={} name={} — {}", r.id, r.name, r.description))
+        .collect();
+    Ok(ToolOutcome::Output(lines.join("\n")))
+}
+
+fn recent_history(ctx: &AppContext) -> Result<ToolOutcome, String> {
+    let entries = history::get_frecent(ctx)?;
+    if entries.is_empty() {
+        return Ok(ToolOutcome::Output("No launch history".into()));
+    }
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|r| format!("id={} name={}", r.id, r.name))
+        .collect();
+    Ok(ToolOutcome::Output(lines.join("\n")))
+}
+
+fn propose_launch(app_id: &str, ctx: &AppContext) -> Result<ToolOutcome, String> {
+    let name = ctx
+        .apps
+        .resolve_name(app_id)
+        .ok_or_else(|| format!("Unknown app id \"{app_id}\" — use find_apps first"))?;
+    Ok(ToolOutcome::Pending(PendingAction::LaunchApp {
+        app_id: app_id.to_string(),
+        name,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::history::DbState;
+    use crate::commands::vectors::VectorDbState;
+    use crate::indexer::IndexerState;
+    use crate::output_buffers::OutputBufferState;
+    use rusqlite::Connection;
+    use std::fs;
+    use std::sync::Arc;
+
+    fn custom_dir_config(dir: &Path) -> AppConfig {
+        let mut cfg = AppConfig::default();
+        cfg.vector_search.index_mode = "custom".into();
+        cfg.vector_search.index_dirs = vec![dir.display().to_string()];
+        // Defaults exclude /tmp, where the tempdirs live.
+        cfg.vector_search.exclude_patterns = vec!["node_modules".into()];
+        cfg
+    }
+
+    fn ctx_with_app(dir: &Path) -> AppContext {
+        fs::write(
+            dir.join("firefox.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Firefox\nExec=firefox %u\n",
+        )
+        .unwrap();
+        AppContext::from_arcs(
+            Arc::new(DbState::new(Connection::open_in_memory().unwrap())),
+            Arc::new(VectorDbState::new(Connection::open_in_memory().unwrap())),
+            Arc::new(IndexerState::new()),
+            Arc::new(OutputBufferState::new()),
+            Arc::new(apps::AppIndexState::new_for_test(vec![dir.to_path_buf()])),
+        )
+    }
+
+    fn call(name: &str, arguments: serde_json::Value) -> ToolCall {
+        ToolCall {
+            id: "call_0".into(),
+            name: name.into(),
+            arguments,
+        }
+    }
+
+    #[test]
+    fn definitions_have_unique_names_and_schemas() {
+        let defs = definitions();
+        let mut seen = std::collections::HashSet::new();
+        for def in &defs {
+            let name = def["function"]["name"].as_str().unwrap();
+            assert!(seen.insert(name.to_string()), "duplicate tool {name}");
+            assert_eq!(def["type"], "function");
+            assert_eq!(def["function"]["parameters"]["type"], "object");
+        }
+        assert!(seen.contains("launch_app"));
+        assert!(seen.contains("type_text"));
+    }
+
+    #[test]
+    fn read_file_inside_index_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        fs::write(&file, "burrow tool test").unwrap();
+        let cfg = custom_dir_config(dir.path());
+
+        let content = read_file(&file.display().to_string(), &cfg).unwrap();
+        assert_eq!(content, "burrow tool test");
+    }
+
+    #[test]
+    fn read_file_outside_index_dir_is_rejected() {
+        let indexed = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let file = other.path().join("secret.md");
+        fs::write(&file, "secret").unwrap();
+        let cfg = custom_dir_config(indexed.path());
+
+        let err = read_file(&file.display().to_string(), &cfg).unwrap_err();
+        assert!(err.contains("outside the indexed directories"), "{err}");
+    }
+
+    #[test]
+    fn read_file_rejects_hidden_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".ssh")).unwrap();
+        let file = dir.path().join(".ssh/config.txt");
+        fs::write(&file, "Host prod").unwrap();
+        let cfg = custom_dir_config(dir.path());
+
+        let err = read_file(&file.display().to_string(), &cfg).unwrap_err();
+        assert!(err.contains("excluded"), "{err}");
+    }
+
+    #[test]
+    fn read_file_rejects_parent_traversal() {
+        let root = tempfile::tempdir().unwrap();
+        let indexed = root.path().join("indexed");
+        fs::create_dir(&indexed).unwrap();
+        fs::write(root.path().join("outside.md"), "nope").unwrap();
+        let cfg = custom_dir_config(&indexed);
+
+        let traversal = format!("{}/../outside.md", indexed.display());
+        assert!(read_file(&traversal, &cfg).is_err());
+    }
+
+    #[test]
+    fn read_file_rejects_unindexable_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("key.pem");
+        fs::write(&file, "-----BEGIN").unwrap();
+        let cfg = custom_dir_config(dir.path());
+
+        let err = read_file(&file.display().to_string(), &cfg).unwrap_err();
+        assert!(err.contains("not a readable document"), "{err}");
+    }
+
+    #[test]
+    fn calculate_evaluates_expressions() {
+        assert_eq!(calculate("6*7").unwrap(), "6*7 = 42");
+        assert!(calculate("not math").is_err());
+    }
+
+    #[tokio::test]
+    async fn launch_app_requires_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx_with_app(dir.path());
+
+        let outcome = execute(&call("launch_app", json!({"app_id": "firefox"})), &ctx).await;
+        assert_eq!(
+            outcome,
+            ToolOutcome::Pending(PendingAction::LaunchApp {
+                app_id: "firefox".into(),
+                name: "Firefox".into(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn launch_app_unknown_id_reports_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx_with_app(dir.path());
+
+        let outcome = execute(&call("launch_app", json!({"app_id": "nope"})), &ctx).await;
+        match outcome {
+            ToolOutcome::Output(text) => assert!(text.contains("Unknown app id"), "{text}"),
+            other => panic!("expected error output, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn type_text_requires_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx_with_app(dir.path());
+
+        let outcome = execute(&call("type_text", json!({"text": "hello"})), &ctx).await;
+        assert_eq!(
+            outcome,
+            ToolOutcome::Pending(PendingAction::TypeText {
+                text: "hello".into()
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn find_apps_lists_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx_with_app(dir.path());
+
+        let outcome = execute(&call("find_apps", json!({"query": "fire"})), &ctx).await;
+        match outcome {
+            ToolOutcome::Output(text) => assert!(text.contains("id=firefox"), "{text}"),
+            other => panic!("expected output, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_argument_and_unknown_tool_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = ctx_with_app(dir.path());
+
+        let missing = execute(&call("calculate", json!({})), &ctx).await;
+        assert!(
+            matches!(missing, ToolOutcome::Output(ref t) if t.contains("Missing required argument"))
+        );
+
+        let unknown = execute(&call("rm_rf", json!({})), &ctx).await;
+        assert!(matches!(unknown, ToolOutcome::Output(ref t) if t.contains("Unknown tool")));
+    }
+
+    #[test]
+    fn pending_action_serializes_with_kind_tag() {
+        let action = PendingAction::LaunchApp {
+            app_id: "firefox".into(),
+            name: "Firefox".into(),
+        };
+        let json = serde_json::to_string(&action).unwrap();
+        assert!(json.contains(r#""kind":"launch_app""#), "{json}");
+        let parsed: PendingAction = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, action);
+        assert_eq!(action.label(), "Launch Firefox");
+    }
+}
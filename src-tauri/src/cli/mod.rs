@@ -77,6 +77,9 @@ pub enum Commands {
         /// Use small/fast chat model instead of large model
         #[arg(long)]
         small: bool,
+        /// Use a persona from [chat.personas] in config
+        #[arg(long)]
+        persona: Option<String>,
     },
     /// Chat with AI directly (no document context)
     Chat {
@@ -85,6 +88,9 @@ pub enum Commands {
         /// Use small/fast chat model instead of large model
         #[arg(long)]
         small: bool,
+        /// Use a persona from [chat.personas] in config
+        #[arg(long)]
+        persona: Option<String>,
     },
     /// Manage AI model configuration
     Models {
@@ -135,6 +141,46 @@ pub enum HistoryAction {
         /// The app ID to remove (shown in 'burrow history list' output)
         id: String,
     },
+    /// Show frecency scores under the configured model
+    Stats {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export launch history as JSON
+    Export {
+        /// File to write (stdout if omitted)
+        file: Option<PathBuf>,
+    },
+    /// Merge a JSON export into the launch history (counts are summed)
+    Import {
+        /// File written by 'burrow history export'
+        file: PathBuf,
+    },
+    /// Sync history with the configured `history.sync_dir` now
+    Sync,
+    /// Pin an app or history entry to the top of the launcher
+    Pin {
+        /// App or history ID
+        id: String,
+    },
+    /// Unpin an entry
+    Unpin {
+        /// ID shown by 'burrow history list-pinned'
+        id: String,
+    },
+    /// Hide an app from the launcher
+    Hide {
+        /// App or history ID
+        id: String,
+    },
+    /// Show a hidden app again
+    Unhide {
+        /// ID shown by 'burrow history list-pinned'
+        id: String,
+    },
+    /// List pinned and hidden entries
+    ListPinned,
 }
 
 #[cfg(test)]
@@ -310,7 +356,7 @@ mod tests {
     #[test]
     fn cli_parses_chat_docs() {
         let cli = Cli::parse_from(["burrow", "chat-docs", "What is Rust?"]);
-        if let Some(Commands::ChatDocs { query, small }) = cli.command {
+        if let Some(Commands::ChatDocs { query, small, .. }) = cli.command {
             assert_eq!(query, "What is Rust?");
             assert!(!small);
         } else {
@@ -321,7 +367,7 @@ mod tests {
     #[test]
     fn cli_parses_chat_docs_small() {
         let cli = Cli::parse_from(["burrow", "chat-docs", "--small", "Hello"]);
-        if let Some(Commands::ChatDocs { query, small }) = cli.command {
+        if let Some(Commands::ChatDocs { query, small, .. }) = cli.command {
             assert_eq!(query, "Hello");
             assert!(small);
         } else {
@@ -332,7 +378,7 @@ mod tests {
     #[test]
     fn cli_parses_chat() {
         let cli = Cli::parse_from(["burrow", "chat", "Hello world"]);
-        if let Some(Commands::Chat { query, small }) = cli.command {
+        if let Some(Commands::Chat { query, small, .. }) = cli.command {
             assert_eq!(query, "Hello world");
             assert!(!small);
         } else {
@@ -343,7 +389,7 @@ mod tests {
     #[test]
     fn cli_parses_chat_small() {
         let cli = Cli::parse_from(["burrow", "chat", "--small", "Hi"]);
-        if let Some(Commands::Chat { query, small }) = cli.command {
+        if let Some(Commands::Chat { query, small, .. }) = cli.command {
             assert_eq!(query, "Hi");
             assert!(small);
         } else {
@@ -351,6 +397,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cli_parses_chat_persona() {
+        let cli = Cli::parse_from(["burrow", "chat", "--persona", "reviewer", "Check this"]);
+        if let Some(Commands::Chat { query, persona, .. }) = cli.command {
+            assert_eq!(query, "Check this");
+            assert_eq!(persona.as_deref(), Some("reviewer"));
+        } else {
+            panic!("Expected Chat command");
+        }
+
+        let cli = Cli::parse_from(["burrow", "chat-docs", "--persona", "reviewer", "Hi"]);
+        if let Some(Commands::ChatDocs { persona, .. }) = cli.command {
+            assert_eq!(persona.as_deref(), Some("reviewer"));
+        } else {
+            panic!("Expected ChatDocs command");
+        }
+    }
+
     #[test]
     fn cli_parses_models_no_action() {
         let cli = Cli::parse_from(["burrow", "models"]);
@@ -428,6 +492,62 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn cli_parses_history_stats() {
+        let cli = Cli::parse_from(["burrow", "history", "stats", "--json"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History {
+                action: Some(HistoryAction::Stats { json: true })
+            })
+        ));
+    }
+
+    #[test]
+    fn cli_parses_history_export_import() {
+        let cli = Cli::parse_from(["burrow", "history", "export"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History {
+                action: Some(HistoryAction::Export { file: None })
+            })
+        ));
+        let cli = Cli::parse_from(["burrow", "history", "import", "/tmp/h.json"]);
+        if let Some(Commands::History {
+            action: Some(HistoryAction::Import { file }),
+        }) = cli.command
+        {
+            assert_eq!(file, PathBuf::from("/tmp/h.json"));
+        } else {
+            panic!("expected History Import command");
+        }
+    }
+
+    #[test]
+    fn cli_parses_history_pins() {
+        let cli = Cli::parse_from(["burrow", "history", "pin", "firefox"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History {
+                action: Some(HistoryAction::Pin { ref id })
+            }) if id == "firefox"
+        ));
+        let cli = Cli::parse_from(["burrow", "history", "hide", "steam"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History {
+                action: Some(HistoryAction::Hide { ref id })
+            }) if id == "steam"
+        ));
+        let cli = Cli::parse_from(["burrow", "history", "list-pinned"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::History {
+                action: Some(HistoryAction::ListPinned)
+            })
+        ));
+    }
+
     #[test]
     fn cli_parses_history_remove() {
         let cli = Cli::parse_from(["burrow", "history", "remove", "firefox"]);
@@ -3,8 +3,8 @@ use super::output::{
 };
 use super::progress::IndexProgress;
 use super::{Commands, DaemonAction, HistoryAction, ModelsAction};
-use crate::chat::{self, ContextSnippet};
-use crate::commands::{health, history, vectors};
+use crate::chat::{self, persona::Persona, ContextSnippet};
+use crate::commands::{health, history, history_sync, vectors};
 use crate::config;
 use crate::daemon;
 use crate::indexer::{self, is_file_modified};
@@ -28,8 +28,16 @@ pub fn run_command(cmd: Commands) -> i32 {
         Commands::Reindex { quiet } => cmd_reindex(quiet),
         Commands::Update { quiet } => cmd_update(quiet),
         Commands::Daemon { action } => cmd_daemon(action),
-        Commands::ChatDocs { query, small } => cmd_chat_docs(&query, small),
-        Commands::Chat { query, small } => cmd_chat(&query, small),
+        Commands::ChatDocs {
+            query,
+            small,
+            persona,
+        } => cmd_chat_docs(&query, small, persona.as_deref()),
+        Commands::Chat {
+            query,
+            small,
+            persona,
+        } => cmd_chat(&query, small, persona.as_deref()),
         Commands::Models { action } => cmd_models(action),
         Commands::History { action } => cmd_history(action),
     }
@@ -1010,7 +1018,7 @@ fn format_uptime(secs: u64) -> String {
 // Chat commands
 // ============================================================================
 
-fn cmd_chat(query: &str, small: bool) -> i32 {
+fn cmd_chat(query: &str, small: bool, persona: Option<&str>) -> i32 {
     if query.trim().is_empty() {
         print_error("Query cannot be empty");
         return 1;
@@ -1018,14 +1026,14 @@ fn cmd_chat(query: &str, small: bool) -> i32 {
 
     // Try daemon first
     if daemon::is_daemon_running().is_some() {
-        return delegate_chat_to_daemon(query, small, false);
+        return delegate_chat_to_daemon(query, small, persona, false);
     }
 
     // Standalone fallback
-    execute_chat_standalone(query, small, false)
+    execute_chat_standalone(query, small, persona, false)
 }
 
-fn cmd_chat_docs(query: &str, small: bool) -> i32 {
+fn cmd_chat_docs(query: &str, small: bool, persona: Option<&str>) -> i32 {
     if query.trim().is_empty() {
         print_error("Query cannot be empty");
         return 1;
@@ -1033,15 +1041,20 @@ fn cmd_chat_docs(query: &str, small: bool) -> i32 {
 
     // Try daemon first
     if daemon::is_daemon_running().is_some() {
-        return delegate_chat_to_daemon(query, small, true);
+        return delegate_chat_to_daemon(query, small, persona, true);
     }
 
     // Standalone fallback
-    execute_chat_standalone(query, small, true)
+    execute_chat_standalone(query, small, persona, true)
 }
 
 /// Delegate chat to daemon with longer timeout.
-fn delegate_chat_to_daemon(query: &str, small: bool, with_docs: bool) -> i32 {
+fn delegate_chat_to_daemon(
+    query: &str,
+    small: bool,
+    persona: Option<&str>,
+    with_docs: bool,
+) -> i32 {
     let rt = match create_runtime() {
         Ok(rt) => rt,
         Err(code) => return code,
@@ -1050,9 +1063,9 @@ fn delegate_chat_to_daemon(query: &str, small: bool, with_docs: bool) -> i32 {
     let client = daemon::DaemonClient::with_chat_timeout();
     let result = rt.block_on(async {
         if with_docs {
-            client.chat_docs(query, small).await
+            client.chat_docs(query, small, persona).await
         } else {
-            client.chat(query, small).await
+            client.chat(query, small, persona).await
         }
     });
 
@@ -1070,7 +1083,13 @@ fn delegate_chat_to_daemon(query: &str, small: bool, with_docs: bool) -> i32 {
 }
 
 /// Execute chat with optional RAG context (standalone mode).
-fn execute_chat_standalone(query: &str, small: bool, use_rag: bool) -> i32 {
+/// A persona's `rag` setting overrides `use_rag`.
+fn execute_chat_standalone(
+    query: &str,
+    small: bool,
+    persona_name: Option<&str>,
+    use_rag: bool,
+) -> i32 {
     let rt = match create_runtime() {
         Ok(rt) => rt,
         Err(code) => return code,
@@ -1078,7 +1097,23 @@ fn execute_chat_standalone(query: &str, small: bool, use_rag: bool) -> i32 {
 
     let cfg = config::get_config();
 
-    let context = if use_rag {
+    let model = if small {
+        &cfg.models.chat
+    } else {
+        &cfg.models.chat_large
+    };
+    let persona = match persona_name {
+        Some(name) => match Persona::resolve(name, cfg, model, use_rag) {
+            Ok(p) => p,
+            Err(e) => {
+                print_error(&e);
+                return 1;
+            }
+        },
+        None => Persona::default_with(model, use_rag),
+    };
+
+    let context = if persona.rag {
         match fetch_context_for_query(&rt, query, cfg) {
             Ok(ctx) => {
                 if ctx.is_empty() {
@@ -1095,15 +1130,12 @@ fn execute_chat_standalone(query: &str, small: bool, use_rag: bool) -> i32 {
         vec![]
     };
 
-    let model = if small {
-        &cfg.models.chat
-    } else {
-        &cfg.models.chat_large
-    };
-
-    print_info(&format!("Using {} via {}", model.name, model.provider));
+    print_info(&format!(
+        "Using {} via {}",
+        persona.model.name, persona.model.provider
+    ));
 
-    match rt.block_on(chat::generate_chat(query, &context, model)) {
+    match rt.block_on(chat::generate_persona_chat(query, &context, &persona)) {
         Ok(response) => {
             println!("\n{response}");
             0
@@ -1273,7 +1305,7 @@ fn models_list_standalone() -> i32 {
 
 fn cmd_models_set(model_type: Option<String>) -> i32 {
     // 1. Select provider
-    let providers = &["ollama", "openrouter"];
+    let providers = &["ollama", "openrouter", "openai_compatible"];
     let provider_idx = match Select::new()
         .with_prompt("Select provider")
         .items(providers)
@@ -1360,10 +1392,49 @@ fn fetch_provider_models(provider: &str) -> Result<Vec<String>, String> {
     match provider {
         "ollama" => ollama::fetch_ollama_models_blocking(),
         "openrouter" => fetch_openrouter_models(),
+        "openai_compatible" => fetch_openai_compatible_models(),
         other => Err(format!("Unknown provider: {other}")),
     }
 }
 
+/// List models from the configured OpenAI-compatible server (`GET {base_url}/models`).
+fn fetch_openai_compatible_models() -> Result<Vec<String>, String> {
+    let endpoint = &config::get_config().openai_compatible;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {e}"))?;
+
+    let mut request = client.get(format!("{}/models", endpoint.base_url));
+    if let Some(key) = endpoint.api_key() {
+        request = request.bearer_auth(key);
+    }
+    for (name, value) in &endpoint.headers {
+        request = request.header(name, value);
+    }
+    let resp = request
+        .send()
+        .map_err(|e| format!("Request to {} failed: {e}", endpoint.base_url))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        return Err(format!("{} returned {status}", endpoint.base_url));
+    }
+
+    let json: serde_json::Value = resp
+        .json()
+        .map_err(|e| format!("Failed to parse models response: {e}"))?;
+
+    let models = json["data"]
+        .as_array()
+        .ok_or("No data array in response")?
+        .iter()
+        .filter_map(|m| m["id"].as_str().map(|s| s.to_string()))
+        .collect();
+
+    Ok(models)
+}
+
 fn fetch_openrouter_models() -> Result<Vec<String>, String> {
     let client = reqwest::blocking::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
@@ -1403,6 +1474,15 @@ fn cmd_history(action: Option<HistoryAction>) -> i32 {
         None | Some(HistoryAction::List) => cmd_history_list(),
         Some(HistoryAction::Clear) => cmd_history_clear(),
         Some(HistoryAction::Remove { id }) => cmd_history_remove(&id),
+        Some(HistoryAction::Stats { json }) => cmd_history_stats(json),
+        Some(HistoryAction::Export { file }) => cmd_history_export(file.as_deref()),
+        Some(HistoryAction::Import { file }) => cmd_history_import(&file),
+        Some(HistoryAction::Sync) => cmd_history_sync(),
+        Some(HistoryAction::Pin { id }) => cmd_history_mark(&id, history::MarkAction::Pin),
+        Some(HistoryAction::Unpin { id }) => cmd_history_mark(&id, history::MarkAction::Unpin),
+        Some(HistoryAction::Hide { id }) => cmd_history_mark(&id, history::MarkAction::Hide),
+        Some(HistoryAction::Unhide { id }) => cmd_history_mark(&id, history::MarkAction::Unhide),
+        Some(HistoryAction::ListPinned) => cmd_history_list_pinned(),
     }
 }
 
@@ -1439,8 +1519,13 @@ fn cmd_history_list() -> i32 {
     print_heading(&format!("Recent History ({total_count} entries)"));
     println!();
 
+    let running = crate::actions::launch::running_app_ids();
     for entry in &entries {
-        print_kv(&entry.id, &entry.name);
+        if running.contains(&entry.id) {
+            print_kv(&entry.id, &format!("{} (running)", entry.name));
+        } else {
+            print_kv(&entry.id, &entry.name);
+        }
     }
 
     println!();
@@ -1503,6 +1588,263 @@ fn cmd_history_remove(id: &str) -> i32 {
     }
 }
 
+fn cmd_history_export(file: Option<&std::path::Path>) -> i32 {
+    let conn = match history::open_history_db() {
+        Ok(c) => c,
+        Err(e) => {
+            print_error(&format!("Failed to open history DB: {e}"));
+            return 1;
+        }
+    };
+
+    let export = match history_sync::export(&conn, &history_sync::machine_name(), false) {
+        Ok(e) => e,
+        Err(e) => {
+            print_error(&format!("Failed to export history: {e}"));
+            return 1;
+        }
+    };
+
+    let Some(file) = file else {
+        if let Err(e) = super::output::print_json(&export) {
+            print_error(&format!("JSON serialization failed: {e}"));
+            return 1;
+        }
+        return 0;
+    };
+    let written = serde_json::to_string_pretty(&export)
+        .map_err(|e| e.to_string())
+        .and_then(|json| std::fs::write(file, json).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        print_error(&format!("Failed to write {}: {e}", file.display()));
+        return 1;
+    }
+    print_success(&format!(
+        "Exported {} history entries to {}",
+        export.entries.len(),
+        file.display()
+    ));
+    0
+}
+
+fn cmd_history_import(file: &std::path::Path) -> i32 {
+    let export = match history_sync::read_export(file) {
+        Ok(e) => e,
+        Err(e) => {
+            print_error(&format!("Failed to read {}: {e}", file.display()));
+            return 1;
+        }
+    };
+
+    let conn = match history::open_history_db() {
+        Ok(c) => c,
+        Err(e) => {
+            print_error(&format!("Failed to open history DB: {e}"));
+            return 1;
+        }
+    };
+
+    match history_sync::import(&conn, &export) {
+        Ok(merged) => {
+            print_success(&format!("Merged {merged} history entries"));
+            0
+        }
+        Err(e) => {
+            print_error(&format!("Failed to import history: {e}"));
+            1
+        }
+    }
+}
+
+fn cmd_history_sync() -> i32 {
+    let Some(dir) = history_sync::configured_dir() else {
+        print_error("History sync is off. Set history.sync_dir in the config file.");
+        return 1;
+    };
+
+    let db = match history::open_history_db() {
+        Ok(c) => history::DbState::new(c),
+        Err(e) => {
+            print_error(&format!("Failed to open history DB: {e}"));
+            return 1;
+        }
+    };
+
+    match history_sync::sync_dir(&db, &dir, &history_sync::machine_name()) {
+        Ok(stats) => {
+            print_success(&format!(
+                "Synced with {} machines ({} entries updated)",
+                stats.peers, stats.merged
+            ));
+            0
+        }
+        Err(e) => {
+            print_error(&format!("History sync failed: {e}"));
+            1
+        }
+    }
+}
+
+fn cmd_history_mark(id: &str, action: history::MarkAction) -> i32 {
+    let id = id.trim();
+    if id.is_empty() {
+        print_error("ID cannot be empty. Use 'burrow history list' to see available IDs.");
+        return 1;
+    }
+
+    let conn = match history::open_history_db() {
+        Ok(c) => c,
+        Err(e) => {
+            print_error(&format!("Failed to open history DB: {e}"));
+            return 1;
+        }
+    };
+
+    let outcome = match action {
+        history::MarkAction::Unpin => history::unpin(&conn, id).map_err(|e| e.to_string()),
+        history::MarkAction::Unhide => history::unhide(&conn, id).map_err(|e| e.to_string()),
+        _ => {
+            let found = match history::find_launch(&conn, id) {
+                Ok(found) => found,
+                Err(e) => {
+                    print_error(&format!("Failed to query history: {e}"));
+                    return 1;
+                }
+            };
+            let Some(result) = found.or_else(|| crate::commands::apps::cached_result_for(id))
+            else {
+                print_error(&format!("No app or history entry found with id '{id}'"));
+                return 1;
+            };
+            history::apply_mark(&conn, &result, action).map(|_| true)
+        }
+    };
+
+    match (outcome, action) {
+        (Ok(true), history::MarkAction::Pin) => print_success(&format!("Pinned '{id}'")),
+        (Ok(true), history::MarkAction::Hide) => print_success(&format!("Hid '{id}'")),
+        (Ok(true), history::MarkAction::Unhide) => print_success(&format!("Unhid '{id}'")),
+        (Ok(true), _) => print_success(&format!("Unpinned '{id}'")),
+        (Ok(false), history::MarkAction::Unhide) => {
+            print_error(&format!("'{id}' is not hidden"));
+            return 1;
+        }
+        (Ok(false), _) => {
+            print_error(&format!("'{id}' is not pinned"));
+            return 1;
+        }
+        (Err(e), _) => {
+            print_error(&format!("Failed to update '{id}': {e}"));
+            return 1;
+        }
+    }
+    0
+}
+
+fn cmd_history_list_pinned() -> i32 {
+    let conn = match history::open_history_db() {
+        Ok(c) => c,
+        Err(e) => {
+            print_error(&format!("Failed to open history DB: {e}"));
+            return 1;
+        }
+    };
+
+    let marks = match history::query_marks(&conn) {
+        Ok(m) => m,
+        Err(e) => {
+            print_error(&format!("Failed to query pins: {e}"));
+            return 1;
+        }
+    };
+    let hidden = match history::list_hidden(&conn) {
+        Ok(h) => h,
+        Err(e) => {
+            print_error(&format!("Failed to query hidden entries: {e}"));
+            return 1;
+        }
+    };
+
+    if marks.pinned.is_empty() && hidden.is_empty() {
+        print_info("Nothing pinned or hidden");
+        return 0;
+    }
+    if !marks.pinned.is_empty() {
+        print_heading(&format!("Pinned ({})", marks.pinned.len()));
+        for result in &marks.pinned {
+            print_kv(&result.id, &result.name);
+        }
+    }
+    if !hidden.is_empty() {
+        if !marks.pinned.is_empty() {
+            println!();
+        }
+        print_heading(&format!("Hidden ({})", hidden.len()));
+        for (id, name) in &hidden {
+            print_kv(id, name);
+        }
+    }
+    0
+}
+
+fn cmd_history_stats(json: bool) -> i32 {
+    let conn = match history::open_history_db() {
+        Ok(c) => c,
+        Err(e) => {
+            print_error(&format!("Failed to open history DB: {e}"));
+            return 1;
+        }
+    };
+
+    let stats = match history::frecency_stats(&conn) {
+        Ok(s) => s,
+        Err(e) => {
+            print_error(&format!("Failed to compute frecency: {e}"));
+            return 1;
+        }
+    };
+
+    if json {
+        if let Err(e) = super::output::print_json_compact(&stats) {
+            print_error(&format!("JSON serialization failed: {e}"));
+            return 1;
+        }
+        return 0;
+    }
+
+    if stats.is_empty() {
+        print_info("No history entries");
+        return 0;
+    }
+
+    let config = config::get_config();
+    let model = match config.history.frecency_model.as_str() {
+        "half_life" => format!("half-life, {} days", config.history.frecency_half_life_days),
+        _ => "buckets".to_string(),
+    };
+    print_heading(&format!("Frecency ({model})"));
+    println!();
+    for stat in &stats {
+        let days = stat.last_used_days.floor() as i64;
+        let last = match days {
+            0 => "today".to_string(),
+            1 => "yesterday".to_string(),
+            d => format!("{d} days ago"),
+        };
+        print_kv(
+            &stat.id,
+            &format!(
+                "{:.2}  {} · {} · {} launches · {last}",
+                stat.score,
+                stat.name,
+                history::stored_category(stat.category).as_str(),
+                stat.count
+            ),
+        );
+    }
+    0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,585 @@
+//! AppImage discovery for the app index.
+//!
+//! AppImages in the configured directories (`[apps] appimage_dirs`, default
+//! `~/Applications`) get a generated desktop file in
+//! `~/.cache/burrow/appimages/applications`, which the app index scans like
+//! any other applications directory. The embedded desktop file and icon are
+//! read from the AppImage's squashfs with `unsquashfs`, without running the
+//! AppImage. When extraction fails the AppImage is still listed under its
+//! file name.
+
+use std::io::Read;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Key added to generated desktop files; marks the entry as an AppImage.
+pub const APPIMAGE_PATH_KEY: &str = "X-AppImage-Path";
+const ID_PREFIX: &str = "appimage-";
+const STAMP_FILE: &str = "stamp";
+const EXTRACT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Extracts the squashfs payload (starting at `offset`) of an AppImage into a directory.
+type Extractor<'a> = dyn Fn(&Path, u64, &Path) -> Result<(), String> + 'a;
+
+/// Where generated desktop files and extracted icons live.
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("burrow/appimages")
+}
+
+/// Applications directory holding the generated desktop files.
+pub fn applications_dir(cache: &Path) -> PathBuf {
+    cache.join("applications")
+}
+
+/// `[apps] appimage_dirs` with `~/` expanded.
+pub fn configured_dirs() -> Vec<PathBuf> {
+    let dirs = crate::config::try_get_config()
+        .map(|c| c.apps.appimage_dirs.clone())
+        .unwrap_or_else(|| crate::config::AppsConfig::default().appimage_dirs);
+    dirs.iter()
+        .map(|dir| crate::indexer::expand_tilde(dir))
+        .collect()
+}
+
+/// Bring the generated desktop files in line with the AppImages on disk.
+/// Returns the number of AppImages listed.
+pub fn sync(appimage_dirs: &[PathBuf], cache: &Path) -> usize {
+    sync_with(appimage_dirs, cache, &unsquashfs)
+}
+
+fn sync_with(appimage_dirs: &[PathBuf], cache: &Path, extract: &Extractor<'_>) -> usize {
+    let apps_dir = applications_dir(cache);
+    if let Err(e) = std::fs::create_dir_all(&apps_dir) {
+        tracing::warn!(path = %apps_dir.display(), error = %e, "failed to create AppImage cache");
+        return 0;
+    }
+
+    let mut ids = std::collections::HashSet::new();
+    for path in find_appimages(appimage_dirs) {
+        let id = appimage_id(&path);
+        if !ids.insert(id.clone()) {
+            tracing::debug!(path = %path.display(), id, "skipping AppImage with duplicate name");
+            continue;
+        }
+        if let Err(e) = update_entry(&path, &id, cache, extract) {
+            tracing::warn!(path = %path.display(), error = %e, "failed to index AppImage");
+            ids.remove(&id);
+        }
+    }
+    remove_stale(cache, &ids);
+    ids.len()
+}
+
+/// Executable `*.AppImage` files directly inside `dirs`, sorted for stable ids.
+fn find_appimages(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !is_appimage_path(&path) {
+                continue;
+            }
+            match std::fs::metadata(&path) {
+                Ok(meta) if meta.is_file() && meta.permissions().mode() & 0o111 != 0 => {
+                    found.push(path)
+                }
+                Ok(meta) if meta.is_file() => {
+                    tracing::debug!(path = %path.display(), "skipping AppImage without execute permission")
+                }
+                _ => {}
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+pub fn is_appimage_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"))
+}
+
+/// Stable id from the file name without version or architecture, so updating
+/// `Obsidian-1.5.3.AppImage` to `Obsidian-1.6.0.AppImage` keeps its history.
+fn appimage_id(path: &Path) -> String {
+    let name = display_name(path).to_lowercase();
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{ID_PREFIX}{slug}")
+}
+
+/// `Obsidian-1.5.3-x86_64.AppImage` → `Obsidian`: the leading name parts, up
+/// to the first one starting with a digit or naming an architecture.
+fn display_name(path: &Path) -> String {
+    const ARCHES: &[&str] = &["x86_64", "amd64", "x64", "aarch64", "arm64", "i386", "i686"];
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // `x86_64` would otherwise split into `x86` and `64`
+    let normalized = stem.replace("x86_64", "x64").replace("X86_64", "x64");
+    let mut parts = Vec::new();
+    for part in normalized.split(['-', '_']) {
+        let is_version = part.starts_with(|c: char| c.is_ascii_digit())
+            || (part.starts_with(['v', 'V'])
+                && part[1..].starts_with(|c: char| c.is_ascii_digit()));
+        if !parts.is_empty()
+            && (is_version || ARCHES.iter().any(|arch| part.eq_ignore_ascii_case(arch)))
+        {
+            break;
+        }
+        parts.push(part);
+    }
+    let name = parts.join(" ").trim().to_string();
+    if name.is_empty() {
+        stem
+    } else {
+        name
+    }
+}
+
+/// Size, mtime and path; extraction is redone when any of them changes.
+fn fingerprint(path: &Path) -> Result<String, String> {
+    let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    Ok(format!("{} {mtime} {}", meta.len(), path.display()))
+}
+
+fn update_entry(
+    path: &Path,
+    id: &str,
+    cache: &Path,
+    extract: &Extractor<'_>,
+) -> Result<(), String> {
+    let path_str = path.to_str().ok_or("AppImage path is not UTF-8")?;
+    if !is_exec_safe(path_str) {
+        return Err("AppImage path contains characters that cannot be quoted in Exec".into());
+    }
+    let entry_dir = cache.join(id);
+    let desktop_path = applications_dir(cache).join(format!("{id}.desktop"));
+    let stamp = fingerprint(path)?;
+    let stamp_path = entry_dir.join(STAMP_FILE);
+    if desktop_path.exists() && std::fs::read_to_string(&stamp_path).ok().as_deref() == Some(&stamp)
+    {
+        return Ok(());
+    }
+
+    let root = entry_dir.join("root");
+    if root.exists() {
+        std::fs::remove_dir_all(&root).map_err(|e| e.to_string())?;
+    }
+    std::fs::create_dir_all(&entry_dir).map_err(|e| e.to_string())?;
+
+    let extracted = squashfs_offset(path)
+        .and_then(|offset| extract(path, offset, &root))
+        .and_then(|()| embedded_desktop(&root, path_str));
+    let content = match extracted {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::debug!(path = %path.display(), error = %e, "using file name for AppImage");
+            fallback_desktop(path, path_str)
+        }
+    };
+    std::fs::write(&desktop_path, content).map_err(|e| e.to_string())?;
+    std::fs::write(&stamp_path, stamp).map_err(|e| e.to_string())?;
+    tracing::info!(path = %path.display(), id, "indexed AppImage");
+    Ok(())
+}
+
+/// Drop generated entries (and their extracted files) for AppImages that are gone.
+fn remove_stale(cache: &Path, keep: &std::collections::HashSet<String>) {
+    let Ok(entries) = std::fs::read_dir(applications_dir(cache)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if !id.starts_with(ID_PREFIX) || keep.contains(id) {
+            continue;
+        }
+        tracing::info!(id, "removing entry for deleted AppImage");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_dir_all(cache.join(id));
+    }
+}
+
+/// Type 2 AppImages are an ELF runtime followed by a squashfs image; the image
+/// starts where the ELF section header table ends.
+fn squashfs_offset(path: &Path) -> Result<u64, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut header = [0u8; 64];
+    file.read_exact(&mut header)
+        .map_err(|e| format!("Failed to read ELF header: {e}"))?;
+    let offset = elf_end(&header).ok_or("Not an ELF AppImage")?;
+
+    use std::io::Seek;
+    let mut magic = [0u8; 4];
+    file.seek(std::io::SeekFrom::Start(offset))
+        .and_then(|_| file.read_exact(&mut magic))
+        .map_err(|e| format!("Failed to read squashfs header: {e}"))?;
+    if &magic != b"hsqs" {
+        return Err("No squashfs image after the ELF runtime".into());
+    }
+    Ok(offset)
+}
+
+/// `e_shoff + e_shentsize * e_shnum` for 32- and 64-bit, either endianness.
+fn elf_end(header: &[u8; 64]) -> Option<u64> {
+    if &header[..4] != b"\x7fELF" {
+        return None;
+    }
+    let little = match header[5] {
+        1 => true,
+        2 => false,
+        _ => return None,
+    };
+    let u16_at = |at: usize| {
+        let bytes = [header[at], header[at + 1]];
+        u64::from(if little {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let (shoff, shentsize, shnum) = match header[4] {
+        1 => {
+            let bytes: [u8; 4] = header[0x20..0x24].try_into().ok()?;
+            let shoff = if little {
+                u32::from_le_bytes(bytes)
+            } else {
+                u32::from_be_bytes(bytes)
+            };
+            (u64::from(shoff), u16_at(0x2E), u16_at(0x30))
+        }
+        2 => {
+            let bytes: [u8; 8] = header[0x28..0x30].try_into().ok()?;
+            let shoff = if little {
+                u64::from_le_bytes(bytes)
+            } else {
+                u64::from_be_bytes(bytes)
+            };
+            (shoff, u16_at(0x3A), u16_at(0x3C))
+        }
+        _ => return None,
+    };
+    shoff.checked_add(shentsize * shnum)
+}
+
+/// Extract the top-level desktop file and icons with `unsquashfs`.
+fn unsquashfs(path: &Path, offset: u64, dest: &Path) -> Result<(), String> {
+    let mut child = Command::new("unsquashfs")
+        .args(["-q", "-n", "-o"])
+        .arg(offset.to_string())
+        .arg("-d")
+        .arg(dest)
+        .arg(path)
+        .args(["*.desktop", "*.png", "*.svg", ".DirIcon"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run unsquashfs: {e}"))?;
+    match crate::process_timeout::wait_with_timeout(&mut child, EXTRACT_TIMEOUT) {
+        Ok(Some(status)) if status.success() => Ok(()),
+        Ok(Some(status)) => Err(format!("unsquashfs exited with {status}")),
+        Ok(None) => {
+            crate::process_timeout::kill_and_reap(&mut child);
+            Err("unsquashfs timed out".into())
+        }
+        Err(e) => Err(format!("Failed to wait for unsquashfs: {e}")),
+    }
+}
+
+/// Rewrite the AppImage's own desktop file to launch the AppImage and use the extracted icon.
+fn embedded_desktop(root: &Path, appimage: &str) -> Result<String, String> {
+    let mut desktop_files: Vec<PathBuf> = std::fs::read_dir(root)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "desktop"))
+        .collect();
+    desktop_files.sort();
+    let desktop = desktop_files
+        .first()
+        .ok_or("AppImage has no desktop file")?;
+    let content = std::fs::read_to_string(desktop).map_err(|e| e.to_string())?;
+    let icon_name = content
+        .lines()
+        .find_map(|line| line.strip_prefix("Icon="))
+        .unwrap_or("")
+        .trim();
+    let icon = find_icon(root, icon_name);
+    rewrite_desktop(&content, appimage, icon.as_deref())
+        .ok_or_else(|| "Invalid desktop file".into())
+}
+
+/// `<Icon>.png`/`<Icon>.svg` at the image root, else `.DirIcon` when it is a
+/// real PNG or SVG (it is often a symlink into `usr/share/icons`, which is not extracted).
+fn find_icon(root: &Path, icon_name: &str) -> Option<PathBuf> {
+    if !icon_name.is_empty() && !icon_name.contains('/') {
+        for ext in ["png", "svg"] {
+            let candidate = root.join(format!("{icon_name}.{ext}"));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+    let dir_icon = root.join(".DirIcon");
+    let bytes = std::fs::read(&dir_icon).ok()?;
+    let ext = if bytes.starts_with(b"\x89PNG") {
+        "png"
+    } else if String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).contains("<svg") {
+        "svg"
+    } else {
+        return None;
+    };
+    let icon = root.join(format!("dir-icon.{ext}"));
+    std::fs::write(&icon, bytes).ok()?;
+    Some(icon)
+}
+
+/// Point `Exec=` (entry and actions) at the AppImage, keeping its arguments and
+/// field codes, drop `TryExec=`, swap in the extracted icon and tag the entry
+/// with [`APPIMAGE_PATH_KEY`].
+fn rewrite_desktop(content: &str, appimage: &str, icon: Option<&Path>) -> Option<String> {
+    let mut out = String::with_capacity(content.len() + 128);
+    let mut section = "";
+    let mut has_entry = false;
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            section = trimmed.trim_end();
+            out.push_str(line);
+            out.push('\n');
+            if section == "[Desktop Entry]" {
+                has_entry = true;
+                out.push_str(&format!("{APPIMAGE_PATH_KEY}={appimage}\n"));
+            }
+            continue;
+        }
+        let in_entry = section == "[Desktop Entry]";
+        let in_app_section = in_entry || section.starts_with("[Desktop Action ");
+        if in_app_section {
+            if let Some(exec) = trimmed.strip_prefix("Exec=") {
+                out.push_str(&format!("Exec=\"{appimage}\"{}\n", exec_arguments(exec)));
+                continue;
+            }
+        }
+        if in_entry && (trimmed.starts_with("TryExec=") || trimmed.starts_with(APPIMAGE_PATH_KEY)) {
+            continue;
+        }
+        if in_entry && trimmed.starts_with("Icon=") {
+            if let Some(icon) = icon {
+                out.push_str(&format!("Icon={}\n", icon.display()));
+                continue;
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    has_entry.then_some(out)
+}
+
+/// Everything after the program in an `Exec=` value, with its leading space.
+fn exec_arguments(exec: &str) -> &str {
+    let exec = exec.trim_start();
+    if let Some(quoted) = exec.strip_prefix('"') {
+        let mut escaped = false;
+        for (i, c) in quoted.char_indices() {
+            match c {
+                '\\' if !escaped => escaped = true,
+                '"' if !escaped => return &quoted[i + 1..],
+                _ => escaped = false,
+            }
+        }
+        return "";
+    }
+    exec.find(char::is_whitespace).map_or("", |i| &exec[i..])
+}
+
+/// Paths needing escapes inside a quoted `Exec=` argument are skipped rather than escaped.
+fn is_exec_safe(path: &str) -> bool {
+    !path
+        .chars()
+        .any(|c| matches!(c, '"' | '`' | '$' | '\\' | '%' | '\n' | '\r'))
+}
+
+fn fallback_desktop(path: &Path, appimage: &str) -> String {
+    format!(
+        "[Desktop Entry]\nType=Application\nName={}\nExec=\"{appimage}\"\n{APPIMAGE_PATH_KEY}={appimage}\n",
+        display_name(path)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal 64-bit little-endian ELF header whose section table ends at `end`.
+    fn elf64_header(end: u64) -> Vec<u8> {
+        let mut header = vec![0u8; 64];
+        header[..4].copy_from_slice(b"\x7fELF");
+        header[4] = 2;
+        header[5] = 1;
+        let shentsize = 64u16;
+        let shnum = 2u16;
+        let shoff = end - u64::from(shentsize * shnum);
+        header[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        header[0x3A..0x3C].copy_from_slice(&shentsize.to_le_bytes());
+        header[0x3C..0x3E].copy_from_slice(&shnum.to_le_bytes());
+        header
+    }
+
+    fn write_appimage(dir: &Path, name: &str) -> PathBuf {
+        let mut bytes = elf64_header(512);
+        bytes.resize(512, 0);
+        bytes.extend_from_slice(b"hsqs");
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    /// Stands in for unsquashfs: writes a desktop file and icon into `dest`.
+    fn fake_extract(_path: &Path, offset: u64, dest: &Path) -> Result<(), String> {
+        assert_eq!(offset, 512);
+        std::fs::create_dir_all(dest).unwrap();
+        std::fs::write(
+            dest.join("obsidian.desktop"),
+            "[Desktop Entry]\nName=Obsidian\nExec=AppRun --no-sandbox %U\nTryExec=obsidian\nIcon=obsidian\nType=Application\n\n\
+             [Desktop Action new]\nName=New Vault\nExec=obsidian --new\n",
+        )
+        .unwrap();
+        std::fs::write(dest.join("obsidian.png"), b"\x89PNG\r\n").unwrap();
+        Ok(())
+    }
+
+    fn failing_extract(_path: &Path, _offset: u64, _dest: &Path) -> Result<(), String> {
+        Err("unsquashfs not installed".into())
+    }
+
+    #[test]
+    fn finds_squashfs_after_elf_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_appimage(dir.path(), "Tool.AppImage");
+        assert_eq!(squashfs_offset(&path).unwrap(), 512);
+
+        let not_elf = dir.path().join("script.AppImage");
+        std::fs::write(&not_elf, [b'#'; 80]).unwrap();
+        assert!(squashfs_offset(&not_elf).is_err());
+
+        let mut header = [0u8; 64];
+        header.copy_from_slice(&elf64_header(4096));
+        header[5] = 3;
+        assert_eq!(elf_end(&header), None);
+    }
+
+    #[test]
+    fn names_drop_versions_and_architectures() {
+        let name = |file: &str| display_name(Path::new(file));
+        assert_eq!(name("Obsidian-1.5.3.AppImage"), "Obsidian");
+        assert_eq!(name("balenaEtcher-1.18.11-x64.AppImage"), "balenaEtcher");
+        assert_eq!(name("nvim.appimage"), "nvim");
+        assert_eq!(name("Joplin_v2.14.AppImage"), "Joplin");
+        assert_eq!(
+            name("Super_Productivity-x86_64.AppImage"),
+            "Super Productivity"
+        );
+        assert_eq!(
+            appimage_id(Path::new("/a/Obsidian-1.5.3.AppImage")),
+            "appimage-obsidian"
+        );
+    }
+
+    #[test]
+    fn rewrites_exec_icon_and_actions() {
+        let content =
+            "[Desktop Entry]\nName=App\nExec=\"App Run\" --flag %F\nTryExec=app\nIcon=app\n\
+                       [Desktop Action x]\nExec=app --x\n[Other]\nExec=keep\n";
+        let out = rewrite_desktop(
+            content,
+            "/home/u/Applications/App.AppImage",
+            Some(Path::new("/c/app.png")),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "[Desktop Entry]\nX-AppImage-Path=/home/u/Applications/App.AppImage\nName=App\n\
+             Exec=\"/home/u/Applications/App.AppImage\" --flag %F\nIcon=/c/app.png\n\
+             [Desktop Action x]\nExec=\"/home/u/Applications/App.AppImage\" --x\n[Other]\nExec=keep\n"
+        );
+        assert!(rewrite_desktop("[Other]\nExec=x\n", "/a", None).is_none());
+        assert!(!is_exec_safe("/home/u/$HOME.AppImage"));
+    }
+
+    #[test]
+    fn sync_generates_updates_and_removes_entries() {
+        let apps = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let obsidian = write_appimage(apps.path(), "Obsidian-1.5.3.AppImage");
+        let plain = write_appimage(apps.path(), "Tool-2.0-x86_64.AppImage");
+        std::fs::write(apps.path().join("notes.txt"), "x").unwrap();
+        let no_exec = write_appimage(apps.path(), "Locked.AppImage");
+        std::fs::set_permissions(&no_exec, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let extract = |path: &Path, offset: u64, dest: &Path| {
+            if path == obsidian {
+                fake_extract(path, offset, dest)
+            } else {
+                failing_extract(path, offset, dest)
+            }
+        };
+        let dirs = vec![apps.path().to_path_buf()];
+        assert_eq!(sync_with(&dirs, cache.path(), &extract), 2);
+
+        let generated = applications_dir(cache.path());
+        let desktop = std::fs::read_to_string(generated.join("appimage-obsidian.desktop")).unwrap();
+        assert!(desktop.contains(&format!("Exec=\"{}\" --no-sandbox %U", obsidian.display())));
+        assert!(desktop.contains(&format!(
+            "Icon={}",
+            cache
+                .path()
+                .join("appimage-obsidian/root/obsidian.png")
+                .display()
+        )));
+        assert!(!desktop.contains("TryExec"));
+        let fallback = std::fs::read_to_string(generated.join("appimage-tool.desktop")).unwrap();
+        assert!(fallback.contains("Name=Tool\n"));
+        assert!(fallback.contains(&format!("Exec=\"{}\"\n", plain.display())));
+
+        // Unchanged AppImages are not extracted again
+        let panicking = |_: &Path, _: u64, _: &Path| -> Result<(), String> {
+            panic!("should use the cached entry")
+        };
+        assert_eq!(sync_with(&dirs, cache.path(), &panicking), 2);
+
+        std::fs::remove_file(&plain).unwrap();
+        assert_eq!(sync_with(&dirs, cache.path(), &extract), 1);
+        assert!(!generated.join("appimage-tool.desktop").exists());
+        assert!(!cache.path().join("appimage-tool").exists());
+        assert!(generated.join("appimage-obsidian.desktop").exists());
+    }
+}
@@ -1,3 +1,5 @@
+use super::history::Marks;
+use super::{appimage, desktop_exec};
 use crate::context::AppContext;
 use crate::icons;
 use crate::router::{Category, SearchResult};
@@ -6,23 +8,185 @@ use notify::{self, RecommendedWatcher, RecursiveMode, Watcher};
 use nucleo::pattern::{CaseMatching, Normalization, Pattern};
 use nucleo::Matcher;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
 use std::sync::{
     atomic::{AtomicBool, AtomicU64, Ordering},
     Arc, Mutex, RwLock,
 };
 use std::time::Duration;
 
+/// Separator between an app id and a desktop action id in result ids
+/// (`firefox#new-private-window`). Action ids are `[A-Za-z0-9-]` per the spec.
+const ACTION_ID_SEPARATOR: char = '#';
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct DesktopEntry {
     id: String,
     name: String,
+    /// Exec with field codes stripped, for display and history
     exec: String,
+    /// Raw `Exec=` value, expanded with real arguments at launch
+    exec_template: String,
+    /// `Terminal=true`: run inside the user's terminal
+    terminal: bool,
+    /// `Path=` working directory, empty for the home directory
+    working_dir: String,
+    /// Desktop file location, for `%k`
+    source_path: PathBuf,
     icon: String,
     comment: String,
+    generic_name: String,
+    keywords: Vec<String>,
+    categories: Vec<String>,
+    /// `MimeType=` list, e.g. `["image/png", "image/jpeg"]`
+    mime_types: Vec<String>,
+    /// `StartupWMClass=`, the window class/app_id when it differs from the desktop id
+    startup_wm_class: String,
+    /// `NoDisplay=true`: not listed, but still offered for its MIME types
     no_display: bool,
+    /// Hidden, missing TryExec or excluded by OnlyShowIn/NotShowIn
+    hidden: bool,
+    /// Packaging channel the entry was installed through
+    source: AppSource,
+    /// `[Desktop Action <id>]` sections listed in `Actions=`
+    actions: Vec<DesktopAction>,
+}
+
+/// How an app was installed. Shown next to sandboxed and bundled apps, and
+/// used to pick one entry when the same app comes from several channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+enum AppSource {
+    /// Distribution package or local desktop file
+    #[default]
+    Native,
+    Flatpak,
+    Snap,
+    AppImage,
+}
+
+impl AppSource {
+    /// Tag from the keys Flatpak, snapd and Burrow's AppImage cache write, or
+    /// from the export directory for older Flatpak/Snap entries.
+    fn detect(path: &Path, has_key: impl Fn(&str) -> bool) -> Self {
+        let path = path.to_string_lossy();
+        if has_key(appimage::APPIMAGE_PATH_KEY) {
+            Self::AppImage
+        } else if has_key("X-Flatpak") || path.contains("/flatpak/exports/") {
+            Self::Flatpak
+        } else if has_key("X-SnapInstanceName") || path.contains("/snapd/desktop/") {
+            Self::Snap
+        } else {
+            Self::Native
+        }
+    }
+
+    fn label(self) -> Option<&'static str> {
+        match self {
+            Self::Native => None,
+            Self::Flatpak => Some("Flatpak"),
+            Self::Snap => Some("Snap"),
+            Self::AppImage => Some("AppImage"),
+        }
+    }
+}
+
+/// A desktop action such as "New Private Window", launched as a child result of its app.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DesktopAction {
+    id: String,
+    name: String,
+    exec: String,
+    exec_template: String,
+    /// Action icon, empty to use the app's icon
+    icon: String,
+}
+
+/// Interpreters and wrappers whose name says nothing about the app they run.
+const GENERIC_EXECUTABLES: &[&str] = &[
+    "env", "sh", "bash", "python", "python3", "java", "flatpak", "snap", "wine",
+];
+
+impl DesktopEntry {
+    /// Lowercase names a window (or process) of this app may carry: StartupWMClass,
+    /// the desktop id, its last reverse-DNS component and the executable name.
+    fn window_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        let mut push = |key: &str| {
+            let key = key.to_lowercase();
+            if !key.is_empty() && !keys.contains(&key) {
+                keys.push(key);
+            }
+        };
+        push(&self.startup_wm_class);
+        push(&self.id);
+        if let Some((_, last)) = self.id.rsplit_once('.') {
+            push(last);
+        }
+        let program = self.exec.split_whitespace().next().unwrap_or("");
+        let program = program.rsplit('/').next().unwrap_or(program);
+        if !GENERIC_EXECUTABLES.contains(&program) {
+            push(program);
+        }
+        keys
+    }
+
+    fn find_action(&self, action_id: &str) -> Option<&DesktopAction> {
+        self.actions.iter().find(|action| action.id == action_id)
+    }
+
+    /// Build the commands to launch this entry (or one of its actions) with
+    /// optional files/URLs. Terminal apps are wrapped in `terminal_cmd`.
+    fn launch_commands(
+        &self,
+        action: Option<&DesktopAction>,
+        targets: &[String],
+        terminal_cmd: &str,
+    ) -> Result<Vec<AppLaunch>, String> {
+        let exec = action.map_or(&self.exec_template, |a| &a.exec_template);
+        let ctx = desktop_exec::ExecContext {
+            icon: action
+                .map(|a| a.icon.as_str())
+                .filter(|icon| !icon.is_empty())
+                .unwrap_or(&self.icon),
+            name: &self.name,
+            desktop_file: &self.source_path,
+            targets,
+        };
+        let working_dir = (!self.working_dir.is_empty()).then(|| PathBuf::from(&self.working_dir));
+        desktop_exec::expand_exec(exec, &ctx)?
+            .into_iter()
+            .map(|argv| {
+                let argv = if self.terminal {
+                    desktop_exec::wrap_in_terminal(argv, terminal_cmd)?
+                } else {
+                    argv
+                };
+                Ok(AppLaunch {
+                    argv,
+                    working_dir: working_dir.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// An app that declares which MIME types it can open, for the "open with" list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimeHandler {
+    pub id: String,
+    pub name: String,
+    pub icon: String,
+    pub mime_types: Vec<String>,
+}
+
+/// A fully expanded app command, spawned without a shell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppLaunch {
+    pub argv: Vec<String>,
+    /// `Path=` from the desktop file; `None` starts in the home directory
+    pub working_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -40,10 +204,26 @@ pub struct RefreshAppsResult {
 
 pub struct AppIndexState {
     entries: RwLock<Vec<DesktopEntry>>,
+    /// `NoDisplay=true` apps, kept only to open files
+    unlisted: RwLock<Vec<DesktopEntry>>,
     revision: AtomicU64,
     watcher_started: AtomicBool,
     watcher: Mutex<Option<RecommendedWatcher>>,
     source_dirs: Vec<PathBuf>,
+    appimages: Option<AppImageSources>,
+}
+
+/// AppImage directories to scan and the cache their desktop files are generated into.
+struct AppImageSources {
+    dirs: Vec<PathBuf>,
+    cache: PathBuf,
+}
+
+impl AppImageSources {
+    fn sync(&self) {
+        let count = appimage::sync(&self.dirs, &self.cache);
+        tracing::debug!(count, "AppImages indexed");
+    }
 }
 
 #[derive(Default)]
@@ -59,19 +239,28 @@ impl Default for AppIndexState {
 }
 
 impl AppIndexState {
+    /// Load the desktop files and the AppImage entries already extracted to the
+    /// cache. New or changed AppImages are picked up by [`Self::start_watcher`],
+    /// which extracts them off the caller's thread.
     pub fn new() -> Self {
-        Self::new_with_dirs(desktop_dirs())
+        let appimages = AppImageSources {
+            dirs: appimage::configured_dirs(),
+            cache: appimage::cache_dir(),
+        };
+        Self::new_with_dirs(index_dirs(), Some(appimages))
     }
 
-    fn new_with_dirs(dirs: Vec<PathBuf>) -> Self {
+    fn new_with_dirs(dirs: Vec<PathBuf>, appimages: Option<AppImageSources>) -> Self {
         let source_dirs = dedupe_dirs(dirs);
-        let entries = load_desktop_entries_from_dirs(&source_dirs);
+        let (entries, unlisted) = load_desktop_entries_from_dirs(&source_dirs);
         Self {
             entries: RwLock::new(entries),
+            unlisted: RwLock::new(unlisted),
             revision: AtomicU64::new(1),
             watcher_started: AtomicBool::new(false),
             watcher: Mutex::new(None),
             source_dirs,
+            appimages,
         }
     }
 
@@ -94,16 +283,23 @@ impl AppIndexState {
     }
 
     pub fn refresh(&self) -> Result<RefreshAppsResult, String> {
+        if let Some(appimages) = &self.appimages {
+            appimages.sync();
+        }
         self.refresh_from_dirs(&self.source_dirs)
     }
 
     fn refresh_from_dirs(&self, dirs: &[PathBuf]) -> Result<RefreshAppsResult, String> {
-        let next_entries = load_desktop_entries_from_dirs(dirs);
+        let (next_entries, next_unlisted) = load_desktop_entries_from_dirs(dirs);
         let mut entries = self
             .entries
             .write()
             .unwrap_or_else(|poisoned| poisoned.into_inner());
-        if *entries == next_entries {
+        let mut unlisted = self
+            .unlisted
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if *entries == next_entries && *unlisted == next_unlisted {
             return Ok(RefreshAppsResult {
                 changed: false,
                 revision: self.revision.load(Ordering::SeqCst),
@@ -112,6 +308,7 @@ impl AppIndexState {
         }
 
         *entries = next_entries;
+        *unlisted = next_unlisted;
         let revision = self.revision.fetch_add(1, Ordering::SeqCst) + 1;
         Ok(RefreshAppsResult {
             changed: true,
@@ -125,7 +322,103 @@ impl AppIndexState {
         fuzzy_search(&entries, query)
     }
 
+    /// Search, boosting apps previously picked for this query (see [`fuzzy_search_ranked`]).
+    pub fn search_with_selections(
+        &self,
+        query: &str,
+        selections: &HashMap<String, f64>,
+        weight: f64,
+    ) -> Vec<SearchResult> {
+        let entries = self.snapshot();
+        fuzzy_search_ranked(&entries, query, selections, weight)
+    }
+
+    /// Resolve the exec for an app id or an `app#action` desktop action id.
     pub fn resolve_exec(&self, id: &str) -> Option<String> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(entry) = entries.iter().find(|entry| entry.id == id) {
+            return Some(entry.exec.clone());
+        }
+        find_desktop_action(&entries, id).map(|(_, action)| action.exec.clone())
+    }
+
+    /// Expand the launch commands for an app id or an `app#action` desktop action id.
+    pub fn launch_commands(&self, id: &str, targets: &[String]) -> Result<Vec<AppLaunch>, String> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let terminal_cmd = crate::actions::utils::get_terminal_cmd();
+        if let Some(entry) = entries.iter().find(|entry| entry.id == id) {
+            return entry.launch_commands(None, targets, &terminal_cmd);
+        }
+        if let Some((entry, action)) = find_desktop_action(&entries, id) {
+            return entry.launch_commands(Some(action), targets, &terminal_cmd);
+        }
+        let unlisted = self
+            .unlisted
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let entry = unlisted
+            .iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("Unknown app id: {id}"))?;
+        entry.launch_commands(None, targets, &terminal_cmd)
+    }
+
+    /// Window match keys for every visible app, as `(app id, keys)`.
+    pub fn window_keys(&self) -> Vec<(String, Vec<String>)> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .iter()
+            .map(|entry| (entry.id.clone(), entry.window_keys()))
+            .collect()
+    }
+
+    /// Window match keys for an app id. `None` for unknown ids and desktop
+    /// actions, which always launch.
+    pub fn window_keys_for(&self, id: &str) -> Option<Vec<String>> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(DesktopEntry::window_keys)
+    }
+
+    /// Apps with their `MimeType=` lists: visible apps in index order, then
+    /// `NoDisplay` ones, which a `mimeapps.list` default may well name.
+    pub fn mime_handlers(&self) -> Vec<MimeHandler> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let unlisted = self
+            .unlisted
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .iter()
+            .chain(unlisted.iter())
+            .map(|entry| MimeHandler {
+                id: entry.id.clone(),
+                name: entry.name.clone(),
+                icon: entry.icon.clone(),
+                mime_types: entry.mime_types.clone(),
+            })
+            .collect()
+    }
+
+    /// `Icon=` name of an app id, `None` for unknown ids.
+    pub fn icon_name(&self, id: &str) -> Option<String> {
         let entries = self
             .entries
             .read()
@@ -133,7 +426,29 @@ impl AppIndexState {
         entries
             .iter()
             .find(|entry| entry.id == id)
-            .map(|entry| entry.exec.clone())
+            .map(|entry| entry.icon.clone())
+    }
+
+    /// Search result for an app id or an `app#action` desktop action id.
+    pub fn result_for(&self, id: &str) -> Option<SearchResult> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        result_in(&entries, id)
+    }
+
+    /// Resolve the display name for an app id or an `app#action` desktop action id.
+    pub fn resolve_name(&self, id: &str) -> Option<String> {
+        let entries = self
+            .entries
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(entry) = entries.iter().find(|entry| entry.id == id) {
+            return Some(entry.name.clone());
+        }
+        let (entry, action) = find_desktop_action(&entries, id)?;
+        Some(action_display_name(entry, action))
     }
 
     pub fn start_watcher(self: &Arc<Self>) -> Result<(), String> {
@@ -145,6 +460,17 @@ impl AppIndexState {
             return Ok(());
         }
 
+        // Extracting AppImages runs unsquashfs per image, so catch up with
+        // images added since the last run in the background.
+        if self.appimages.is_some() {
+            let index = Arc::clone(self);
+            std::thread::spawn(move || {
+                if let Err(error) = index.refresh() {
+                    tracing::warn!(error = %error, "application cache refresh failed after AppImage sync");
+                }
+            });
+        }
+
         let index_weak = Arc::downgrade(self);
         let refresh_state = Arc::new(Mutex::new(WatcherRefreshState::default()));
         let refresh_state_for_callback = Arc::clone(&refresh_state);
@@ -226,7 +552,8 @@ impl AppIndexState {
         };
 
         let mut watched = 0usize;
-        for dir in &self.source_dirs {
+        let appimage_dirs = self.appimages.iter().flat_map(|a| &a.dirs);
+        for dir in self.source_dirs.iter().chain(appimage_dirs) {
             // Note: only directories that exist at this point are watched.
             // Directories created after startup require a manual #refresh to be scanned.
             if !dir.exists() {
@@ -260,7 +587,7 @@ impl AppIndexState {
 
     #[cfg(test)]
     pub(crate) fn new_for_test(dirs: Vec<PathBuf>) -> Self {
-        Self::new_with_dirs(dirs)
+        Self::new_with_dirs(dirs, None)
     }
 
     #[cfg(test)]
@@ -270,6 +597,29 @@ impl AppIndexState {
     }
 }
 
+/// Desktop file directories plus the cached AppImage entries.
+fn index_dirs() -> Vec<PathBuf> {
+    let mut dirs = desktop_dirs();
+    // Generated AppImage entries rank below installed desktop files
+    dirs.push(appimage::applications_dir(&appimage::cache_dir()));
+    dirs
+}
+
+/// Search result for an app id from the installed and cached desktop files,
+/// for callers without an [`AppIndexState`]. AppImages are not re-extracted.
+pub fn cached_result_for(id: &str) -> Option<SearchResult> {
+    let (entries, _) = load_desktop_entries_from_dirs(&dedupe_dirs(index_dirs()));
+    result_in(&entries, id)
+}
+
+fn result_in(entries: &[DesktopEntry], id: &str) -> Option<SearchResult> {
+    if let Some(entry) = entries.iter().find(|entry| entry.id == id) {
+        return Some(entry_to_result(entry, Category::App));
+    }
+    let (entry, action) = find_desktop_action(entries, id)?;
+    Some(action_to_result(entry, action, Category::App))
+}
+
 fn desktop_dirs() -> Vec<PathBuf> {
     let mut dirs = vec![];
     if let Ok(data_dirs) = std::env::var("XDG_DATA_DIRS") {
@@ -300,23 +650,58 @@ fn desktop_dirs() -> Vec<PathBuf> {
     dirs
 }
 
-fn load_desktop_entries_from_dirs(dirs: &[PathBuf]) -> Vec<DesktopEntry> {
+/// Desktop entries under `dirs`: the apps to list, and the `NoDisplay` ones
+/// only offered to open files.
+fn load_desktop_entries_from_dirs(dirs: &[PathBuf]) -> (Vec<DesktopEntry>, Vec<DesktopEntry>) {
+    let env = EntryEnv::from_env();
     let mut entries = Vec::new();
+    let mut unlisted = Vec::new();
     let mut seen_ids = std::collections::HashSet::new();
     for dir in dirs {
         let pattern = dir.join("*.desktop");
         if let Ok(paths) = glob::glob(pattern.to_str().unwrap_or("")) {
             for path in paths.flatten() {
-                if let Some(entry) = parse_desktop_file(&path) {
-                    if !entry.no_display && seen_ids.insert(entry.id.clone()) {
+                if let Some(entry) = parse_desktop_file(&path, &env) {
+                    // Earlier dirs take precedence, even when their entry is hidden
+                    if !seen_ids.insert(entry.id.clone()) || entry.hidden {
+                        continue;
+                    }
+                    if entry.no_display {
+                        unlisted.push(entry);
+                    } else {
                         entries.push(entry);
                     }
                 }
             }
         }
     }
+    let mut entries = dedupe_channels(entries);
     entries.sort_by(|a, b| a.id.cmp(&b.id));
-    entries
+    unlisted.sort_by(|a, b| a.id.cmp(&b.id));
+    (entries, unlisted)
+}
+
+/// Keep one entry per app installed through several channels (e.g. a distro
+/// package and a Flatpak): same name and an overlapping window key such as
+/// `firefox` for `firefox.desktop` and `org.mozilla.firefox.desktop`. Native
+/// entries win over Flatpak, then Snap, then AppImage.
+fn dedupe_channels(mut entries: Vec<DesktopEntry>) -> Vec<DesktopEntry> {
+    entries.sort_by(|a, b| (a.source, &a.id).cmp(&(b.source, &b.id)));
+    let mut kept: Vec<(DesktopEntry, Vec<String>)> = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let keys = entry.window_keys();
+        let duplicate = kept.iter().find(|(other, other_keys)| {
+            other.source != entry.source
+                && other.name.to_lowercase() == entry.name.to_lowercase()
+                && keys.iter().any(|key| other_keys.contains(key))
+        });
+        if let Some((other, _)) = duplicate {
+            tracing::debug!(id = %entry.id, kept = %other.id, "skipping app installed through another channel");
+            continue;
+        }
+        kept.push((entry, keys));
+    }
+    kept.into_iter().map(|(entry, _)| entry).collect()
 }
 
 fn dedupe_dirs(dirs: Vec<PathBuf>) -> Vec<PathBuf> {
@@ -331,9 +716,29 @@ fn is_relevant_app_fs_path(path: &Path) -> bool {
         .and_then(|ext| ext.to_str())
         .map(|ext| ext.eq_ignore_ascii_case("desktop"))
         .unwrap_or(false)
+        || appimage::is_appimage_path(path)
+}
+
+/// Look up an `app#action` id. App ids (file stems) may themselves contain `#`,
+/// so split at the last separator.
+fn find_desktop_action<'a>(
+    entries: &'a [DesktopEntry],
+    id: &str,
+) -> Option<(&'a DesktopEntry, &'a DesktopAction)> {
+    let (app_id, action_id) = id.rsplit_once(ACTION_ID_SEPARATOR)?;
+    let entry = entries.iter().find(|entry| entry.id == app_id)?;
+    Some((entry, entry.find_action(action_id)?))
+}
+
+fn action_result_id(entry: &DesktopEntry, action: &DesktopAction) -> String {
+    format!("{}{ACTION_ID_SEPARATOR}{}", entry.id, action.id)
+}
+
+fn action_display_name(entry: &DesktopEntry, action: &DesktopAction) -> String {
+    format!("{}: {}", entry.name, action.name)
 }
 
-fn parse_desktop_file(path: &PathBuf) -> Option<DesktopEntry> {
+fn parse_desktop_file(path: &PathBuf, env: &EntryEnv) -> Option<DesktopEntry> {
     let entry = parse_entry(path).ok()?;
     let section = entry.section("Desktop Entry")?;
 
@@ -345,12 +750,20 @@ fn parse_desktop_file(path: &PathBuf) -> Option<DesktopEntry> {
             .unwrap_or("")
             .to_string()
     };
+    let localized = |key: &str| -> String {
+        localized_attr(section, key, &env.locales)
+            .unwrap_or("")
+            .to_string()
+    };
 
-    let name = section.attr("Name").first()?.to_string();
+    let name = localized_attr(section, "Name", &env.locales)?.to_string();
     let exec_raw = get_attr("Exec");
     let icon = get_attr("Icon");
-    let comment = get_attr("Comment");
-    let no_display = get_attr("NoDisplay") == "true";
+    let comment = localized("Comment");
+    let generic_name = localized("GenericName");
+    let keywords = split_list(&localized("Keywords"));
+    let categories = split_list(&get_attr("Categories"));
+    let mime_types = split_list(&get_attr("MimeType"));
     let entry_type = section
         .attr("Type")
         .first()
@@ -361,6 +774,15 @@ fn parse_desktop_file(path: &PathBuf) -> Option<DesktopEntry> {
         return None;
     }
 
+    // Hidden entries still shadow same-id entries from lower-priority dirs
+    let try_exec = get_attr("TryExec");
+    let hidden = get_attr("Hidden") == "true"
+        || (!try_exec.is_empty() && !is_executable_available(&try_exec))
+        || !env.shows(
+            &split_list(&get_attr("OnlyShowIn")),
+            &split_list(&get_attr("NotShowIn")),
+        );
+
     let exec = strip_field_codes(&exec_raw);
 
     let id = path
@@ -368,16 +790,163 @@ fn parse_desktop_file(path: &PathBuf) -> Option<DesktopEntry> {
         .map(|s| s.to_string_lossy().to_string())
         .unwrap_or_default();
 
+    // Actions=new-window;new-private-window;
+    let actions = split_list(&get_attr("Actions"))
+        .into_iter()
+        .filter_map(|action_id| {
+            let section = entry.section(format!("Desktop Action {action_id}"))?;
+            let name = localized_attr(section, "Name", &env.locales)?.to_string();
+            let exec_template = section.attr("Exec").first()?.to_string();
+            let exec = strip_field_codes(&exec_template);
+            if exec.is_empty() {
+                return None;
+            }
+            Some(DesktopAction {
+                id: action_id,
+                name,
+                exec,
+                exec_template,
+                icon: section
+                    .attr("Icon")
+                    .first()
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect();
+
     Some(DesktopEntry {
         id,
         name,
         exec,
+        exec_template: exec_raw,
+        terminal: get_attr("Terminal") == "true",
+        working_dir: get_attr("Path"),
+        source_path: path.clone(),
         icon,
         comment,
-        no_display,
+        generic_name,
+        keywords,
+        categories,
+        mime_types,
+        startup_wm_class: get_attr("StartupWMClass"),
+        no_display: get_attr("NoDisplay") == "true",
+        hidden,
+        source: AppSource::detect(path, |key| !section.attr(key).is_empty()),
+        actions,
     })
 }
 
+/// Locale and desktop environment used to localize and filter desktop entries.
+#[derive(Debug, Clone, Default)]
+struct EntryEnv {
+    /// Locale keys to try in order, e.g. `["de_DE", "de"]`
+    locales: Vec<String>,
+    /// `XDG_CURRENT_DESKTOP` entries, e.g. `["Hyprland"]`
+    desktops: Vec<String>,
+}
+
+impl EntryEnv {
+    fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Self {
+            locales: locale_candidates(&locale),
+            desktops: std::env::var("XDG_CURRENT_DESKTOP")
+                .unwrap_or_default()
+                .split(':')
+                .filter(|d| !d.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// Apply `OnlyShowIn`/`NotShowIn`. Desktop names compare case-insensitively.
+    fn shows(&self, only_show_in: &[String], not_show_in: &[String]) -> bool {
+        let current = |list: &[String]| {
+            list.iter()
+                .any(|d| self.desktops.iter().any(|c| c.eq_ignore_ascii_case(d)))
+        };
+        if !only_show_in.is_empty() && !current(only_show_in) {
+            return false;
+        }
+        !current(not_show_in)
+    }
+}
+
+/// Locale match order from the desktop entry spec for `lang_COUNTRY.ENCODING@MODIFIER`:
+/// `lang_COUNTRY@MODIFIER`, `lang_COUNTRY`, `lang@MODIFIER`, `lang`.
+fn locale_candidates(locale: &str) -> Vec<String> {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or("");
+    let (lang, country) = match rest.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (rest, None),
+    };
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{lang}_{country}@{modifier}"));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{lang}_{country}"));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{lang}@{modifier}"));
+    }
+    candidates.push(lang.to_string());
+    candidates
+}
+
+/// First `key[locale]` value for the candidate locales, falling back to plain `key`.
+fn localized_attr<'a>(
+    section: &'a freedesktop_entry_parser::Section,
+    key: &str,
+    locales: &[String],
+) -> Option<&'a str> {
+    locales
+        .iter()
+        .find_map(|locale| section.attr_with_param(key, locale).first())
+        .or_else(|| section.attr(key).first())
+        .map(String::as_str)
+}
+
+/// Split a `;`-separated desktop entry list, dropping empty items.
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `TryExec` check: absolute paths must be executable files, bare names are
+/// looked up in `PATH`.
+fn is_executable_available(program: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    let is_executable = |path: &Path| {
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    };
+    if program.contains('/') {
+        return is_executable(Path::new(program));
+    }
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| is_executable(&dir.join(program))))
+        .unwrap_or(false)
+}
+
 /// Strip freedesktop field codes (%f, %F, %u, %U, etc.) from an Exec string.
 fn strip_field_codes(exec: &str) -> String {
     exec.split_whitespace()
@@ -410,8 +979,29 @@ fn strip_field_codes(exec: &str) -> String {
         .join(" ")
 }
 
-/// Fuzzy search a list of entries and return scored results.
+/// Fuzzy search a list of entries and return scored results. Desktop actions are
+/// matched on "App: Action" and fill the slots left after matching apps, so they
+/// show up as children once the query narrows down to an app.
 fn fuzzy_search(entries: &[DesktopEntry], query: &str) -> Vec<SearchResult> {
+    fuzzy_search_ranked(entries, query, &HashMap::new(), 0.0)
+}
+
+/// Multiplier for an app's match score from its decayed pick score for the
+/// query: 1 when never picked, approaching `1 + weight` with repeated picks
+/// (one recent pick gives half of the boost).
+fn selection_boost(selection_score: f64, weight: f64) -> f64 {
+    let score = selection_score.max(0.0);
+    1.0 + weight * score / (score + 1.0)
+}
+
+/// [`fuzzy_search`] with learned ranking: match scores are scaled by
+/// [`selection_boost`]. Sorting is stable, so equal scores keep index order.
+fn fuzzy_search_ranked(
+    entries: &[DesktopEntry],
+    query: &str,
+    selections: &HashMap<String, f64>,
+    weight: f64,
+) -> Vec<SearchResult> {
     let mut matcher = Matcher::new(nucleo::Config::DEFAULT);
     let pattern = Pattern::new(
         query,
@@ -420,30 +1010,88 @@ fn fuzzy_search(entries: &[DesktopEntry], query: &str) -> Vec<SearchResult> {
         nucleo::pattern::AtomKind::Fuzzy,
     );
 
-    let mut scored: Vec<(u32, &DesktopEntry)> = entries
+    let mut scored: Vec<(f64, &DesktopEntry)> = entries
         .iter()
         .filter_map(|app| {
-            let mut buf = Vec::new();
-            let haystack = nucleo::Utf32Str::new(&app.name, &mut buf);
-            let score = pattern.score(haystack, &mut matcher)?;
-            Some((score, app))
+            let score = f64::from(score_entry(app, &pattern, &mut matcher)?);
+            let picked = selections.get(&app.id).copied().unwrap_or(0.0);
+            Some((score * selection_boost(picked, weight), app))
         })
         .collect();
 
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
 
-    scored
+    let mut results: Vec<SearchResult> = scored
         .into_iter()
         .take(10)
         .map(|(_, app)| entry_to_result(app, Category::App))
-        .collect()
+        .collect();
+    if results.len() == 10 {
+        return results;
+    }
+
+    let mut scored_actions: Vec<(u32, &DesktopEntry, &DesktopAction)> = entries
+        .iter()
+        .flat_map(|app| app.actions.iter().map(move |action| (app, action)))
+        .filter_map(|(app, action)| {
+            let mut buf = Vec::new();
+            let label = action_display_name(app, action);
+            let haystack = nucleo::Utf32Str::new(&label, &mut buf);
+            let score = pattern.score(haystack, &mut matcher)?;
+            Some((score, app, action))
+        })
+        .collect();
+    scored_actions.sort_by_key(|(score, _, _)| std::cmp::Reverse(*score));
+
+    let remaining = 10 - results.len();
+    results.extend(
+        scored_actions
+            .into_iter()
+            .take(remaining)
+            .map(|(_, app, action)| action_to_result(app, action, Category::App)),
+    );
+    results
+}
+
+/// Field weights (percent of the raw match score). A name match beats the same
+/// match on GenericName, which beats Keywords, which beats Categories.
+const NAME_WEIGHT: u32 = 100;
+const GENERIC_NAME_WEIGHT: u32 = 80;
+const KEYWORD_WEIGHT: u32 = 70;
+const CATEGORY_WEIGHT: u32 = 50;
+
+/// Best weighted score across an entry's name, GenericName, Keywords and Categories.
+fn score_entry(app: &DesktopEntry, pattern: &Pattern, matcher: &mut Matcher) -> Option<u32> {
+    let mut buf = Vec::new();
+    let mut score_field = |text: &str, weight: u32| {
+        pattern
+            .score(nucleo::Utf32Str::new(text, &mut buf), matcher)
+            .map(|score| score * weight / 100)
+    };
+    let fields = std::iter::once((app.name.as_str(), NAME_WEIGHT))
+        .chain(
+            (!app.generic_name.is_empty())
+                .then_some((app.generic_name.as_str(), GENERIC_NAME_WEIGHT)),
+        )
+        .chain(app.keywords.iter().map(|k| (k.as_str(), KEYWORD_WEIGHT)))
+        .chain(app.categories.iter().map(|c| (c.as_str(), CATEGORY_WEIGHT)));
+    fields
+        .filter_map(|(text, weight)| score_field(text, weight))
+        .max()
 }
 
 fn entry_to_result(entry: &DesktopEntry, category: Category) -> SearchResult {
     SearchResult {
         id: entry.id.clone(),
         name: entry.name.clone(),
-        description: entry.comment.clone(),
+        description: with_source_tag(
+            if entry.comment.is_empty() {
+                &entry.generic_name
+            } else {
+                &entry.comment
+            },
+            entry.source,
+        ),
         icon: icons::resolve_icon(&entry.icon),
         category,
         exec: entry.exec.clone(),
@@ -453,7 +1101,40 @@ fn entry_to_result(entry: &DesktopEntry, category: Category) -> SearchResult {
     }
 }
 
+/// Append the packaging channel, e.g. "Web Browser · Flatpak".
+fn with_source_tag(description: &str, source: AppSource) -> String {
+    match source.label() {
+        Some(label) if description.is_empty() => label.to_string(),
+        Some(label) => format!("{description} · {label}"),
+        None => description.to_string(),
+    }
+}
+
+fn action_to_result(
+    entry: &DesktopEntry,
+    action: &DesktopAction,
+    category: Category,
+) -> SearchResult {
+    let icon = if action.icon.is_empty() {
+        &entry.icon
+    } else {
+        &action.icon
+    };
+    SearchResult {
+        id: action_result_id(entry, action),
+        name: action_display_name(entry, action),
+        description: with_source_tag(&entry.comment, entry.source),
+        icon: icons::resolve_icon(icon),
+        category,
+        exec: action.exec.clone(),
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    }
+}
+
 /// Sort apps: history entries by frecency score first, then remaining apps alphabetically.
+/// Desktop actions only appear when they have history of their own.
 fn sort_apps_by_frecency(
     apps: &[DesktopEntry],
     scores: &std::collections::HashMap<String, f64>,
@@ -469,13 +1150,22 @@ fn sort_apps_by_frecency(
         }
     }
 
-    with_history.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-    without_history.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-
-    let mut results: Vec<SearchResult> = with_history
+    let mut history: Vec<(SearchResult, f64)> = with_history
         .into_iter()
-        .map(|(entry, _)| entry_to_result(entry, Category::History))
+        .map(|(entry, score)| (entry_to_result(entry, Category::History), score))
         .collect();
+    for entry in apps {
+        for action in &entry.actions {
+            if let Some(&score) = scores.get(&action_result_id(entry, action)) {
+                history.push((action_to_result(entry, action, Category::History), score));
+            }
+        }
+    }
+
+    history.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    without_history.sort_by_key(|entry| entry.name.to_lowercase());
+
+    let mut results: Vec<SearchResult> = history.into_iter().map(|(result, _)| result).collect();
 
     results.extend(
         without_history
@@ -487,21 +1177,147 @@ fn sort_apps_by_frecency(
 }
 
 /// Returns all apps sorted by frecency (history first, then alphabetical).
+/// Files, hosts and commands from history join the apps in the history part,
+/// up to `history.max_results` of them, if they still resolve.
 /// Uses AppContext (Tauri-free).
 pub fn get_all_apps_with_frecency(ctx: &AppContext) -> Result<Vec<SearchResult>, String> {
     let apps = ctx.apps.snapshot();
-    let scores = match super::history::get_frecency_scores(ctx) {
-        Ok(s) => s,
+    let history = match super::history::get_scored_history(ctx) {
+        Ok(h) => h,
         Err(e) => {
             tracing::warn!(error = %e, "failed to load frecency scores, falling back to alphabetical");
-            std::collections::HashMap::new()
+            Vec::new()
         }
     };
-    Ok(sort_apps_by_frecency(&apps, &scores))
+    let max_others = crate::config::try_get_config().map_or(6, |cfg| cfg.history.max_results);
+    let mut scores = HashMap::new();
+    let mut others = Vec::new();
+    // ssh_config (with its Includes) is parsed once, on the first host
+    let mut ssh_hosts: Option<HashMap<String, SearchResult>> = None;
+    for (result, score) in history {
+        if result.category == Category::History {
+            scores.insert(result.id, score);
+        } else if others.len() < max_others && is_listed_history(result.category) {
+            // Re-derived like any executed result: gone files and hosts drop out
+            let trusted = if result.category == Category::Ssh {
+                ssh_hosts
+                    .get_or_insert_with(super::ssh::resolve_all_ssh)
+                    .get(&result.id)
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown SSH host: {}", result.id))
+            } else {
+                crate::actions::handlers::resolve_trusted_result(&result, ctx)
+            };
+            match trusted {
+                Ok(trusted) => others.push((trusted, score)),
+                Err(e) => {
+                    tracing::debug!(id = %result.id, error = %e, "skipping stale history entry")
+                }
+            }
+        }
+    }
+    let results = merge_history(sort_apps_by_frecency(&apps, &scores), &scores, others);
+    // Pinned apps not listed (desktop actions without history) come from the
+    // index; a pinned app missing from it was uninstalled and is skipped.
+    Ok(apply_marks(results, &load_marks(ctx), |pin| {
+        match pin.category {
+            Category::App | Category::History => ctx.apps.result_for(&pin.id),
+            _ => Some(pin.clone()),
+        }
+    }))
+}
+
+/// Non-app categories that can be re-run from the empty-query view.
+fn is_listed_history(category: Category) -> bool {
+    matches!(
+        category,
+        Category::File | Category::Vector | Category::Ssh | Category::Onepass | Category::Special
+    )
+}
+
+/// Interleave `others` (best first) into the history part of `results` by score.
+/// Ties go to apps.
+fn merge_history(
+    mut results: Vec<SearchResult>,
+    scores: &HashMap<String, f64>,
+    others: Vec<(SearchResult, f64)>,
+) -> Vec<SearchResult> {
+    let split = results
+        .iter()
+        .position(|result| result.category != Category::History)
+        .unwrap_or(results.len());
+    let rest = results.split_off(split);
+    let mut history: Vec<(SearchResult, f64)> = results
+        .into_iter()
+        .map(|result| {
+            let score = scores.get(&result.id).copied().unwrap_or(0.0);
+            (result, score)
+        })
+        .chain(others)
+        .collect();
+    history.sort_by(|a, b| b.1.total_cmp(&a.1));
+    history
+        .into_iter()
+        .map(|(result, _)| result)
+        .chain(rest)
+        .collect()
+}
+
+fn load_marks(ctx: &AppContext) -> Marks {
+    super::history::get_marks(ctx).unwrap_or_else(|e| {
+        tracing::debug!(error = %e, "failed to load pins, listing without them");
+        Marks::default()
+    })
+}
+
+/// Drop hidden results and move pinned ones to the top, in pin order.
+/// Pins missing from `results` are filled in by `missing_pin`, or left out
+/// when it returns `None`.
+fn apply_marks(
+    results: Vec<SearchResult>,
+    marks: &Marks,
+    missing_pin: impl Fn(&SearchResult) -> Option<SearchResult>,
+) -> Vec<SearchResult> {
+    let mut rest: Vec<SearchResult> = results
+        .into_iter()
+        .filter(|result| !marks.hidden.contains(&result.id))
+        .collect();
+    let mut pinned = Vec::with_capacity(marks.pinned.len() + rest.len());
+    for pin in &marks.pinned {
+        match rest.iter().position(|result| result.id == pin.id) {
+            Some(index) => pinned.push(rest.remove(index)),
+            None => pinned.extend(missing_pin(pin)),
+        }
+    }
+    pinned.extend(rest);
+    pinned
 }
 
 pub fn search_apps(query: &str, ctx: &AppContext) -> Result<Vec<SearchResult>, String> {
-    Ok(ctx.apps.search(query))
+    let weight = crate::config::try_get_config()
+        .map(|c| c.history.selection_weight)
+        .unwrap_or_else(|| crate::config::HistoryConfig::default().selection_weight);
+    let results = if weight <= 0.0 {
+        ctx.apps.search(query)
+    } else {
+        let selections = super::history::get_selection_scores(query, ctx).unwrap_or_else(|e| {
+            tracing::debug!(error = %e, "failed to load selection scores, ranking by match only");
+            HashMap::new()
+        });
+        ctx.apps.search_with_selections(query, &selections, weight)
+    };
+    // Pins only lead among the apps that match
+    Ok(apply_marks(results, &load_marks(ctx), |_| None))
+}
+
+/// Launch an app (or `app#action`) by id, passing optional files/URLs for its
+/// Exec field codes.
+pub fn launch_app_by_id(id: &str, targets: &[String], ctx: &AppContext) -> Result<(), String> {
+    for launch in ctx.apps.launch_commands(id, targets)? {
+        tracing::info!(id, argv = ?launch.argv, "launching app");
+        crate::actions::utils::spawn_command(id, &launch.argv, launch.working_dir.as_deref())?;
+    }
+    Ok(())
 }
 
 /// Resolve a canonical app exec command by app ID from cache.
@@ -541,11 +1357,11 @@ pub fn launch_app(exec: String) -> Result<(), String> {
     if crate::actions::dry_run::is_enabled() {
         return crate::actions::dry_run::launch_app(&exec);
     }
-    Command::new(&parts[0])
-        .args(&parts[1..])
-        .spawn()
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    let app_id = Path::new(&parts[0])
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| parts[0].clone());
+    crate::actions::launch::spawn(&app_id, &parts, None)
 }
 
 #[cfg(test)]
@@ -562,14 +1378,45 @@ mod tests {
             id: id.into(),
             name: name.into(),
             exec: exec.into(),
+            exec_template: exec.into(),
+            terminal: false,
+            working_dir: "".into(),
+            source_path: PathBuf::new(),
             icon: "".into(),
             comment: "".into(),
+            generic_name: "".into(),
+            keywords: vec![],
+            categories: vec![],
+            mime_types: vec![],
+            startup_wm_class: "".into(),
             no_display: false,
+            hidden: false,
+            source: AppSource::Native,
+            actions: vec![],
         }
     }
 
+    fn make_entry_with_actions(
+        id: &str,
+        name: &str,
+        actions: &[(&str, &str, &str)],
+    ) -> DesktopEntry {
+        let mut entry = make_entry(id, name, id);
+        entry.actions = actions
+            .iter()
+            .map(|(action_id, action_name, exec)| DesktopAction {
+                id: (*action_id).into(),
+                name: (*action_name).into(),
+                exec: (*exec).into(),
+                exec_template: (*exec).into(),
+                icon: "".into(),
+            })
+            .collect();
+        entry
+    }
+
     fn load_desktop_entries() -> Vec<DesktopEntry> {
-        load_desktop_entries_from_dirs(&desktop_dirs())
+        load_desktop_entries_from_dirs(&desktop_dirs()).0
     }
 
     // --- strip_field_codes ---
@@ -623,6 +1470,70 @@ mod tests {
         assert_eq!(results[0].name, "Firefox");
     }
 
+    #[test]
+    fn selections_boost_apps_picked_for_the_query() {
+        let entries = vec![
+            make_entry("telegram", "Telegram", "telegram"),
+            make_entry("terminal", "Terminal", "kgx"),
+            make_entry("text-editor", "Text Editor", "gedit"),
+        ];
+        let ids = |query: &str, selections: &HashMap<String, f64>| -> Vec<String> {
+            fuzzy_search_ranked(&entries, query, selections, 1.0)
+                .into_iter()
+                .map(|r| r.id)
+                .collect()
+        };
+        let none = HashMap::new();
+        let baseline = ids("te", &none);
+        // Without picks, learned ranking is plain fuzzy ranking
+        assert_eq!(
+            baseline,
+            fuzzy_search(&entries, "te")
+                .into_iter()
+                .map(|r| r.id)
+                .collect::<Vec<_>>()
+        );
+
+        let last = baseline.last().unwrap().clone();
+        let picked = HashMap::from([(last.clone(), 3.0)]);
+        assert_eq!(ids("te", &picked)[0], last);
+        // Zero weight ignores picks
+        assert_eq!(
+            fuzzy_search_ranked(&entries, "te", &picked, 0.0)
+                .into_iter()
+                .map(|r| r.id)
+                .collect::<Vec<_>>(),
+            baseline
+        );
+        // Picks never bring in non-matching apps
+        let unrelated = HashMap::from([("telegram".to_string(), 100.0)]);
+        assert_eq!(ids("edit", &unrelated), vec!["text-editor"]);
+    }
+
+    #[test]
+    fn selection_ranking_is_stable() {
+        let entries: Vec<DesktopEntry> = ["a-one", "a-two", "a-three"]
+            .iter()
+            .map(|id| make_entry(id, "Same Name", id))
+            .collect();
+        // Equal scores keep index order, with or without equal boosts
+        let order = |selections: &HashMap<String, f64>| -> Vec<String> {
+            fuzzy_search_ranked(&entries, "same", selections, 2.0)
+                .into_iter()
+                .map(|r| r.id)
+                .collect()
+        };
+        let expected = vec!["a-one", "a-two", "a-three"];
+        assert_eq!(order(&HashMap::new()), expected);
+        let equal: HashMap<String, f64> = expected.iter().map(|id| (id.to_string(), 1.5)).collect();
+        assert_eq!(order(&equal), expected);
+
+        assert_eq!(selection_boost(0.0, 2.0), 1.0);
+        assert_eq!(selection_boost(1.0, 2.0), 2.0);
+        assert!(selection_boost(1000.0, 2.0) < 3.0);
+        assert!(selection_boost(2.0, 1.0) > selection_boost(1.0, 1.0));
+    }
+
     #[test]
     fn fuzzy_case_insensitive() {
         let entries = vec![make_entry("ff", "Firefox", "firefox")];
@@ -814,7 +1725,7 @@ mod tests {
         // This test only validates on systems with .desktop files
         let test_path = PathBuf::from("/usr/share/applications/firefox.desktop");
         if test_path.exists() {
-            let entry = parse_desktop_file(&test_path);
+            let entry = parse_desktop_file(&test_path, &EntryEnv::from_env());
             if let Some(e) = entry {
                 assert!(!e.name.is_empty());
                 assert!(!e.exec.is_empty());
@@ -1019,6 +1930,420 @@ mod tests {
         assert_eq!(status.app_count, 2);
     }
 
+    #[test]
+    fn parses_desktop_actions() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("firefox.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Firefox\nExec=firefox %u\nIcon=firefox\n\
+             Actions=new-window;new-private-window;missing;\n\n\
+             [Desktop Action new-window]\nName=New Window\nExec=firefox --new-window %u\n\n\
+             [Desktop Action new-private-window]\nName=New Private Window\n\
+             Exec=firefox --private-window %u\nIcon=firefox-private\n\n\
+             [Desktop Action unlisted]\nName=Unlisted\nExec=firefox --unlisted\n",
+        )
+        .unwrap();
+        let entry =
+            parse_desktop_file(&dir.path().join("firefox.desktop"), &EntryEnv::default()).unwrap();
+        assert_eq!(
+            entry.actions,
+            vec![
+                DesktopAction {
+                    id: "new-window".into(),
+                    name: "New Window".into(),
+                    exec: "firefox --new-window".into(),
+                    exec_template: "firefox --new-window %u".into(),
+                    icon: "".into(),
+                },
+                DesktopAction {
+                    id: "new-private-window".into(),
+                    name: "New Private Window".into(),
+                    exec: "firefox --private-window".into(),
+                    exec_template: "firefox --private-window %u".into(),
+                    icon: "firefox-private".into(),
+                },
+            ]
+        );
+
+        let index = AppIndexState::new_for_test(vec![dir.path().to_path_buf()]);
+        assert_eq!(
+            index.resolve_exec("firefox#new-private-window").as_deref(),
+            Some("firefox --private-window")
+        );
+        assert_eq!(
+            index.resolve_name("firefox#new-window").as_deref(),
+            Some("Firefox: New Window")
+        );
+        assert!(index.resolve_exec("firefox#unlisted").is_none());
+        assert!(index.resolve_exec("firefox#").is_none());
+    }
+
+    fn parse_fixture(content: &str, env: &EntryEnv) -> Option<DesktopEntry> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("fixture.desktop");
+        fs::write(&path, content).unwrap();
+        parse_desktop_file(&path, env)
+    }
+
+    fn env_with(locale: &str, desktops: &[&str]) -> EntryEnv {
+        EntryEnv {
+            locales: locale_candidates(locale),
+            desktops: desktops.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    const FIXTURE_HEADER: &str = "[Desktop Entry]\nType=Application\nExec=fixture\n";
+
+    #[test]
+    fn locale_candidates_follow_spec_order() {
+        assert_eq!(
+            locale_candidates("sr_RS.UTF-8@latin"),
+            vec!["sr_RS@latin", "sr_RS", "sr@latin", "sr"]
+        );
+        assert_eq!(locale_candidates("de_DE.UTF-8"), vec!["de_DE", "de"]);
+        assert_eq!(locale_candidates("fr"), vec!["fr"]);
+        assert!(locale_candidates("C.UTF-8").is_empty());
+        assert!(locale_candidates("POSIX").is_empty());
+        assert!(locale_candidates("").is_empty());
+    }
+
+    #[test]
+    fn fixture_localized_name_and_comment() {
+        let content = format!(
+            "{FIXTURE_HEADER}Name=Files\nName[de]=Dateien\nName[de_AT]=Dateien (AT)\n\
+             Comment=Browse files\nComment[de]=Dateien durchsuchen\n"
+        );
+        let de = parse_fixture(&content, &env_with("de_DE.UTF-8", &[])).unwrap();
+        assert_eq!(de.name, "Dateien");
+        assert_eq!(de.comment, "Dateien durchsuchen");
+
+        let at = parse_fixture(&content, &env_with("de_AT.UTF-8", &[])).unwrap();
+        assert_eq!(at.name, "Dateien (AT)");
+        assert_eq!(at.comment, "Dateien durchsuchen");
+
+        let en = parse_fixture(&content, &env_with("en_US.UTF-8", &[])).unwrap();
+        assert_eq!(en.name, "Files");
+        assert_eq!(en.comment, "Browse files");
+    }
+
+    #[test]
+    fn flatpak_and_snap_entries_are_tagged_and_deduped() {
+        let root = tempdir().unwrap();
+        let native = root.path().join("usr/share/applications");
+        let flatpak = root.path().join("flatpak/exports/share/applications");
+        let snap = root.path().join("snapd/desktop/applications");
+        for dir in [&native, &flatpak, &snap] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        write_desktop_file(&native, "firefox.desktop", "Firefox", "firefox %u");
+        write_desktop_file(
+            &flatpak,
+            "org.mozilla.firefox.desktop",
+            "Firefox",
+            "/usr/bin/flatpak run --command=firefox org.mozilla.firefox",
+        );
+        fs::write(
+            flatpak.join("org.gimp.GIMP.desktop"),
+            "[Desktop Entry]\nType=Application\nName=GIMP\nComment=Edit images\n\
+             Exec=/usr/bin/flatpak run org.gimp.GIMP\nX-Flatpak=org.gimp.GIMP\n",
+        )
+        .unwrap();
+        write_desktop_file(
+            &snap,
+            "spotify_spotify.desktop",
+            "Spotify",
+            "/snap/bin/spotify",
+        );
+        // Same name, different app: both stay
+        write_desktop_file(&snap, "notes_notes.desktop", "Firefox", "/snap/bin/notes");
+
+        let index = AppIndexState::new_for_test(vec![native, flatpak, snap]);
+        let mut ids: Vec<String> = index.snapshot().into_iter().map(|e| e.id).collect();
+        ids.sort();
+        assert_eq!(
+            ids,
+            vec!["firefox", "notes_notes", "org.gimp.GIMP", "spotify_spotify"]
+        );
+
+        let description = |query: &str, id: &str| {
+            index
+                .search(query)
+                .into_iter()
+                .find(|r| r.id == id)
+                .map(|r| r.description)
+                .unwrap()
+        };
+        assert_eq!(
+            description("gimp", "org.gimp.GIMP"),
+            "Edit images · Flatpak"
+        );
+        assert_eq!(description("spotify", "spotify_spotify"), "Snap");
+        assert_eq!(description("firefox", "firefox"), "");
+    }
+
+    #[test]
+    fn appimages_are_listed_and_launch_the_image() {
+        use std::os::unix::fs::PermissionsExt;
+        let apps = tempdir().unwrap();
+        let cache = tempdir().unwrap();
+        // An ELF header whose section table ends at 64, followed by no squashfs:
+        // extraction fails and the file name is used.
+        let mut image = vec![0u8; 64];
+        image[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        let appimage = apps.path().join("Tool-2.1-x86_64.AppImage");
+        fs::write(&appimage, image).unwrap();
+        fs::set_permissions(&appimage, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let index = AppIndexState::new_with_dirs(
+            vec![appimage::applications_dir(cache.path())],
+            Some(AppImageSources {
+                dirs: vec![apps.path().to_path_buf()],
+                cache: cache.path().to_path_buf(),
+            }),
+        );
+        // Extraction waits for a refresh rather than blocking construction
+        assert!(index.search("tool").is_empty());
+        assert!(index.refresh().unwrap().changed);
+        let results = index.search("tool");
+        assert_eq!(results[0].id, "appimage-tool");
+        assert_eq!(results[0].description, "AppImage");
+        let launches = index.launch_commands("appimage-tool", &[]).unwrap();
+        assert_eq!(launches[0].argv, vec![appimage.display().to_string()]);
+
+        fs::remove_file(&appimage).unwrap();
+        assert!(index.refresh().unwrap().changed);
+        assert!(index.search("tool").is_empty());
+    }
+
+    #[test]
+    fn window_keys_cover_wm_class_id_and_executable() {
+        let mut code = make_entry(
+            "com.visualstudio.code",
+            "Code",
+            "/usr/share/code/code --unity",
+        );
+        code.startup_wm_class = "Code".into();
+        assert_eq!(code.window_keys(), vec!["code", "com.visualstudio.code"]);
+
+        let wrapped = make_entry("org.example.Tool", "Tool", "env GDK_BACKEND=x11 tool");
+        assert_eq!(wrapped.window_keys(), vec!["org.example.tool", "tool"]);
+    }
+
+    #[test]
+    fn fixture_keywords_generic_name_and_categories() {
+        let content = format!(
+            "{FIXTURE_HEADER}Name=Firefox\nGenericName=Web Browser\nGenericName[de]=Webbrowser\n\
+             Keywords=Internet;WWW;\nKeywords[de]=Internet;Netz;\nCategories=Network;WebBrowser;\n\
+             MimeType=text/html;x-scheme-handler/https;\n"
+        );
+        let entry = parse_fixture(&content, &EntryEnv::default()).unwrap();
+        assert_eq!(entry.generic_name, "Web Browser");
+        assert_eq!(entry.keywords, vec!["Internet", "WWW"]);
+        assert_eq!(entry.categories, vec!["Network", "WebBrowser"]);
+        assert_eq!(
+            entry.mime_types,
+            vec!["text/html", "x-scheme-handler/https"]
+        );
+
+        let de = parse_fixture(&content, &env_with("de_DE", &[])).unwrap();
+        assert_eq!(de.generic_name, "Webbrowser");
+        assert_eq!(de.keywords, vec!["Internet", "Netz"]);
+    }
+
+    #[test]
+    fn fuzzy_search_matches_secondary_fields_with_lower_weight() {
+        let mut firefox = make_entry("firefox", "Firefox", "firefox");
+        firefox.generic_name = "Web Browser".into();
+        firefox.keywords = vec!["internet".into()];
+        firefox.categories = vec!["Network".into()];
+        let mut browser = make_entry("browser", "Browser", "browser");
+        browser.categories = vec!["Utility".into()];
+        let entries = vec![firefox, browser];
+
+        let ids = |query: &str| -> Vec<String> {
+            fuzzy_search(&entries, query)
+                .into_iter()
+                .map(|r| r.id)
+                .collect()
+        };
+        // Name match outranks the same text in GenericName
+        assert_eq!(ids("browser"), vec!["browser", "firefox"]);
+        assert_eq!(ids("internet"), vec!["firefox"]);
+        assert_eq!(ids("network"), vec!["firefox"]);
+
+        let results = fuzzy_search(&entries, "firefox");
+        assert_eq!(results[0].description, "Web Browser");
+    }
+
+    #[test]
+    fn fixture_hidden_entries_are_flagged() {
+        let hidden = parse_fixture(
+            &format!("{FIXTURE_HEADER}Name=Gone\nHidden=true\n"),
+            &EntryEnv::default(),
+        )
+        .unwrap();
+        assert!(hidden.hidden && !hidden.no_display);
+        let shown = parse_fixture(
+            &format!("{FIXTURE_HEADER}Name=Here\nHidden=false\n"),
+            &EntryEnv::default(),
+        )
+        .unwrap();
+        assert!(!shown.hidden);
+    }
+
+    #[test]
+    fn fixture_try_exec() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("tool");
+        fs::write(&bin, "#!/bin/sh\n").unwrap();
+        let not_executable = dir.path().join("data");
+        fs::write(&not_executable, "").unwrap();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let with_try_exec = |try_exec: &str| {
+            parse_fixture(
+                &format!("{FIXTURE_HEADER}Name=Tool\nTryExec={try_exec}\n"),
+                &EntryEnv::default(),
+            )
+            .unwrap()
+            .hidden
+        };
+        assert!(!with_try_exec(bin.to_str().unwrap()));
+        assert!(with_try_exec(not_executable.to_str().unwrap()));
+        assert!(with_try_exec("/nonexistent/burrow-tool"));
+        assert!(with_try_exec("burrow-definitely-not-on-path"));
+        assert!(!with_try_exec("sh"));
+    }
+
+    #[test]
+    fn fixture_only_show_in_and_not_show_in() {
+        let only_gnome = format!("{FIXTURE_HEADER}Name=Settings\nOnlyShowIn=GNOME;Unity;\n");
+        let not_kde = format!("{FIXTURE_HEADER}Name=Tweaks\nNotShowIn=KDE;\n");
+        let flagged = |content: &str, desktops: &[&str]| {
+            parse_fixture(content, &env_with("", desktops))
+                .unwrap()
+                .hidden
+        };
+
+        assert!(!flagged(&only_gnome, &["ubuntu", "GNOME"]));
+        assert!(flagged(&only_gnome, &["Hyprland"]));
+        assert!(flagged(&only_gnome, &[]));
+        assert!(flagged(&not_kde, &["KDE"]));
+        assert!(!flagged(&not_kde, &["Hyprland"]));
+        assert!(!flagged(&not_kde, &[]));
+    }
+
+    #[test]
+    fn hidden_user_entry_shadows_system_entry() {
+        let user = tempdir().unwrap();
+        let system = tempdir().unwrap();
+        write_desktop_file(system.path(), "app.desktop", "App", "app");
+        fs::write(
+            user.path().join("app.desktop"),
+            format!("{FIXTURE_HEADER}Name=App\nHidden=true\n"),
+        )
+        .unwrap();
+        let (entries, unlisted) = load_desktop_entries_from_dirs(&[
+            user.path().to_path_buf(),
+            system.path().to_path_buf(),
+        ]);
+        assert!(entries.is_empty() && unlisted.is_empty());
+    }
+
+    #[test]
+    fn launch_commands_expand_targets_terminal_and_path() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("htop.desktop"),
+            "[Desktop Entry]\nType=Application\nName=htop\nExec=htop\nTerminal=true\nPath=/var/tmp\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("viewer.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Viewer\nIcon=viewer\n\
+             Exec=viewer %i --name=%c %F\nActions=single;\n\n\
+             [Desktop Action single]\nName=Single\nExec=viewer --single %f\n",
+        )
+        .unwrap();
+        let parsed =
+            parse_desktop_file(&dir.path().join("htop.desktop"), &EntryEnv::default()).unwrap();
+
+        let htop = parsed.launch_commands(None, &[], "alacritty -e").unwrap();
+        assert_eq!(
+            htop,
+            vec![AppLaunch {
+                argv: vec!["alacritty".into(), "-e".into(), "htop".into()],
+                working_dir: Some(PathBuf::from("/var/tmp")),
+            }]
+        );
+
+        let index = AppIndexState::new_for_test(vec![dir.path().to_path_buf()]);
+        let targets = vec!["/a.png".to_string(), "file:///b.png".to_string()];
+        let viewer = index.launch_commands("viewer", &targets).unwrap();
+        assert_eq!(
+            viewer[0].argv,
+            vec![
+                "viewer",
+                "--icon",
+                "viewer",
+                "--name=Viewer",
+                "/a.png",
+                "/b.png"
+            ]
+        );
+        assert_eq!(viewer[0].working_dir, None);
+
+        let single = index.launch_commands("viewer#single", &targets).unwrap();
+        assert_eq!(single.len(), 2);
+        assert_eq!(single[1].argv, vec!["viewer", "--single", "/b.png"]);
+
+        assert!(index.launch_commands("missing", &[]).is_err());
+    }
+
+    #[test]
+    fn fuzzy_search_lists_actions_after_apps() {
+        let entries = vec![
+            make_entry_with_actions(
+                "firefox",
+                "Firefox",
+                &[(
+                    "new-private-window",
+                    "New Private Window",
+                    "firefox --private-window",
+                )],
+            ),
+            make_entry("files", "Files", "nautilus"),
+        ];
+        let results = fuzzy_search(&entries, "firefox");
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["firefox", "firefox#new-private-window"]);
+        assert_eq!(results[1].name, "Firefox: New Private Window");
+        assert_eq!(results[1].exec, "firefox --private-window");
+
+        let results = fuzzy_search(&entries, "private window");
+        assert_eq!(results[0].id, "firefox#new-private-window");
+    }
+
+    #[test]
+    fn all_apps_include_actions_with_history() {
+        let entries = vec![make_entry_with_actions(
+            "kitty",
+            "kitty",
+            &[
+                ("new-window", "New Window", "kitty"),
+                ("quick-access", "Quick Access", "kitten quick-access"),
+            ],
+        )];
+        let scores = std::collections::HashMap::from([("kitty#quick-access".to_string(), 3.0)]);
+        let results = sort_apps_by_frecency(&entries, &scores);
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["kitty#quick-access", "kitty"]);
+        assert_eq!(results[0].category, Category::History);
+    }
+
     #[test]
     fn relevant_fs_event_accepts_desktop_files() {
         assert!(is_relevant_app_fs_path(std::path::Path::new(
@@ -1153,4 +2478,101 @@ mod tests {
             "watcher callback should not keep AppIndexState alive after drop"
         );
     }
+
+    #[test]
+    fn pins_lead_and_hidden_apps_disappear() {
+        let (ctx, _dir) = crate::wm::tests::ctx_with_apps(&[
+            ("alpha", "Exec=alpha"),
+            ("beta", "Exec=beta"),
+            ("gamma", "Exec=gamma"),
+        ]);
+        let conn = ctx.db.lock().unwrap();
+        crate::commands::history::create_table(&conn).unwrap();
+        let gamma = ctx.apps.result_for("gamma").unwrap();
+        let file = SearchResult {
+            id: "/home/u/todo.md".into(),
+            name: "todo.md".into(),
+            description: String::new(),
+            icon: String::new(),
+            category: Category::File,
+            exec: String::new(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        };
+        crate::commands::history::pin(&conn, &gamma).unwrap();
+        crate::commands::history::pin(&conn, &file).unwrap();
+        crate::commands::history::hide(&conn, "beta", "beta").unwrap();
+        drop(conn);
+
+        let listed = get_all_apps_with_frecency(&ctx).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["gamma", "/home/u/todo.md", "alpha"]);
+
+        // In search, pins lead only among matches and hidden apps never show
+        let found = search_apps("a", &ctx).unwrap();
+        let ids: Vec<&str> = found.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["gamma", "alpha"]);
+    }
+
+    #[test]
+    fn empty_view_mixes_in_other_categories_that_still_resolve() {
+        let (ctx, dir) =
+            crate::wm::tests::ctx_with_apps(&[("alpha", "Exec=alpha"), ("beta", "Exec=beta")]);
+        let note = dir.path().join("note.md");
+        std::fs::write(&note, "").unwrap();
+        let conn = ctx.db.lock().unwrap();
+        crate::commands::history::create_table(&conn).unwrap();
+        let now = crate::commands::history::julian_now();
+        let launch = |id: &str, category: Category, times: usize| {
+            let result = SearchResult {
+                id: id.into(),
+                name: id.into(),
+                description: String::new(),
+                icon: String::new(),
+                category,
+                exec: "rm -rf /".into(),
+                input_spec: None,
+                output_mode: None,
+                output_format: None,
+            };
+            for _ in 0..times {
+                crate::commands::history::insert_launch_at(&conn, &result, now).unwrap();
+            }
+        };
+        launch("alpha", Category::App, 1);
+        launch(&note.display().to_string(), Category::File, 3);
+        launch("/nonexistent/burrow-test.md", Category::File, 5);
+        launch("special-refresh", Category::Special, 2);
+        launch("2+2", Category::Math, 9);
+        drop(conn);
+
+        let listed = get_all_apps_with_frecency(&ctx).unwrap();
+        let ids: Vec<&str> = listed.iter().map(|r| r.id.as_str()).collect();
+        let note_id = note.display().to_string();
+        assert_eq!(
+            ids,
+            vec![note_id.as_str(), "special-refresh", "alpha", "beta"]
+        );
+        // Entries are re-derived, not replayed from the database
+        assert_eq!(listed[0].exec, "");
+        assert_eq!(listed[0].name, "note.md");
+        assert_eq!(listed[1].category, Category::Special);
+        assert_ne!(listed[1].exec, "rm -rf /");
+    }
+
+    #[test]
+    fn uninstalled_pinned_apps_are_skipped() {
+        let marks = Marks {
+            pinned: vec![entry_to_result(
+                &make_entry("gone", "Gone", "gone"),
+                Category::App,
+            )],
+            hidden: Default::default(),
+        };
+        let results = vec![entry_to_result(&make_entry("a", "A", "a"), Category::App)];
+        let listed = apply_marks(results, &marks, |_| None);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "a");
+    }
 }
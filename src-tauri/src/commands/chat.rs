@@ -1,21 +1,38 @@
 use crate::{
-    chat::{self, ContextSnippet},
+    actions::{handlers, modifier::Modifier, output_window, utils},
+    chat::{
+        self, answer,
+        persona::{self, Persona},
+        tools::PendingAction,
+        ChatReply, ContextSnippet,
+    },
+    commands::{apps, history},
     config,
     context::AppContext,
-    ollama,
+    indexer, ollama,
+    router::{Category, SearchResult},
 };
 
 /// Primary chat implementation — Tauri-free.
-pub async fn chat_ask(query: String, ctx: &AppContext) -> Result<String, String> {
+/// A leading `@name` selects a persona from `[chat.personas]`.
+pub async fn chat_ask(query: String, ctx: &AppContext) -> Result<ChatReply, String> {
     let trimmed = query.trim_start_matches('?').trim();
-    if trimmed.is_empty() {
+    let cfg = config::get_config();
+    let (persona_name, question) = persona::split_persona(trimmed);
+    if question.is_empty() {
         return Err("Empty question".into());
     }
 
-    let cfg = config::get_config();
-    let context_snippets = if cfg.vector_search.enabled {
+    let persona = match persona_name {
+        Some(name) => {
+            Persona::resolve(name, cfg, &cfg.models.chat_large, cfg.vector_search.enabled)?
+        }
+        None => Persona::default_with(&cfg.models.chat_large, cfg.vector_search.enabled),
+    };
+
+    let context_snippets = if persona.rag && cfg.vector_search.enabled {
         fetch_context_ctx(
-            trimmed,
+            question,
             ctx,
             cfg.vector_search.top_k,
             cfg.vector_search.min_score,
@@ -25,17 +42,114 @@ pub async fn chat_ask(query: String, ctx: &AppContext) -> Result<String, String>
         vec![]
     };
 
-    chat::generate_answer(trimmed, &context_snippets).await
+    if cfg.chat.tools_enabled {
+        return chat::generate_chat_with_tools(question, &context_snippets, &persona, ctx).await;
+    }
+
+    chat::generate_persona_chat(question, &context_snippets, &persona)
+        .await
+        .map(|answer| ChatReply::new(answer, vec![]))
 }
 
 /// Tauri command wrapper for chat_ask.
 #[tauri::command]
-pub async fn chat_ask_cmd(query: String, app: tauri::AppHandle) -> Result<String, String> {
+pub async fn chat_ask_cmd(query: String, app: tauri::AppHandle) -> Result<ChatReply, String> {
     use tauri::Manager;
     let ctx = app.state::<AppContext>();
     chat_ask(query, &ctx).await
 }
 
+/// Run an action the chat model proposed, after the user confirmed it in the UI.
+/// App launches go through the regular action dispatcher, so the exec is
+/// re-resolved from the app index rather than taken from the model.
+pub fn chat_confirm_action(action: PendingAction, ctx: &AppContext) -> Result<(), String> {
+    tracing::info!(action = %action.label(), "running confirmed chat action");
+    match action {
+        PendingAction::LaunchApp { app_id, name } => {
+            let result = SearchResult {
+                id: app_id,
+                name,
+                description: String::new(),
+                icon: String::new(),
+                category: Category::App,
+                exec: String::new(),
+                input_spec: None,
+                output_mode: None,
+                output_format: None,
+            };
+            handlers::handle_action(&result, Modifier::None, None, ctx)?;
+            let exec = apps::resolve_app_exec(&result.id, ctx).unwrap_or_default();
+            history::record_launch(&SearchResult { exec, ..result }, None, ctx)
+        }
+        PendingAction::TypeText { text } => {
+            let app = ctx
+                .clone_app_handle()
+                .ok_or("Typing text requires the Burrow window")?;
+            utils::type_text_wayland(&text, &app)
+        }
+        PendingAction::CopyText { text } => utils::copy_to_clipboard(&text),
+        PendingAction::RunBlock { block_id } => run_block(&block_id, ctx),
+        PendingAction::SaveBlock { block_id, path } => save_block(&block_id, &path),
+    }
+}
+
+/// Run a shell block from a recent answer. The command comes from the server-side
+/// block store, never from the client.
+fn run_block(block_id: &str, ctx: &AppContext) -> Result<(), String> {
+    let block = answer::find_block(block_id)
+        .ok_or_else(|| format!("Unknown or expired code block: {block_id}"))?;
+    if !block.runnable {
+        return Err("Only shell command blocks can be run".into());
+    }
+    let app = ctx
+        .clone_app_handle()
+        .ok_or("Running commands requires the Burrow window")?;
+    ctx.hide_window();
+    let buffers = ctx.output_buffers.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = output_window::run_in_output_window(
+            block.code,
+            "Chat command".into(),
+            &app,
+            buffers,
+            None,
+        )
+        .await
+        {
+            tracing::error!(error = %e, "chat command output window failed");
+        }
+    });
+    Ok(())
+}
+
+/// Save a code block from a recent answer to a new file (`~/` is expanded). A
+/// directory path gets a `snippet.<ext>` name derived from the block language.
+fn save_block(block_id: &str, path: &str) -> Result<(), String> {
+    let block = answer::find_block(block_id)
+        .ok_or_else(|| format!("Unknown or expired code block: {block_id}"))?;
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("No file path given".into());
+    }
+    let mut content = block.code.clone();
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    let mut target = indexer::expand_tilde(path);
+    if target.is_dir() {
+        target.push(format!("snippet.{}", block.extension()));
+    }
+    utils::write_new_file(&target, &content)
+}
+
+/// Tauri command wrapper for chat_confirm_action.
+#[tauri::command]
+pub fn chat_confirm_action_cmd(action: PendingAction, app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+    let ctx = app.state::<AppContext>();
+    chat_confirm_action(action, &ctx)
+}
+
 /// Fetch context snippets using AppContext (Tauri-free).
 async fn fetch_context_ctx(
     query: &str,
@@ -118,3 +232,69 @@ fn fetch_context_from_conn(
         .map(|(_, path, preview)| ContextSnippet { path, preview })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::history::DbState;
+    use crate::commands::vectors::VectorDbState;
+    use crate::indexer::IndexerState;
+    use rusqlite::Connection;
+
+    fn in_memory_ctx() -> AppContext {
+        AppContext::new(
+            DbState::new(Connection::open_in_memory().unwrap()),
+            VectorDbState::new(Connection::open_in_memory().unwrap()),
+            IndexerState::new(),
+        )
+    }
+
+    fn block_ids(answer: &str) -> Vec<String> {
+        chat::answer::parse_answer(answer)
+            .into_iter()
+            .filter_map(|part| match part {
+                answer::AnswerPart::Code { id, .. } => Some(id),
+                answer::AnswerPart::Text { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn save_block_writes_new_file_only() {
+        let ids = block_ids("```python\nprint('hi')\n```");
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.py");
+        let path_str = path.to_str().unwrap().to_string();
+
+        save_block(&ids[0], &path_str).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "print('hi')\n");
+        assert!(save_block(&ids[0], &path_str).is_err());
+
+        save_block(&ids[0], dir.path().to_str().unwrap()).unwrap();
+        assert!(dir.path().join("snippet.py").exists());
+    }
+
+    #[test]
+    fn block_actions_reject_unknown_ids() {
+        let ctx = in_memory_ctx();
+        assert!(save_block("blk-missing", "/tmp/x").is_err());
+        let err = chat_confirm_action(
+            PendingAction::RunBlock {
+                block_id: "blk-missing".into(),
+            },
+            &ctx,
+        )
+        .unwrap_err();
+        assert!(err.contains("Unknown"), "{err}");
+    }
+
+    #[test]
+    fn run_block_requires_runnable_block_and_window() {
+        let ctx = in_memory_ctx();
+        let ids = block_ids("```rust\nfn main() {}\n```\n```bash\nls\n```");
+        let err = run_block(&ids[0], &ctx).unwrap_err();
+        assert!(err.contains("shell"), "{err}");
+        let err = run_block(&ids[1], &ctx).unwrap_err();
+        assert!(err.contains("window"), "{err}");
+    }
+}
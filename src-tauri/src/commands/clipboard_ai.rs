@@ -0,0 +1,211 @@
+//! AI text actions on the clipboard or primary selection (`#summarize`, `#translate`, ...).
+//!
+//! The special commands carry a `clipboard-ai:<action>` sentinel exec. The frontend
+//! sends the result id here instead of `execute_action`; the answer comes back as a
+//! [`ChatReply`] whose copy/type actions the user confirms like any chat action.
+
+use crate::actions::utils;
+use crate::chat::{self, persona::Persona, tools::PendingAction, ChatReply};
+use crate::commands::special;
+use crate::config;
+
+/// Exec prefix marking a special command as a clipboard AI action.
+pub const EXEC_PREFIX: &str = "clipboard-ai:";
+
+/// Longest input sent to the model; larger clipboards are truncated.
+const MAX_INPUT_CHARS: usize = 12_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAction {
+    Summarize,
+    Translate,
+    Explain,
+    FixGrammar,
+    ToCommand,
+}
+
+impl TextAction {
+    pub fn from_exec(exec: &str) -> Option<Self> {
+        match exec.strip_prefix(EXEC_PREFIX)? {
+            "summarize" => Some(Self::Summarize),
+            "translate" => Some(Self::Translate),
+            "explain" => Some(Self::Explain),
+            "fix-grammar" => Some(Self::FixGrammar),
+            "to-command" => Some(Self::ToCommand),
+            _ => None,
+        }
+    }
+
+    /// System prompt for the action. `language` only applies to Translate.
+    fn instruction(self, language: Option<&str>) -> String {
+        match self {
+            Self::Summarize => {
+                "Summarize the text the user provides in a few short bullet points. \
+                 Reply with the summary only."
+                    .into()
+            }
+            Self::Translate => {
+                let language = language
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .unwrap_or("English");
+                format!(
+                    "Translate the text the user provides into {language}. \
+                     Preserve formatting. Reply with the translation only."
+                )
+            }
+            Self::Explain => {
+                "Explain the text or code the user provides clearly and concisely.".into()
+            }
+            Self::FixGrammar => {
+                "Fix grammar, spelling and punctuation in the text the user provides \
+                 without changing its meaning or tone. Reply with the corrected text only."
+                    .into()
+            }
+            Self::ToCommand => "Rewrite the user's request as a single Linux shell command. \
+                 Reply with the command only — no explanation and no code fences."
+                .into(),
+        }
+    }
+}
+
+/// Run a clipboard AI action. `primary` reads the primary selection instead of the
+/// clipboard; `language` is the optional secondary input for `#translate`.
+pub async fn clipboard_ai(
+    id: &str,
+    primary: bool,
+    language: Option<String>,
+) -> Result<ChatReply, String> {
+    let action = special::resolve_special_by_id(id)
+        .and_then(|result| TextAction::from_exec(&result.exec))
+        .ok_or_else(|| format!("Unknown clipboard AI action: {id}"))?;
+
+    let input = utils::read_clipboard(primary)?;
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(if primary {
+            "No text selected".into()
+        } else {
+            "Clipboard is empty".into()
+        });
+    }
+    let input = crate::actions::dry_run::truncate(input, MAX_INPUT_CHARS);
+
+    tracing::info!(
+        ?action,
+        primary,
+        chars = input.chars().count(),
+        "clipboard AI action"
+    );
+
+    let cfg = config::get_config();
+    let mut persona = Persona::default_with(&cfg.models.chat, false);
+    persona.system_prompt = Some(action.instruction(language.as_deref()));
+
+    let answer = chat::generate_persona_chat(input, &[], &persona).await?;
+    Ok(reply_with_actions(action, &answer))
+}
+
+/// Wrap an answer with copy/type actions. Shell commands get code fences stripped
+/// so the copied text is runnable.
+fn reply_with_actions(action: TextAction, answer: &str) -> ChatReply {
+    let text = match action {
+        TextAction::ToCommand => strip_code_fence(answer),
+        _ => answer.trim(),
+    }
+    .to_string();
+    ChatReply::new(
+        text.clone(),
+        vec![
+            PendingAction::CopyText { text: text.clone() },
+            PendingAction::TypeText { text },
+        ],
+    )
+}
+
+fn strip_code_fence(answer: &str) -> &str {
+    let trimmed = answer.trim();
+    let Some(inner) = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+    else {
+        return trimmed.trim_matches('`');
+    };
+    // Drop the language tag line (```sh)
+    match inner.split_once('\n') {
+        Some((_lang, body)) => body.trim(),
+        None => inner.trim(),
+    }
+}
+
+/// Tauri command wrapper for clipboard_ai.
+#[tauri::command]
+pub async fn clipboard_ai_cmd(
+    id: String,
+    primary: bool,
+    language: Option<String>,
+) -> Result<ChatReply, String> {
+    clipboard_ai(&id, primary, language).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_commands_map_to_actions() {
+        for (id, action) in [
+            ("special-summarize", TextAction::Summarize),
+            ("special-translate", TextAction::Translate),
+            ("special-explain", TextAction::Explain),
+            ("special-fix-grammar", TextAction::FixGrammar),
+            ("special-to-command", TextAction::ToCommand),
+        ] {
+            let result = special::resolve_special_by_id(id).expect(id);
+            assert_eq!(TextAction::from_exec(&result.exec), Some(action), "{id}");
+        }
+        let cowork = special::resolve_special_by_id("special-cowork").unwrap();
+        assert_eq!(TextAction::from_exec(&cowork.exec), None);
+    }
+
+    #[test]
+    fn translate_uses_target_language() {
+        assert!(TextAction::Translate
+            .instruction(Some("German"))
+            .contains("into German"));
+        assert!(TextAction::Translate
+            .instruction(Some("  "))
+            .contains("into English"));
+        assert!(TextAction::Translate
+            .instruction(None)
+            .contains("into English"));
+    }
+
+    #[test]
+    fn strip_code_fence_variants() {
+        assert_eq!(strip_code_fence("```sh\nls -la\n```"), "ls -la");
+        assert_eq!(strip_code_fence("```ls -la```"), "ls -la");
+        assert_eq!(strip_code_fence("`ls -la`"), "ls -la");
+        assert_eq!(strip_code_fence("  ls -la \n"), "ls -la");
+    }
+
+    #[test]
+    fn reply_offers_copy_and_type() {
+        let reply = reply_with_actions(TextAction::ToCommand, "```bash\nfind . -name '*.rs'\n```");
+        assert_eq!(reply.answer, "find . -name '*.rs'");
+        assert_eq!(
+            reply.actions,
+            vec![
+                PendingAction::CopyText {
+                    text: "find . -name '*.rs'".into()
+                },
+                PendingAction::TypeText {
+                    text: "find . -name '*.rs'".into()
+                },
+            ]
+        );
+
+        let summary = reply_with_actions(TextAction::Summarize, "\n- point\n");
+        assert_eq!(summary.answer, "- point");
+    }
+}
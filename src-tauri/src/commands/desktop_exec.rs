@@ -0,0 +1,335 @@
+//! Desktop entry `Exec=` parsing and field-code expansion per the freedesktop
+//! Desktop Entry spec ("The Exec key").
+//!
+//! Exec values are tokenized with the spec's own quoting rules rather than
+//! handed to `sh -c`, so arguments (file names, URLs) never pass through a shell.
+
+use std::path::Path;
+
+/// Values substituted for field codes.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecContext<'a> {
+    /// `Icon=` value for `%i`
+    pub icon: &'a str,
+    /// Localized `Name=` for `%c`
+    pub name: &'a str,
+    /// Location of the desktop file for `%k`
+    pub desktop_file: &'a Path,
+    /// Files or URLs to open; `%f`/`%u` take one, `%F`/`%U` take all
+    pub targets: &'a [String],
+}
+
+/// Expand an Exec value into argv lists. Usually one command; an Exec with a
+/// single-target code (`%f`/`%u`) and several targets yields one command per target.
+pub fn expand_exec(exec: &str, ctx: &ExecContext) -> Result<Vec<Vec<String>>, String> {
+    let args = tokenize(&unescape_string(exec))?;
+    if args.is_empty() {
+        return Err("Empty Exec command".into());
+    }
+    let single_target = args
+        .iter()
+        .any(|arg| !arg.quoted && (arg.text.contains("%f") || arg.text.contains("%u")));
+
+    if single_target && ctx.targets.len() > 1 {
+        return ctx
+            .targets
+            .iter()
+            .map(|target| expand_args(&args, ctx, std::slice::from_ref(target)))
+            .collect();
+    }
+    Ok(vec![expand_args(&args, ctx, ctx.targets)?])
+}
+
+/// Prefix a command with the terminal command (`$TERMINAL`, e.g. `foot` or
+/// `alacritty -e`), which may carry its own arguments.
+pub fn wrap_in_terminal(argv: Vec<String>, terminal_cmd: &str) -> Result<Vec<String>, String> {
+    let mut wrapped = shlex::split(terminal_cmd)
+        .filter(|parts| !parts.is_empty())
+        .ok_or_else(|| format!("Invalid terminal command: {terminal_cmd}"))?;
+    wrapped.extend(argv);
+    Ok(wrapped)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Arg {
+    text: String,
+    /// Quoted arguments are literal — field codes inside them are not expanded
+    quoted: bool,
+}
+
+fn expand_args(args: &[Arg], ctx: &ExecContext, targets: &[String]) -> Result<Vec<String>, String> {
+    let mut argv = Vec::new();
+    for arg in args {
+        if arg.quoted {
+            argv.push(arg.text.clone());
+            continue;
+        }
+        match arg.text.as_str() {
+            "%F" => argv.extend(targets.iter().map(|t| as_local_path(t))),
+            "%U" => argv.extend(targets.iter().cloned()),
+            "%f" => argv.extend(targets.first().map(|t| as_local_path(t))),
+            "%u" => argv.extend(targets.first().cloned()),
+            "%i" => {
+                if !ctx.icon.is_empty() {
+                    argv.push("--icon".into());
+                    argv.push(ctx.icon.into());
+                }
+            }
+            text => {
+                let expanded = expand_inline(text, ctx, targets)?;
+                // A lone deprecated code (%d, %n, ...) expands to nothing — drop it
+                if !expanded.is_empty() || !text.starts_with('%') {
+                    argv.push(expanded);
+                }
+            }
+        }
+    }
+    if argv.is_empty() {
+        return Err("Exec command expanded to nothing".into());
+    }
+    Ok(argv)
+}
+
+/// Expand field codes embedded in a larger argument, e.g. `--class=%c`.
+fn expand_inline(text: &str, ctx: &ExecContext, targets: &[String]) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some('f' | 'F') => out.push_str(
+                &targets
+                    .first()
+                    .map(|t| as_local_path(t))
+                    .unwrap_or_default(),
+            ),
+            Some('u' | 'U') => out.push_str(targets.first().map(String::as_str).unwrap_or("")),
+            Some('c') => out.push_str(ctx.name),
+            Some('k') => out.push_str(&ctx.desktop_file.to_string_lossy()),
+            Some('i') => out.push_str(ctx.icon),
+            // Deprecated codes expand to nothing
+            Some('d' | 'D' | 'n' | 'N' | 'v' | 'm') => {}
+            Some(other) => return Err(format!("Invalid field code %{other} in Exec")),
+            None => return Err("Exec ends with a lone %".into()),
+        }
+    }
+    Ok(out)
+}
+
+/// Local `file://` URLs become decoded paths for `%f`/`%F`; everything
+/// else, including files on other hosts, passes through.
+fn as_local_path(target: &str) -> String {
+    if !target.starts_with("file://") {
+        return target.to_string();
+    }
+    url::Url::parse(target)
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| target.to_string())
+}
+
+/// Undo the general string escapes (`\s`, `\n`, `\t`, `\r`, `\\`) that apply
+/// to every desktop entry value before Exec quoting is interpreted.
+fn unescape_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => out.push(' '),
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            // Not a general escape — leave it for the Exec quoting rules
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// Split an Exec value into arguments. Arguments are separated by spaces and may
+/// be double-quoted; inside quotes `\"`, `` \` ``, `\$` and `\\` are escapes.
+fn tokenize(exec: &str) -> Result<Vec<Arg>, String> {
+    let mut args = Vec::new();
+    let mut chars = exec.chars().peekable();
+    loop {
+        while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+        let Some(&first) = chars.peek() else {
+            break;
+        };
+        if first == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c @ ('"' | '`' | '$' | '\\')) => text.push(c),
+                        Some(c) => {
+                            text.push('\\');
+                            text.push(c);
+                        }
+                        None => return Err("Exec ends inside a quoted argument".into()),
+                    },
+                    Some(c) => text.push(c),
+                    None => return Err("Unterminated quote in Exec".into()),
+                }
+            }
+            args.push(Arg { text, quoted: true });
+        } else {
+            let mut text = String::new();
+            while let Some(c) = chars.next_if(|c| *c != ' ' && *c != '\t') {
+                text.push(c);
+            }
+            args.push(Arg {
+                text,
+                quoted: false,
+            });
+        }
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx<'a>(targets: &'a [String]) -> ExecContext<'a> {
+        ExecContext {
+            icon: "firefox",
+            name: "Firefox",
+            desktop_file: Path::new("/usr/share/applications/firefox.desktop"),
+            targets,
+        }
+    }
+
+    fn expand(exec: &str, targets: &[&str]) -> Vec<Vec<String>> {
+        let targets: Vec<String> = targets.iter().map(|t| t.to_string()).collect();
+        expand_exec(exec, &ctx(&targets)).unwrap()
+    }
+
+    #[test]
+    fn no_targets_drops_file_codes() {
+        assert_eq!(expand("firefox %u", &[]), vec![vec!["firefox"]]);
+        assert_eq!(expand("gimp %F", &[]), vec![vec!["gimp"]]);
+    }
+
+    #[test]
+    fn list_codes_take_all_targets() {
+        assert_eq!(
+            expand("gimp %F", &["/a.png", "file:///b.png"]),
+            vec![vec!["gimp", "/a.png", "/b.png"]]
+        );
+        assert_eq!(
+            expand("vlc --started-from-file %U", &["https://x/y", "/c.mp4"]),
+            vec![vec!["vlc", "--started-from-file", "https://x/y", "/c.mp4"]]
+        );
+    }
+
+    #[test]
+    fn file_urls_are_decoded_to_paths() {
+        assert_eq!(
+            expand("evince %f", &["file:///home/me/My%20File.pdf"]),
+            vec![vec!["evince", "/home/me/My File.pdf"]]
+        );
+        assert_eq!(
+            expand("evince %F", &["file://localhost/tmp/a.pdf"]),
+            vec![vec!["evince", "/tmp/a.pdf"]]
+        );
+        // Not a local file: left for the app to deal with
+        assert_eq!(
+            expand("evince %f", &["file://server/share/a.pdf"]),
+            vec![vec!["evince", "file://server/share/a.pdf"]]
+        );
+    }
+
+    #[test]
+    fn single_codes_launch_once_per_target() {
+        assert_eq!(
+            expand("mpv %f", &["/a.mkv", "/b.mkv"]),
+            vec![vec!["mpv", "/a.mkv"], vec!["mpv", "/b.mkv"]]
+        );
+        assert_eq!(
+            expand("firefox %u", &["https://example.com"]),
+            vec![vec!["firefox", "https://example.com"]]
+        );
+    }
+
+    #[test]
+    fn icon_name_and_location_codes() {
+        assert_eq!(
+            expand("app %i --class=%c --desktop=%k", &[]),
+            vec![vec![
+                "app",
+                "--icon",
+                "firefox",
+                "--class=Firefox",
+                "--desktop=/usr/share/applications/firefox.desktop"
+            ]]
+        );
+    }
+
+    #[test]
+    fn percent_escape_and_deprecated_codes() {
+        assert_eq!(
+            expand("printf 100%% %d %N", &[]),
+            vec![vec!["printf", "100%"]]
+        );
+        let err = expand_exec("app %z", &ctx(&[])).unwrap_err();
+        assert!(err.contains("%z"), "{err}");
+    }
+
+    #[test]
+    fn quoted_arguments_are_literal() {
+        assert_eq!(
+            expand(r#"sh -c "echo \"%f\" \$HOME" %f"#, &["/a.txt"]),
+            vec![vec!["sh", "-c", "echo \"%f\" $HOME", "/a.txt"]]
+        );
+        assert_eq!(
+            expand(r#""/opt/My App/run" --flag"#, &[]),
+            vec![vec!["/opt/My App/run", "--flag"]]
+        );
+    }
+
+    #[test]
+    fn general_escapes_are_undone_first() {
+        assert_eq!(
+            expand(r#"sh -c "echo a\\\\b""#, &[]),
+            vec![vec!["sh", "-c", "echo a\\b"]]
+        );
+    }
+
+    #[test]
+    fn invalid_exec_values() {
+        assert!(expand_exec("", &ctx(&[])).is_err());
+        assert!(expand_exec("\"unterminated", &ctx(&[])).is_err());
+        assert!(expand_exec("%f", &ctx(&[])).is_err());
+    }
+
+    #[test]
+    fn wrap_in_terminal_splits_terminal_command() {
+        let argv = vec!["htop".to_string()];
+        assert_eq!(
+            wrap_in_terminal(argv.clone(), "foot").unwrap(),
+            vec!["foot", "htop"]
+        );
+        assert_eq!(
+            wrap_in_terminal(argv.clone(), "alacritty -e").unwrap(),
+            vec!["alacritty", "-e", "htop"]
+        );
+        assert!(wrap_in_terminal(argv, "").is_err());
+    }
+}
@@ -1,7 +1,36 @@
 use crate::config;
 use crate::indexer;
 use crate::router::{Category, SearchResult};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+fn path_to_result(path: &Path, category: Category) -> SearchResult {
+    SearchResult {
+        id: path.display().to_string(),
+        name: path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string()),
+        description: path
+            .parent()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default(),
+        icon: "".into(),
+        category,
+        // Security: exec intentionally empty. handle_file uses result.id
+        // with xdg_open via Command::arg() to prevent shell injection
+        exec: String::new(),
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    }
+}
+
+/// Rebuild a file result from its id (an absolute path) if the file still
+/// exists. `category` is `File` or `Vector`, whichever search it came from.
+pub fn resolve_file_by_id(id: &str, category: Category) -> Option<SearchResult> {
+    let path = Path::new(id);
+    (path.is_absolute() && path.exists()).then(|| path_to_result(path, category))
+}
 
 fn match_files_in_dirs(dirs: &[PathBuf], query: &str, limit: usize) -> Vec<SearchResult> {
     let query_lower = query.to_lowercase();
@@ -12,23 +41,7 @@ fn match_files_in_dirs(dirs: &[PathBuf], query: &str, limit: usize) -> Vec<Searc
             for entry in entries.flatten() {
                 let name = entry.file_name().to_string_lossy().to_string();
                 if name.to_lowercase().contains(&query_lower) {
-                    let path = entry.path();
-                    results.push(SearchResult {
-                        id: path.display().to_string(),
-                        name,
-                        description: path
-                            .parent()
-                            .map(|p| p.display().to_string())
-                            .unwrap_or_default(),
-                        icon: "".into(),
-                        category: Category::File,
-                        // Security: exec intentionally empty. handle_file uses result.id
-                        // with xdg_open via Command::arg() to prevent shell injection
-                        exec: String::new(),
-                        input_spec: None,
-                        output_mode: None,
-                        output_format: None,
-                    });
+                    results.push(path_to_result(&entry.path(), Category::File));
                 }
                 if results.len() >= limit {
                     return results;
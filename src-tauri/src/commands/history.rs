@@ -1,6 +1,10 @@
+use super::migrations::Migration;
+use crate::config::HistoryConfig;
 use crate::context::AppContext;
 use crate::router::{Category, SearchResult};
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Mutex, MutexGuard};
 use tauri::AppHandle;
@@ -39,18 +43,176 @@ pub fn open_history_db() -> Result<Connection, rusqlite::Error> {
     Ok(conn)
 }
 
-fn create_table(conn: &Connection) -> Result<(), rusqlite::Error> {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS launches (
-            id TEXT PRIMARY KEY,
-            name TEXT NOT NULL,
-            exec TEXT NOT NULL,
-            icon TEXT NOT NULL DEFAULT '',
-            description TEXT NOT NULL DEFAULT '',
-            count INTEGER NOT NULL DEFAULT 0,
-            last_used REAL NOT NULL DEFAULT 0
-        )",
-    )
+/// History schema steps. Databases from before versioning may already have
+/// any prefix of these tables, so the first three tolerate existing ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "launches table",
+        destructive: false,
+        apply: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS launches (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    exec TEXT NOT NULL,
+                    icon TEXT NOT NULL DEFAULT '',
+                    description TEXT NOT NULL DEFAULT '',
+                    count INTEGER NOT NULL DEFAULT 0,
+                    last_used REAL NOT NULL DEFAULT 0
+                )",
+            )
+        },
+    },
+    Migration {
+        version: 2,
+        description: "per-query selections for learned ranking",
+        destructive: false,
+        apply: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS selections (
+                    query TEXT NOT NULL,
+                    id TEXT NOT NULL,
+                    score REAL NOT NULL DEFAULT 0,
+                    last_used REAL NOT NULL DEFAULT 0,
+                    PRIMARY KEY (query, id)
+                )",
+            )
+        },
+    },
+    Migration {
+        version: 3,
+        description: "per-launch timestamps for frecency",
+        destructive: false,
+        apply: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS launch_events (
+                    id TEXT NOT NULL,
+                    used_at REAL NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS launch_events_id ON launch_events (id, used_at)",
+            )?;
+            migrate_launch_events(conn)
+        },
+    },
+    Migration {
+        version: 4,
+        description: "peer counts for history sync",
+        destructive: false,
+        apply: |conn| {
+            conn.execute_batch(
+                "ALTER TABLE launches ADD COLUMN synced INTEGER NOT NULL DEFAULT 0;
+                CREATE TABLE sync_peers (
+                    peer TEXT NOT NULL,
+                    id TEXT NOT NULL,
+                    count INTEGER NOT NULL,
+                    PRIMARY KEY (peer, id)
+                )",
+            )
+        },
+    },
+    Migration {
+        version: 5,
+        description: "pinned and hidden results",
+        destructive: false,
+        apply: |conn| {
+            conn.execute_batch(
+                "CREATE TABLE pins (
+                    id TEXT PRIMARY KEY,
+                    result TEXT NOT NULL,
+                    pinned_at REAL NOT NULL
+                );
+                CREATE TABLE hidden (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    hidden_at REAL NOT NULL
+                )",
+            )
+        },
+    },
+    Migration {
+        version: 6,
+        description: "originating category of launches",
+        destructive: false,
+        apply: |conn| {
+            conn.execute_batch(
+                "ALTER TABLE launches ADD COLUMN category TEXT NOT NULL DEFAULT 'app'",
+            )?;
+            migrate_launch_categories(conn)
+        },
+    },
+];
+
+pub(crate) fn create_table(conn: &Connection) -> Result<(), rusqlite::Error> {
+    super::migrations::run(conn, "history", MIGRATIONS)
+}
+
+/// Launch timestamps kept per id; older ones only survive in `launches.count`.
+pub(crate) const MAX_EVENTS_PER_ID: i64 = 100;
+
+/// Backfill `launch_events` for rows recorded before per-launch timestamps
+/// existed. Only the last launch time is known, so every backfilled event
+/// gets that timestamp.
+fn migrate_launch_events(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let migrated = conn.execute(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+         INSERT INTO launch_events (id, used_at)
+         SELECT l.id, l.last_used FROM launches l JOIN n ON n.i <= l.count
+         WHERE NOT EXISTS (SELECT 1 FROM launch_events e WHERE e.id = l.id)",
+        [MAX_EVENTS_PER_ID],
+    )?;
+    if migrated > 0 {
+        tracing::info!(events = migrated, "backfilled launch events from history");
+    }
+    Ok(())
+}
+
+/// Categorize rows recorded before the category was stored, from their ids.
+fn migrate_launch_categories(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let ids: Vec<String> = conn
+        .prepare("SELECT id FROM launches")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    let mut update = conn.prepare("UPDATE launches SET category = ?2 WHERE id = ?1")?;
+    for id in &ids {
+        let category = infer_category(id);
+        if category != Category::App {
+            update.execute(rusqlite::params![id, category.as_str()])?;
+        }
+    }
+    Ok(())
+}
+
+/// Best guess at the category of a history id from its shape, for rows
+/// recorded (or synced from peers) without one.
+pub(crate) fn infer_category(id: &str) -> Category {
+    if id.starts_with("special-") {
+        Category::Special
+    } else if id.starts_with("ssh-") {
+        Category::Ssh
+    } else if id.starts_with("op-") {
+        Category::Onepass
+    } else if id.starts_with('/') {
+        Category::File
+    } else {
+        Category::App
+    }
+}
+
+/// The category a launch is stored under. Apps picked from history are still apps.
+pub(crate) fn stored_category(category: Category) -> Category {
+    match category {
+        Category::History => Category::App,
+        other => other,
+    }
+}
+
+/// The category a stored launch is shown with: apps come back as history.
+fn history_category(stored: &str) -> Category {
+    match Category::from_name(stored) {
+        Some(Category::App) | None => Category::History,
+        Some(other) => other,
+    }
 }
 
 pub fn init_db(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
@@ -61,30 +223,143 @@ pub fn init_db(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-/// Query the most frequent/recent (frecent) entries from the database.
+/// Launches sampled by the bucket model, newest first (as Firefox does).
+const BUCKET_SAMPLES: usize = 10;
+
+/// Firefox-style recency weight for a launch `age_days` old.
+fn bucket_weight(age_days: f64) -> f64 {
+    match age_days {
+        d if d <= 4.0 => 100.0,
+        d if d <= 14.0 => 70.0,
+        d if d <= 31.0 => 50.0,
+        d if d <= 90.0 => 30.0,
+        _ => 10.0,
+    }
+}
+
+/// Frecency of an entry launched `count` times in total, with `events` the
+/// retained launch timestamps (newest first).
 ///
-/// Returns up to 6 entries, ordered by frecency score (count weighted by recency).
-/// Public for CLI use.
-pub fn query_frecent(conn: &Connection) -> Result<Vec<SearchResult>, rusqlite::Error> {
-    let mut stmt = conn.prepare(
-        "SELECT id, name, exec, icon, description FROM launches
-         ORDER BY count * (1.0 / (1.0 + (julianday('now') - last_used))) DESC
-         LIMIT 6",
-    )?;
+/// - `buckets`: `count` × the average bucket weight of the last 10 launches / 100,
+///   so long-term heavy use fades over months rather than after a day away.
+/// - `half_life`: every retained launch contributes `0.5^(age / half_life)`.
+fn frecency(count: i64, events: &[f64], config: &HistoryConfig, now: f64) -> f64 {
+    if events.is_empty() {
+        return 0.0;
+    }
+    match config.frecency_model.as_str() {
+        "half_life" => events
+            .iter()
+            .map(|used_at| decayed(1.0, now - used_at, config.frecency_half_life_days))
+            .sum(),
+        _ => {
+            let samples = &events[..events.len().min(BUCKET_SAMPLES)];
+            let weight: f64 = samples
+                .iter()
+                .map(|used_at| bucket_weight(now - used_at))
+                .sum();
+            count.max(1) as f64 * weight / samples.len() as f64 / 100.0
+        }
+    }
+}
 
-    let results = stmt
-        .query_map([], |row| {
-            Ok(SearchResult {
+/// A history entry with its computed frecency, as shown by `burrow history stats`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FrecencyStat {
+    pub id: String,
+    pub name: String,
+    pub category: Category,
+    pub count: i64,
+    /// Days since the most recent launch
+    pub last_used_days: f64,
+    pub score: f64,
+}
+
+/// Half-lives after which a launch no longer counts under the `half_life`
+/// model: its weight is below one millionth.
+const HALF_LIFE_HORIZON: f64 = 20.0;
+
+/// History rows with their frecency score, best first: every row, or only
+/// those recorded from `categories`.
+///
+/// Only the launch events the model reads are loaded: the newest
+/// [`BUCKET_SAMPLES`] per id for `buckets`, those within
+/// [`HALF_LIFE_HORIZON`] half-lives for `half_life`.
+fn scored_launches(
+    conn: &Connection,
+    config: &HistoryConfig,
+    now: f64,
+    categories: Option<&[Category]>,
+) -> Result<Vec<(FrecencyStat, SearchResult)>, rusqlite::Error> {
+    let (since, per_id) = match config.frecency_model.as_str() {
+        "half_life" => (
+            now - HALF_LIFE_HORIZON * config.frecency_half_life_days,
+            MAX_EVENTS_PER_ID,
+        ),
+        _ => (f64::MIN, BUCKET_SAMPLES as i64),
+    };
+    let category_names: Vec<&str> = categories
+        .unwrap_or_default()
+        .iter()
+        .map(|c| stored_category(*c).as_str())
+        .collect();
+    // `launches` rows to score, with the category names bound from `?first`
+    let category_filter = |first: usize| match categories {
+        Some(_) => format!(
+            "category IN ({})",
+            (first..first + category_names.len())
+                .map(|i| format!("?{i}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => "1".to_string(),
+    };
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&since, &per_id];
+    params.extend(
+        category_names
+            .iter()
+            .map(|name| name as &dyn rusqlite::ToSql),
+    );
+
+    let mut events: HashMap<String, Vec<f64>> = HashMap::new();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, used_at FROM (
+           SELECT id, used_at, ROW_NUMBER() OVER (PARTITION BY id ORDER BY used_at DESC) AS n
+           FROM launch_events
+           WHERE used_at >= ?1 AND id IN (SELECT id FROM launches WHERE {})
+         )
+         WHERE n <= ?2
+         ORDER BY used_at DESC",
+        category_filter(3)
+    ))?;
+    for row in stmt.query_map(params.as_slice(), |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+    })? {
+        match row {
+            Ok((id, used_at)) => events.entry(id).or_default().push(used_at),
+            Err(e) => tracing::warn!(error = %e, "skipping corrupted launch event"),
+        }
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, name, exec, icon, description, count, last_used, category FROM launches
+         WHERE {}",
+        category_filter(1)
+    ))?;
+    let mut scored: Vec<(FrecencyStat, SearchResult)> = stmt
+        .query_map(&params[2..], |row| {
+            let result = SearchResult {
                 id: row.get(0)?,
                 name: row.get(1)?,
                 exec: row.get(2)?,
                 icon: row.get(3)?,
                 description: row.get(4)?,
-                category: Category::History,
+                category: history_category(&row.get::<_, String>(7)?),
                 input_spec: None,
                 output_mode: None,
                 output_format: None,
-            })
+            };
+            Ok((result, row.get::<_, i64>(5)?, row.get::<_, f64>(6)?))
         })?
         .filter_map(|r| match r {
             Ok(val) => Some(val),
@@ -93,9 +368,62 @@ pub fn query_frecent(conn: &Connection) -> Result<Vec<SearchResult>, rusqlite::E
                 None
             }
         })
+        .map(|(result, count, last_used)| {
+            let score = frecency(
+                count,
+                events.get(&result.id).map_or(&[][..], Vec::as_slice),
+                config,
+                now,
+            );
+            let stat = FrecencyStat {
+                id: result.id.clone(),
+                name: result.name.clone(),
+                category: result.category,
+                count,
+                last_used_days: (now - last_used).max(0.0),
+                score,
+            };
+            (stat, result)
+        })
         .collect();
+    // Best first; ties go to the most recently used
+    scored.sort_by(|(a, _), (b, _)| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.last_used_days.total_cmp(&b.last_used_days))
+    });
+    Ok(scored)
+}
+
+/// Query the most frequent/recent (frecent) entries from the database.
+///
+/// Returns up to 6 entries, ordered by the configured frecency model.
+/// Public for CLI use.
+pub fn query_frecent(conn: &Connection) -> Result<Vec<SearchResult>, rusqlite::Error> {
+    query_frecent_with(conn, &history_config(), julian_now())
+}
 
-    Ok(results)
+fn query_frecent_with(
+    conn: &Connection,
+    config: &HistoryConfig,
+    now: f64,
+) -> Result<Vec<SearchResult>, rusqlite::Error> {
+    Ok(scored_launches(conn, config, now, None)?
+        .into_iter()
+        .take(6)
+        .map(|(_, result)| result)
+        .collect())
+}
+
+/// Every history entry with its frecency score under the configured model,
+/// best first. Public for CLI use.
+pub fn frecency_stats(conn: &Connection) -> Result<Vec<FrecencyStat>, rusqlite::Error> {
+    Ok(
+        scored_launches(conn, &history_config(), julian_now(), None)?
+            .into_iter()
+            .map(|(stat, _)| stat)
+            .collect(),
+    )
 }
 
 /// Get frecent results using AppContext (Tauri-free).
@@ -104,6 +432,76 @@ pub fn get_frecent(ctx: &AppContext) -> Result<Vec<SearchResult>, String> {
     query_frecent(&conn).map_err(|e| e.to_string())
 }
 
+/// History entries with their frecency scores, best first. Apps are
+/// reported as [`Category::History`], everything else by its own category.
+pub fn get_scored_history(ctx: &AppContext) -> Result<Vec<(SearchResult, f64)>, String> {
+    let conn = ctx.db.lock()?;
+    Ok(
+        scored_launches(&conn, &history_config(), julian_now(), None)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|(stat, result)| (result, stat.score))
+            .collect(),
+    )
+}
+
+/// Returns a map of id → frecency score for history entries recorded from
+/// one of `categories` (apps as [`Category::App`]).
+fn query_category_scores(
+    conn: &Connection,
+    categories: &[Category],
+    config: &HistoryConfig,
+    now: f64,
+) -> Result<HashMap<String, f64>, rusqlite::Error> {
+    Ok(scored_launches(conn, config, now, Some(categories))?
+        .into_iter()
+        .map(|(stat, _)| (stat.id, stat.score))
+        .collect())
+}
+
+/// Stable re-rank of a provider's results by the frecency of entries
+/// recorded from `categories`: used results come first, best first, and the
+/// rest keep the provider's order. Results are returned unchanged if the
+/// history can't be read.
+pub fn rank_by_frecency(
+    mut results: Vec<SearchResult>,
+    categories: &[Category],
+    ctx: &AppContext,
+) -> Vec<SearchResult> {
+    let scores = match get_category_scores(categories, ctx) {
+        Ok(scores) => scores,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to read frecency for ranking");
+            return results;
+        }
+    };
+    sort_by_scores(&mut results, &scores);
+    results
+}
+
+/// Result id → frecency score for entries recorded from `categories`, for
+/// providers that fold frecency into their own ranking.
+pub fn get_category_scores(
+    categories: &[Category],
+    ctx: &AppContext,
+) -> Result<HashMap<String, f64>, String> {
+    let conn = ctx.db.lock()?;
+    query_category_scores(&conn, categories, &history_config(), julian_now())
+        .map_err(|e| e.to_string())
+}
+
+fn sort_by_scores(results: &mut [SearchResult], scores: &HashMap<String, f64>) {
+    if scores.is_empty() {
+        return;
+    }
+    results.sort_by(|a, b| match (scores.get(&a.id), scores.get(&b.id)) {
+        (Some(a), Some(b)) => b.total_cmp(a),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
 /// Returns a map of app id → frecency score for all entries in the history DB.
 pub fn get_frecency_scores(
     ctx: &AppContext,
@@ -115,82 +513,220 @@ pub fn get_frecency_scores(
 fn query_frecency_scores(
     conn: &Connection,
 ) -> Result<std::collections::HashMap<String, f64>, rusqlite::Error> {
-    let mut stmt = conn.prepare(
-        "SELECT id, count * (1.0 / (1.0 + (julianday('now') - last_used))) AS score
-         FROM launches",
+    Ok(
+        scored_launches(conn, &history_config(), julian_now(), None)?
+            .into_iter()
+            .map(|(stat, _)| (stat.id, stat.score))
+            .collect(),
+    )
+}
+
+/// Count a launch of `launch` at Julian day `now`, under the category it came from.
+pub(crate) fn insert_launch_at(
+    conn: &Connection,
+    launch: &SearchResult,
+    now: f64,
+) -> Result<(), rusqlite::Error> {
+    let id = &launch.id;
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO launches (id, name, exec, icon, description, count, last_used, category)
+         VALUES (?1, ?2, ?3, ?4, ?5, 1, ?6, ?7)
+         ON CONFLICT(id) DO UPDATE SET
+           count = count + 1,
+           last_used = ?6,
+           name = ?2, exec = ?3, icon = ?4, description = ?5, category = ?7",
+        rusqlite::params![
+            id,
+            launch.name,
+            launch.exec,
+            launch.icon,
+            launch.description,
+            now,
+            stored_category(launch.category).as_str()
+        ],
     )?;
-    let map = stmt
-        .query_map([], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
-        })?
+    tx.execute(
+        "INSERT INTO launch_events (id, used_at) VALUES (?1, ?2)",
+        rusqlite::params![id, now],
+    )?;
+    prune_events(&tx, id)?;
+    tx.commit()
+}
+
+/// Drop all but the newest `MAX_EVENTS_PER_ID` launch events of `id`.
+pub(crate) fn prune_events(conn: &Connection, id: &str) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM launch_events WHERE id = ?1 AND rowid NOT IN (
+           SELECT rowid FROM launch_events WHERE id = ?1 ORDER BY used_at DESC LIMIT ?2
+         )",
+        rusqlite::params![id, MAX_EVENTS_PER_ID],
+    )?;
+    Ok(())
+}
+
+/// Current time as a Julian day, the unit of `last_used`.
+pub(crate) fn julian_now() -> f64 {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0);
+    secs / 86_400.0 + 2_440_587.5
+}
+
+fn history_config() -> HistoryConfig {
+    crate::config::try_get_config()
+        .map(|c| c.history.clone())
+        .unwrap_or_default()
+}
+
+/// Lowercase, whitespace-collapsed query cut to `max_chars` characters.
+fn normalize_query(query: &str, max_chars: usize) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(max_chars)
+        .collect()
+}
+
+/// `score` after `days` of decay with the given half-life.
+fn decayed(score: f64, days: f64, half_life_days: f64) -> f64 {
+    score * 0.5f64.powf(days.max(0.0) / half_life_days)
+}
+
+/// Remember that `id` was picked after typing `query`. Every prefix of the
+/// query is credited, so typing "te" later benefits from a pick made at "tel".
+/// Scores decay continuously: the stored score is brought up to date, then 1 is added.
+fn insert_selection(
+    conn: &Connection,
+    query: &str,
+    id: &str,
+    config: &HistoryConfig,
+    now: f64,
+) -> Result<(), rusqlite::Error> {
+    let query = normalize_query(query, config.selection_max_prefix);
+    if query.is_empty() {
+        return Ok(());
+    }
+    let tx = conn.unchecked_transaction()?;
+    let prefixes = query
+        .char_indices()
+        .map(|(i, c)| &query[..i + c.len_utf8()]);
+    for prefix in prefixes.filter(|p| !p.ends_with(' ')) {
+        let previous: Option<(f64, f64)> = tx
+            .query_row(
+                "SELECT score, last_used FROM selections WHERE query = ?1 AND id = ?2",
+                rusqlite::params![prefix, id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map(Some)
+            .or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?;
+        let score = previous.map_or(0.0, |(score, last_used)| {
+            decayed(score, now - last_used, config.selection_half_life_days)
+        }) + 1.0;
+        tx.execute(
+            "INSERT INTO selections (query, id, score, last_used) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(query, id) DO UPDATE SET score = ?3, last_used = ?4",
+            rusqlite::params![prefix, id, score, now],
+        )?;
+    }
+    // After 16 half-lives even 1000 picks are worth less than 0.02
+    tx.execute(
+        "DELETE FROM selections WHERE last_used < ?1",
+        [now - 16.0 * config.selection_half_life_days],
+    )?;
+    tx.commit()
+}
+
+/// Decayed pick scores for ids chosen after typing exactly this (normalized) query.
+fn query_selection_scores(
+    conn: &Connection,
+    query: &str,
+    config: &HistoryConfig,
+    now: f64,
+) -> Result<HashMap<String, f64>, rusqlite::Error> {
+    let query = normalize_query(query, config.selection_max_prefix);
+    if query.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut stmt = conn.prepare("SELECT id, score, last_used FROM selections WHERE query = ?1")?;
+    let rows = stmt.query_map([&query], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, f64>(1)?,
+            row.get::<_, f64>(2)?,
+        ))
+    })?;
+    Ok(rows
         .filter_map(|r| match r {
             Ok(val) => Some(val),
             Err(e) => {
-                tracing::warn!(error = %e, "skipping corrupted history row");
+                tracing::warn!(error = %e, "skipping corrupted selection row");
                 None
             }
         })
-        .collect();
-    Ok(map)
+        .map(|(id, score, last_used)| {
+            let score = decayed(score, now - last_used, config.selection_half_life_days);
+            (id, score)
+        })
+        .collect())
 }
 
-fn insert_launch(
-    conn: &Connection,
-    id: &str,
-    name: &str,
-    exec: &str,
-    icon: &str,
-    description: &str,
-) -> Result<(), rusqlite::Error> {
-    conn.execute(
-        "INSERT INTO launches (id, name, exec, icon, description, count, last_used)
-         VALUES (?1, ?2, ?3, ?4, ?5, 1, julianday('now'))
-         ON CONFLICT(id) DO UPDATE SET
-           count = count + 1,
-           last_used = julianday('now'),
-           name = ?2, exec = ?3, icon = ?4, description = ?5",
-        rusqlite::params![id, name, exec, icon, description],
-    )?;
-    Ok(())
+/// Returns a map of id → decayed pick score for the typed query.
+pub fn get_selection_scores(query: &str, ctx: &AppContext) -> Result<HashMap<String, f64>, String> {
+    let conn = ctx.db.lock()?;
+    query_selection_scores(&conn, query, &history_config(), julian_now()).map_err(|e| e.to_string())
 }
 
-/// Record a launch using AppContext (Tauri-free).
+/// Record a launch of `result` using AppContext (Tauri-free). It is stored
+/// under its category, so each provider can rank by its own history. `query`
+/// is what was typed before picking the result, for learned ranking; `None`
+/// when not picked from a search.
 pub fn record_launch(
-    id: &str,
-    name: &str,
-    exec: &str,
-    icon: &str,
-    description: &str,
+    result: &SearchResult,
+    query: Option<&str>,
     ctx: &AppContext,
 ) -> Result<(), String> {
     let conn = ctx.db.lock()?;
-    insert_launch(&conn, id, name, exec, icon, description).map_err(|e| e.to_string())
+    insert_launch_at(&conn, result, julian_now()).map_err(|e| e.to_string())?;
+    if let Some(query) = query {
+        insert_selection(&conn, query, &result.id, &history_config(), julian_now())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
 }
 
 /// Tauri command wrapper for record_launch.
 #[tauri::command]
 pub fn record_launch_cmd(
-    id: String,
-    name: String,
-    exec: String,
-    icon: String,
-    description: String,
+    result: SearchResult,
+    query: Option<String>,
     app: AppHandle,
 ) -> Result<(), String> {
     use tauri::Manager;
     let ctx = app.state::<AppContext>();
-    record_launch(&id, &name, &exec, &icon, &description, &ctx)
+    record_launch(&result, query.as_deref(), &ctx)
 }
 
 /// Clear all entries from the history database.
 /// Returns the number of entries deleted.
 pub fn clear_all_history(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM selections", [])?;
+    conn.execute("DELETE FROM launch_events", [])?;
     conn.execute("DELETE FROM launches", [])
 }
 
 /// Remove a specific entry from history by its ID.
 /// Returns true if an entry was removed, false if not found.
 pub fn remove_from_history(conn: &Connection, id: &str) -> Result<bool, rusqlite::Error> {
+    conn.execute("DELETE FROM selections WHERE id = ?1", [id])?;
+    conn.execute("DELETE FROM launch_events WHERE id = ?1", [id])?;
     let rows_affected = conn.execute("DELETE FROM launches WHERE id = ?1", [id])?;
     Ok(rows_affected > 0)
 }
@@ -200,6 +736,197 @@ pub fn get_launch_count(conn: &Connection) -> Result<i64, rusqlite::Error> {
     conn.query_row("SELECT COUNT(*) FROM launches", [], |row| row.get(0))
 }
 
+/// The history entry for `id`, if any. Public for CLI use.
+pub fn find_launch(conn: &Connection, id: &str) -> Result<Option<SearchResult>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT id, name, exec, icon, description, category FROM launches WHERE id = ?1",
+        [id],
+        |row| {
+            Ok(SearchResult {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                exec: row.get(2)?,
+                icon: row.get(3)?,
+                description: row.get(4)?,
+                category: history_category(&row.get::<_, String>(5)?),
+                input_spec: None,
+                output_mode: None,
+                output_format: None,
+            })
+        },
+    )
+    .optional()
+}
+
+/// Pinned results (in pin order) and hidden ids, applied to app listings.
+#[derive(Debug, Default)]
+pub struct Marks {
+    pub pinned: Vec<SearchResult>,
+    pub hidden: HashSet<String>,
+}
+
+/// A pin/hide change requested from the launcher or CLI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkAction {
+    Pin,
+    Unpin,
+    TogglePin,
+    Hide,
+    Unhide,
+}
+
+/// Whether a result is pinned or hidden after a [`MarkAction`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MarkState {
+    pub pinned: bool,
+    pub hidden: bool,
+}
+
+/// Results that are only meaningful for the query that produced them.
+fn is_pinnable(category: Category) -> bool {
+    !matches!(
+        category,
+        Category::Math | Category::Chat | Category::Info | Category::Window
+    )
+}
+
+/// Pin `result` after the existing pins. The whole result is stored, so
+/// pinned files or hosts can be listed without searching for them; its
+/// `exec` is re-derived from the id when run, like any other result.
+pub fn pin(conn: &Connection, result: &SearchResult) -> Result<(), String> {
+    if !is_pinnable(result.category) {
+        return Err(format!("{} results can't be pinned", result.name));
+    }
+    let json = serde_json::to_string(result).map_err(|e| e.to_string())?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM hidden WHERE id = ?1", [&result.id])
+        .map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO pins (id, result, pinned_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET result = ?2",
+        rusqlite::params![result.id, json, julian_now()],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Returns true if `id` was pinned.
+pub fn unpin(conn: &Connection, id: &str) -> Result<bool, rusqlite::Error> {
+    Ok(conn.execute("DELETE FROM pins WHERE id = ?1", [id])? > 0)
+}
+
+/// Hide `id` from app listings and search; unpins it.
+pub fn hide(conn: &Connection, id: &str, name: &str) -> Result<(), rusqlite::Error> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM pins WHERE id = ?1", [id])?;
+    tx.execute(
+        "INSERT INTO hidden (id, name, hidden_at) VALUES (?1, ?2, ?3)
+         ON CONFLICT(id) DO UPDATE SET name = ?2",
+        rusqlite::params![id, name, julian_now()],
+    )?;
+    tx.commit()
+}
+
+/// Returns true if `id` was hidden.
+pub fn unhide(conn: &Connection, id: &str) -> Result<bool, rusqlite::Error> {
+    Ok(conn.execute("DELETE FROM hidden WHERE id = ?1", [id])? > 0)
+}
+
+/// Hidden entries as (id, name), oldest first. Public for CLI use.
+pub fn list_hidden(conn: &Connection) -> Result<Vec<(String, String)>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT id, name FROM hidden ORDER BY hidden_at, rowid")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+pub fn query_marks(conn: &Connection) -> Result<Marks, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT result FROM pins ORDER BY pinned_at, rowid")?;
+    let pinned = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .filter_map(|r| {
+            let parsed = r
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(result) => Some(result),
+                Err(e) => {
+                    tracing::warn!(error = %e, "skipping corrupted pin");
+                    None
+                }
+            }
+        })
+        .collect();
+    let hidden = list_hidden(conn)?.into_iter().map(|(id, _)| id).collect();
+    Ok(Marks { pinned, hidden })
+}
+
+/// Get pins and hidden ids using AppContext (Tauri-free).
+pub fn get_marks(ctx: &AppContext) -> Result<Marks, String> {
+    let conn = ctx.db.lock()?;
+    query_marks(&conn).map_err(|e| e.to_string())
+}
+
+fn mark_state(conn: &Connection, id: &str) -> Result<MarkState, rusqlite::Error> {
+    let exists = |table: &str| -> Result<bool, rusqlite::Error> {
+        conn.query_row(
+            &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE id = ?1)"),
+            [id],
+            |row| row.get(0),
+        )
+    };
+    Ok(MarkState {
+        pinned: exists("pins")?,
+        hidden: exists("hidden")?,
+    })
+}
+
+/// Apply a pin/hide action to `result` and report its new state.
+pub fn apply_mark(
+    conn: &Connection,
+    result: &SearchResult,
+    action: MarkAction,
+) -> Result<MarkState, String> {
+    let pinned = mark_state(conn, &result.id)
+        .map_err(|e| e.to_string())?
+        .pinned;
+    match action {
+        MarkAction::Pin => pin(conn, result)?,
+        MarkAction::TogglePin if !pinned => pin(conn, result)?,
+        MarkAction::Unpin | MarkAction::TogglePin => {
+            unpin(conn, &result.id).map_err(|e| e.to_string())?;
+        }
+        MarkAction::Hide => hide(conn, &result.id, &result.name).map_err(|e| e.to_string())?,
+        MarkAction::Unhide => {
+            unhide(conn, &result.id).map_err(|e| e.to_string())?;
+        }
+    }
+    tracing::info!(id = %result.id, ?action, "updated result mark");
+    mark_state(conn, &result.id).map_err(|e| e.to_string())
+}
+
+/// Pin, unpin, hide or unhide a result using AppContext (Tauri-free).
+pub fn mark_result(
+    result: &SearchResult,
+    action: MarkAction,
+    ctx: &AppContext,
+) -> Result<MarkState, String> {
+    let conn = ctx.db.lock()?;
+    apply_mark(&conn, result, action)
+}
+
+/// Tauri command wrapper for mark_result.
+#[tauri::command]
+pub fn mark_result_cmd(
+    result: SearchResult,
+    action: MarkAction,
+    app: AppHandle,
+) -> Result<MarkState, String> {
+    use tauri::Manager;
+    let ctx = app.state::<AppContext>();
+    mark_result(&result, action, &ctx)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,6 +937,43 @@ mod tests {
         conn
     }
 
+    fn app(id: &str, name: &str, exec: &str, icon: &str, description: &str) -> SearchResult {
+        SearchResult {
+            id: id.into(),
+            name: name.into(),
+            description: description.into(),
+            icon: icon.into(),
+            category: Category::App,
+            exec: exec.into(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        }
+    }
+
+    fn insert_launch(
+        conn: &Connection,
+        id: &str,
+        name: &str,
+        exec: &str,
+        icon: &str,
+        description: &str,
+    ) -> Result<(), rusqlite::Error> {
+        super::insert_launch_at(conn, &app(id, name, exec, icon, description), julian_now())
+    }
+
+    fn insert_launch_at(
+        conn: &Connection,
+        id: &str,
+        name: &str,
+        exec: &str,
+        icon: &str,
+        description: &str,
+        now: f64,
+    ) -> Result<(), rusqlite::Error> {
+        super::insert_launch_at(conn, &app(id, name, exec, icon, description), now)
+    }
+
     #[test]
     fn create_table_succeeds() {
         let conn = Connection::open_in_memory().unwrap();
@@ -401,4 +1165,439 @@ mod tests {
         let conn = test_db();
         assert_eq!(get_launch_count(&conn).unwrap(), 0);
     }
+
+    fn selection_config() -> HistoryConfig {
+        HistoryConfig {
+            selection_half_life_days: 10.0,
+            selection_max_prefix: 5,
+            ..HistoryConfig::default()
+        }
+    }
+
+    #[test]
+    fn selections_credit_every_prefix() {
+        let conn = test_db();
+        let config = selection_config();
+        insert_selection(&conn, "  Tele  Gram", "telegram", &config, 100.0).unwrap();
+
+        let mut prefixes: Vec<String> = conn
+            .prepare("SELECT query FROM selections WHERE id = 'telegram'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        prefixes.sort();
+        // Cut to 5 characters, no prefix ending in a space
+        assert_eq!(prefixes, vec!["t", "te", "tel", "tele"]);
+
+        let scores = query_selection_scores(&conn, "TE", &config, 100.0).unwrap();
+        assert_eq!(scores.get("telegram"), Some(&1.0));
+        assert!(query_selection_scores(&conn, "x", &config, 100.0)
+            .unwrap()
+            .is_empty());
+        assert!(query_selection_scores(&conn, " ", &config, 100.0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn selection_scores_decay_with_half_life() {
+        let conn = test_db();
+        let config = selection_config();
+        insert_selection(&conn, "te", "terminal", &config, 100.0).unwrap();
+        insert_selection(&conn, "te", "terminal", &config, 100.0).unwrap();
+        insert_selection(&conn, "te", "telegram", &config, 110.0).unwrap();
+
+        let scores = query_selection_scores(&conn, "te", &config, 110.0).unwrap();
+        // Two picks one half-life ago weigh as much as one pick now
+        assert!((scores["terminal"] - 1.0).abs() < 1e-9);
+        assert!((scores["telegram"] - 1.0).abs() < 1e-9);
+
+        // A new pick adds to the decayed score
+        insert_selection(&conn, "te", "terminal", &config, 110.0).unwrap();
+        let scores = query_selection_scores(&conn, "te", &config, 110.0).unwrap();
+        assert!((scores["terminal"] - 2.0).abs() < 1e-9);
+
+        // Long-unused picks are pruned
+        insert_selection(&conn, "x", "xterm", &config, 300.0).unwrap();
+        assert!(query_selection_scores(&conn, "te", &config, 300.0)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn clearing_history_clears_selections() {
+        let conn = test_db();
+        let config = selection_config();
+        insert_launch(&conn, "a", "A", "a", "", "").unwrap();
+        insert_selection(&conn, "a", "a", &config, 1.0).unwrap();
+        insert_selection(&conn, "b", "b", &config, 1.0).unwrap();
+        assert!(remove_from_history(&conn, "a").unwrap());
+        assert!(query_selection_scores(&conn, "a", &config, 1.0)
+            .unwrap()
+            .is_empty());
+        clear_all_history(&conn).unwrap();
+        assert!(query_selection_scores(&conn, "b", &config, 1.0)
+            .unwrap()
+            .is_empty());
+    }
+
+    fn model(name: &str) -> HistoryConfig {
+        HistoryConfig {
+            frecency_model: name.into(),
+            frecency_half_life_days: 30.0,
+            ..HistoryConfig::default()
+        }
+    }
+
+    fn launch_n(conn: &Connection, id: &str, times: &[f64]) {
+        for &at in times {
+            insert_launch_at(conn, id, id, id, "", "", at).unwrap();
+        }
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn heavy_use_survives_a_day_away() {
+        let now = 10_000.0;
+        for name in ["buckets", "half_life"] {
+            let conn = test_db();
+            // Used daily for a month, then not at all yesterday
+            let daily: Vec<f64> = (2..32).map(|d| now - d as f64).collect();
+            launch_n(&conn, "editor", &daily);
+            launch_n(&conn, "once", &[now - 0.1]);
+            let results = query_frecent_with(&conn, &model(name), now).unwrap();
+            assert_eq!(ids(&results), vec!["editor", "once"], "{name}");
+        }
+    }
+
+    #[test]
+    fn old_use_fades_behind_recent_use() {
+        let now = 10_000.0;
+        for name in ["buckets", "half_life"] {
+            let conn = test_db();
+            // Five launches a year ago lose to three this week
+            launch_n(&conn, "old", &[now - 365.0; 5]);
+            launch_n(&conn, "new", &[now - 1.0, now - 2.0, now - 3.0]);
+            let results = query_frecent_with(&conn, &model(name), now).unwrap();
+            assert_eq!(ids(&results), vec!["new", "old"], "{name}");
+        }
+    }
+
+    #[test]
+    fn bucket_and_half_life_scores() {
+        let now = 1_000.0;
+        let events = [now - 1.0, now - 20.0];
+        // Buckets: 4 launches × avg(100, 50) / 100
+        assert_eq!(frecency(4, &events, &model("buckets"), now), 3.0);
+        let half_life = frecency(4, &[now, now - 30.0], &model("half_life"), now);
+        assert!((half_life - 1.5).abs() < 1e-9, "{half_life}");
+        assert_eq!(frecency(3, &[], &model("buckets"), now), 0.0);
+    }
+
+    #[test]
+    fn scoring_reads_only_the_sampled_events() {
+        let conn = test_db();
+        let now = 10_000.0;
+        let mut times = vec![now - 1_000.0; 2];
+        times.extend([now - 1.0; BUCKET_SAMPLES]);
+        launch_n(&conn, "a", &times);
+
+        let buckets = scored_launches(&conn, &model("buckets"), now, None).unwrap();
+        // 12 launches × the newest 10, all in the 100 bucket
+        assert_eq!(buckets[0].0.score, 12.0);
+        let half_life = scored_launches(&conn, &model("half_life"), now, None).unwrap();
+        // The launches 1000 days back are past the horizon and not read
+        let expected = BUCKET_SAMPLES as f64 * decayed(1.0, 1.0, 30.0);
+        assert!((half_life[0].0.score - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn events_are_capped_per_id() {
+        let conn = test_db();
+        let times: Vec<f64> = (0..MAX_EVENTS_PER_ID + 5).map(|i| i as f64).collect();
+        launch_n(&conn, "a", &times);
+        let (kept, oldest): (i64, f64) = conn
+            .query_row(
+                "SELECT COUNT(*), MIN(used_at) FROM launch_events WHERE id = 'a'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(kept, MAX_EVENTS_PER_ID);
+        assert_eq!(oldest, 5.0);
+        remove_from_history(&conn, "a").unwrap();
+        let left: i64 = conn
+            .query_row("SELECT COUNT(*) FROM launch_events", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(left, 0);
+    }
+
+    #[test]
+    fn migrates_launches_without_events() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE launches (
+                id TEXT PRIMARY KEY, name TEXT NOT NULL, exec TEXT NOT NULL,
+                icon TEXT NOT NULL DEFAULT '', description TEXT NOT NULL DEFAULT '',
+                count INTEGER NOT NULL DEFAULT 0, last_used REAL NOT NULL DEFAULT 0
+            );
+            INSERT INTO launches VALUES ('term', 'Terminal', 'foot', '', '', 40, 9990.0);
+            INSERT INTO launches VALUES ('calc', 'Calculator', 'calc', '', '', 2, 9999.0);",
+        )
+        .unwrap();
+        create_table(&conn).unwrap();
+        // Running again must not duplicate the backfill
+        create_table(&conn).unwrap();
+
+        let events: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM launch_events WHERE id = 'term' AND used_at = 9990.0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(events, 40);
+        let results = query_frecent_with(&conn, &model("buckets"), 10_000.0).unwrap();
+        assert_eq!(ids(&results), vec!["term", "calc"]);
+    }
+
+    fn result(id: &str, category: Category) -> SearchResult {
+        SearchResult {
+            id: id.into(),
+            name: id.to_uppercase(),
+            description: String::new(),
+            icon: String::new(),
+            category,
+            exec: String::new(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        }
+    }
+
+    #[test]
+    fn pins_keep_order_and_full_results() {
+        let conn = test_db();
+        let file = result("/home/u/notes.md", Category::File);
+        pin(&conn, &result("firefox", Category::App)).unwrap();
+        pin(&conn, &file).unwrap();
+        // Re-pinning keeps the original position
+        pin(&conn, &result("firefox", Category::History)).unwrap();
+
+        let marks = query_marks(&conn).unwrap();
+        let ids: Vec<&str> = marks.pinned.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["firefox", "/home/u/notes.md"]);
+        assert_eq!(marks.pinned[1], file);
+        assert!(unpin(&conn, "firefox").unwrap());
+        assert!(!unpin(&conn, "firefox").unwrap());
+        assert_eq!(query_marks(&conn).unwrap().pinned.len(), 1);
+    }
+
+    #[test]
+    fn pin_and_hide_exclude_each_other() {
+        let conn = test_db();
+        let app = result("steam", Category::App);
+        let state = apply_mark(&conn, &app, MarkAction::Hide).unwrap();
+        assert_eq!(
+            state,
+            MarkState {
+                pinned: false,
+                hidden: true
+            }
+        );
+        assert_eq!(
+            list_hidden(&conn).unwrap(),
+            vec![("steam".into(), "STEAM".into())]
+        );
+
+        let state = apply_mark(&conn, &app, MarkAction::TogglePin).unwrap();
+        assert_eq!(
+            state,
+            MarkState {
+                pinned: true,
+                hidden: false
+            }
+        );
+        let state = apply_mark(&conn, &app, MarkAction::TogglePin).unwrap();
+        assert_eq!(
+            state,
+            MarkState {
+                pinned: false,
+                hidden: false
+            }
+        );
+
+        apply_mark(&conn, &app, MarkAction::Pin).unwrap();
+        apply_mark(&conn, &app, MarkAction::Hide).unwrap();
+        let marks = query_marks(&conn).unwrap();
+        assert!(marks.pinned.is_empty());
+        assert!(marks.hidden.contains("steam"));
+        let state = apply_mark(&conn, &app, MarkAction::Unhide).unwrap();
+        assert_eq!(
+            state,
+            MarkState {
+                pinned: false,
+                hidden: false
+            }
+        );
+    }
+
+    #[test]
+    fn query_specific_results_cannot_be_pinned() {
+        let conn = test_db();
+        for category in [
+            Category::Math,
+            Category::Info,
+            Category::Window,
+            Category::Chat,
+        ] {
+            let err = pin(&conn, &result("x", category)).unwrap_err();
+            assert!(err.contains("can't be pinned"), "{err}");
+        }
+        assert!(query_marks(&conn).unwrap().pinned.is_empty());
+    }
+
+    /// Schemas shipped by earlier releases, oldest first. The first three
+    /// predate `user_version`.
+    const LAUNCHES_V0: &str = "CREATE TABLE launches (
+            id TEXT PRIMARY KEY, name TEXT NOT NULL, exec TEXT NOT NULL,
+            icon TEXT NOT NULL DEFAULT '', description TEXT NOT NULL DEFAULT '',
+            count INTEGER NOT NULL DEFAULT 0, last_used REAL NOT NULL DEFAULT 0
+        );
+        INSERT INTO launches VALUES ('term', 'Terminal', 'foot', 'term', 'Shell', 3, 9990.0);";
+    const SELECTIONS_V0: &str = "CREATE TABLE selections (
+            query TEXT NOT NULL, id TEXT NOT NULL, score REAL NOT NULL DEFAULT 0,
+            last_used REAL NOT NULL DEFAULT 0, PRIMARY KEY (query, id)
+        );
+        INSERT INTO selections VALUES ('te', 'term', 2.0, 9990.0);";
+    const EVENTS_V0: &str = "CREATE TABLE launch_events (id TEXT NOT NULL, used_at REAL NOT NULL);
+        CREATE INDEX launch_events_id ON launch_events (id, used_at);
+        INSERT INTO launch_events VALUES ('term', 9980.0);";
+
+    #[test]
+    fn upgrades_every_historical_schema() {
+        let versioned = [
+            LAUNCHES_V0,
+            SELECTIONS_V0,
+            EVENTS_V0,
+            "PRAGMA user_version = 3",
+        ];
+        let fixtures: [(&str, &[&str], i64); 4] = [
+            ("launches only", &[LAUNCHES_V0], 3),
+            ("with selections", &[LAUNCHES_V0, SELECTIONS_V0], 3),
+            (
+                "with launch events",
+                &[LAUNCHES_V0, SELECTIONS_V0, EVENTS_V0],
+                1,
+            ),
+            ("version 3", &versioned, 1),
+        ];
+        for (name, schema, expected_events) in fixtures {
+            let conn = Connection::open_in_memory().unwrap();
+            for sql in schema {
+                conn.execute_batch(sql).unwrap();
+            }
+            create_table(&conn).unwrap();
+            assert_eq!(
+                crate::commands::migrations::schema_version(&conn).unwrap(),
+                MIGRATIONS.last().unwrap().version,
+                "{name}"
+            );
+
+            let results = query_frecent_with(&conn, &model("buckets"), 10_000.0).unwrap();
+            assert_eq!(ids(&results), vec!["term"], "{name}");
+            assert_eq!(results[0].description, "Shell", "{name}");
+            let events: i64 = conn
+                .query_row("SELECT COUNT(*) FROM launch_events", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(events, expected_events, "{name}");
+            let synced: i64 = conn
+                .query_row("SELECT synced FROM launches", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(synced, 0, "{name}");
+            if schema.len() > 1 {
+                let scores = query_selection_scores(&conn, "te", &selection_config(), 9990.0);
+                assert_eq!(scores.unwrap().get("term"), Some(&2.0), "{name}");
+            }
+            // New launches work on the upgraded schema
+            insert_launch_at(&conn, "term", "Terminal", "foot", "", "", 9999.0).unwrap();
+        }
+    }
+
+    #[test]
+    fn infers_categories_of_existing_launches() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::commands::migrations::run(&conn, "history", &MIGRATIONS[..5]).unwrap();
+        for id in [
+            "firefox",
+            "ssh-box",
+            "/home/u/notes.md",
+            "special-cowork",
+            "op-abc",
+        ] {
+            conn.execute(
+                "INSERT INTO launches (id, name, exec, count, last_used) VALUES (?1, ?1, '', 1, 9990.0)",
+                [id],
+            )
+            .unwrap();
+        }
+        create_table(&conn).unwrap();
+
+        let category = |id: &str| find_launch(&conn, id).unwrap().unwrap().category;
+        assert_eq!(category("firefox"), Category::History);
+        assert_eq!(category("ssh-box"), Category::Ssh);
+        assert_eq!(category("/home/u/notes.md"), Category::File);
+        assert_eq!(category("special-cowork"), Category::Special);
+        assert_eq!(category("op-abc"), Category::Onepass);
+    }
+
+    #[test]
+    fn launches_keep_their_category_and_rank_per_category() {
+        let conn = test_db();
+        let now = 10_000.0;
+        for (id, category, times) in [
+            ("firefox", Category::History, 5),
+            ("ssh-box", Category::Ssh, 3),
+            ("ssh-db", Category::Ssh, 1),
+            ("/home/u/a.md", Category::Vector, 2),
+        ] {
+            for _ in 0..times {
+                super::insert_launch_at(&conn, &result(id, category), now - 1.0).unwrap();
+            }
+        }
+        let results = query_frecent_with(&conn, &model("buckets"), now).unwrap();
+        let categories: Vec<Category> = results.iter().map(|r| r.category).collect();
+        assert_eq!(
+            categories,
+            vec![
+                Category::History,
+                Category::Ssh,
+                Category::Vector,
+                Category::Ssh
+            ]
+        );
+
+        let ssh = query_category_scores(&conn, &[Category::Ssh], &model("buckets"), now).unwrap();
+        assert_eq!(ssh.len(), 2);
+        let apps = query_category_scores(&conn, &[Category::App], &model("buckets"), now).unwrap();
+        assert_eq!(apps.keys().collect::<Vec<_>>(), vec!["firefox"]);
+
+        // Used hosts move up; the rest keep the provider's order
+        let mut hosts = vec![
+            result("ssh-web", Category::Ssh),
+            result("ssh-db", Category::Ssh),
+            result("ssh-mail", Category::Ssh),
+            result("ssh-box", Category::Ssh),
+        ];
+        sort_by_scores(&mut hosts, &ssh);
+        assert_eq!(
+            ids(&hosts),
+            vec!["ssh-box", "ssh-db", "ssh-web", "ssh-mail"]
+        );
+    }
 }
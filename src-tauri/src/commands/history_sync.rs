@@ -0,0 +1,663 @@
+//! History export/import and file-based sync between machines.
+//!
+//! The exchange format is JSON (see [`HistoryExport`]). Importing merges into
+//! the local history: launch counts are summed, `last_used` keeps the later
+//! time and launch timestamps are unioned.
+//!
+//! Sync goes through a shared directory in which every machine owns one file,
+//! `<machine>.json`, written only by that machine and replaced atomically. A
+//! sync merges the other machines' files, then rewrites our own. Machines
+//! are named after their hostname and machine id (see [`machine_name`]).
+//! The counts last seen from each peer are kept in `sync_peers`, so only
+//! launches made since then are added, and counts received from peers
+//! (`launches.synced`) are left out of our own file so they never travel back.
+
+use super::history::{self, DbState};
+use crate::router::Category;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Version written to and accepted from export files.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Peer files larger than this are ignored.
+const MAX_FILE_BYTES: u64 = 16 * 1024 * 1024;
+
+const UNIX_EPOCH_JULIAN: f64 = 2_440_587.5;
+
+/// A history export file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistoryExport {
+    pub version: u32,
+    /// Machine that wrote the file
+    #[serde(default)]
+    pub machine: String,
+    pub entries: Vec<ExportEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportEntry {
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+    #[serde(default)]
+    pub icon: String,
+    #[serde(default)]
+    pub description: String,
+    /// Provider the entry was launched from (`app`, `file`, `ssh`, ...).
+    /// Missing or unknown categories are inferred from the id.
+    #[serde(default)]
+    pub category: String,
+    pub count: i64,
+    /// Unix time in seconds
+    pub last_used: f64,
+    /// Unix times of the most recent launches, newest first
+    #[serde(default)]
+    pub launches: Vec<f64>,
+}
+
+fn to_unix(julian: f64) -> f64 {
+    (julian - UNIX_EPOCH_JULIAN) * 86_400.0
+}
+
+fn to_julian(unix: f64) -> f64 {
+    unix / 86_400.0 + UNIX_EPOCH_JULIAN
+}
+
+/// Snapshot of the history DB. With `local_only`, counts received from sync
+/// peers are subtracted and entries that only came from peers are left out.
+pub fn export(
+    conn: &Connection,
+    machine: &str,
+    local_only: bool,
+) -> Result<HistoryExport, rusqlite::Error> {
+    let mut launches: HashMap<String, Vec<f64>> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, used_at FROM launch_events ORDER BY used_at DESC")?;
+    for row in stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+    })? {
+        match row {
+            Ok((id, used_at)) => launches.entry(id).or_default().push(to_unix(used_at)),
+            Err(e) => tracing::warn!(error = %e, "skipping corrupted launch event"),
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, name, exec, icon, description, count, synced, last_used, category
+         FROM launches ORDER BY id",
+    )?;
+    let entries = stmt
+        .query_map([], |row| {
+            let count: i64 = row.get(5)?;
+            let synced: i64 = row.get(6)?;
+            Ok(ExportEntry {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                exec: row.get(2)?,
+                icon: row.get(3)?,
+                description: row.get(4)?,
+                category: row.get(8)?,
+                count: if local_only { count - synced } else { count },
+                last_used: to_unix(row.get(7)?),
+                launches: Vec::new(),
+            })
+        })?
+        .filter_map(|r| match r {
+            Ok(val) => Some(val),
+            Err(e) => {
+                tracing::warn!(error = %e, "skipping corrupted history row");
+                None
+            }
+        })
+        .filter(|entry| !local_only || entry.count > 0)
+        .map(|mut entry| {
+            entry.launches = launches.remove(&entry.id).unwrap_or_default();
+            entry
+        })
+        .collect();
+
+    Ok(HistoryExport {
+        version: FORMAT_VERSION,
+        machine: machine.to_string(),
+        entries,
+    })
+}
+
+fn check_version(export: &HistoryExport) -> Result<(), String> {
+    if export.version != FORMAT_VERSION {
+        return Err(format!(
+            "unsupported history format version {} (expected {FORMAT_VERSION})",
+            export.version
+        ));
+    }
+    Ok(())
+}
+
+fn is_valid(entry: &ExportEntry) -> bool {
+    let valid = !entry.id.trim().is_empty()
+        && entry.count >= 0
+        && entry.last_used.is_finite()
+        && entry.launches.iter().all(|t| t.is_finite());
+    if !valid {
+        tracing::warn!(id = %entry.id, "skipping invalid history entry");
+    }
+    valid
+}
+
+/// Add `added` launches of `entry`, `synced` of which came from a peer.
+/// Name, exec, icon and category follow whichever side was used last.
+fn merge_entry(
+    conn: &Connection,
+    entry: &ExportEntry,
+    added: i64,
+    synced: i64,
+) -> Result<(), rusqlite::Error> {
+    let last_used = to_julian(entry.last_used);
+    let category = Category::from_name(&entry.category)
+        .map(history::stored_category)
+        .unwrap_or_else(|| history::infer_category(&entry.id));
+    conn.execute(
+        "INSERT INTO launches (id, name, exec, icon, description, count, last_used, synced, category)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+         ON CONFLICT(id) DO UPDATE SET
+           count = count + ?6,
+           synced = synced + ?8,
+           name = CASE WHEN ?7 > last_used THEN ?2 ELSE name END,
+           exec = CASE WHEN ?7 > last_used THEN ?3 ELSE exec END,
+           icon = CASE WHEN ?7 > last_used THEN ?4 ELSE icon END,
+           description = CASE WHEN ?7 > last_used THEN ?5 ELSE description END,
+           category = CASE WHEN ?7 > last_used THEN ?9 ELSE category END,
+           last_used = MAX(last_used, ?7)",
+        rusqlite::params![
+            entry.id,
+            entry.name,
+            entry.exec,
+            entry.icon,
+            entry.description,
+            added,
+            last_used,
+            synced,
+            category.as_str()
+        ],
+    )?;
+
+    let fallback = [entry.last_used];
+    let launches = if entry.launches.is_empty() {
+        &fallback[..]
+    } else {
+        &entry.launches[..]
+    };
+    for &used_at in launches.iter().take(history::MAX_EVENTS_PER_ID as usize) {
+        // Times went through a unix-seconds round trip, so match within ~10ms
+        conn.execute(
+            "INSERT INTO launch_events (id, used_at) SELECT ?1, ?2
+             WHERE NOT EXISTS (
+               SELECT 1 FROM launch_events WHERE id = ?1 AND ABS(used_at - ?2) < 1e-7
+             )",
+            rusqlite::params![entry.id, to_julian(used_at)],
+        )?;
+    }
+    history::prune_events(conn, &entry.id)
+}
+
+/// Merge an export into the history DB, summing counts.
+/// Returns the number of entries merged.
+pub fn import(conn: &Connection, export: &HistoryExport) -> Result<usize, String> {
+    check_version(export)?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut merged = 0;
+    for entry in export.entries.iter().filter(|entry| is_valid(entry)) {
+        merge_entry(&tx, entry, entry.count, 0).map_err(|e| e.to_string())?;
+        merged += 1;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(merged)
+}
+
+/// Merge a sync peer's file, adding only launches made since its last merge.
+/// Returns the number of entries that changed.
+fn merge_peer(conn: &Connection, peer: &str, export: &HistoryExport) -> Result<usize, String> {
+    check_version(export)?;
+    let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut merged = 0;
+    for entry in export.entries.iter().filter(|entry| is_valid(entry)) {
+        let seen: i64 = tx
+            .query_row(
+                "SELECT count FROM sync_peers WHERE peer = ?1 AND id = ?2",
+                [peer, &entry.id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+            .unwrap_or(0);
+        // A peer that cleared its history reports less; count again from there
+        let added = entry.count - seen;
+        if added > 0 {
+            merge_entry(&tx, entry, added, added).map_err(|e| e.to_string())?;
+            merged += 1;
+        }
+        tx.execute(
+            "INSERT INTO sync_peers (peer, id, count) VALUES (?1, ?2, ?3)
+             ON CONFLICT(peer, id) DO UPDATE SET count = ?3",
+            rusqlite::params![peer, entry.id, entry.count],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(merged)
+}
+
+/// Outcome of one sync run.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    pub peers: usize,
+    pub merged: usize,
+}
+
+/// Merge every other machine's file in `dir`, then write ours as `<machine>.json`.
+/// The history DB is only locked for the merge, not while files are read or written.
+pub fn sync_dir(db: &DbState, dir: &Path, machine: &str) -> Result<SyncStats, String> {
+    let peers = read_peers(dir, machine)?;
+    let mut stats = SyncStats::default();
+    let ours = {
+        let conn = db.lock()?;
+        for (peer, export) in &peers {
+            stats.merged += merge_peer(&conn, peer, export)?;
+            stats.peers += 1;
+        }
+        export(&conn, machine, true).map_err(|e| e.to_string())?
+    };
+
+    let json = serde_json::to_string_pretty(&ours).map_err(|e| e.to_string())?;
+    let target = dir.join(format!("{machine}.json"));
+    let tmp = dir.join(format!(".{machine}.json.{}.tmp", std::process::id()));
+    std::fs::write(&tmp, json)
+        .and_then(|_| std::fs::rename(&tmp, &target))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("failed to write {}: {e}", target.display())
+        })?;
+    Ok(stats)
+}
+
+/// The other machines' files in `dir`, by machine name. Unreadable files are skipped.
+fn read_peers(dir: &Path, machine: &str) -> Result<Vec<(String, HistoryExport)>, String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("failed to create sync dir {}: {e}", dir.display()))?;
+    let mut peers = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("failed to read sync dir {}: {e}", dir.display()))?;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Some(peer) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        // Hidden files are temporaries, ours or the sync tool's
+        if peer.starts_with('.')
+            || peer == machine
+            || path.extension().and_then(|e| e.to_str()) != Some("json")
+        {
+            continue;
+        }
+        let export = match read_export(&path).and_then(|e| check_version(&e).map(|_| e)) {
+            Ok(export) => export,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "skipping history sync file");
+                continue;
+            }
+        };
+        peers.push((peer.to_string(), export));
+    }
+    Ok(peers)
+}
+
+/// Read and parse an export file.
+pub fn read_export(path: &Path) -> Result<HistoryExport, String> {
+    let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+    if size > MAX_FILE_BYTES {
+        return Err(format!("file is too large ({size} bytes)"));
+    }
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| format!("invalid history export: {e}"))
+}
+
+/// Hex digits of the machine id in [`machine_name`].
+const SUFFIX_LEN: usize = 8;
+
+/// This machine's name in the sync directory: the hostname, reduced to
+/// characters that are safe in a file name, and the start of the machine id,
+/// so machines sharing a hostname ("localhost", cloned images) keep separate
+/// files.
+pub fn machine_name() -> String {
+    let hostname = ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "burrow".into());
+    format!("{}-{}", file_safe(&hostname), machine_id())
+}
+
+/// `/etc/machine-id`, else a random id generated once and kept in the data dir.
+fn machine_id() -> String {
+    let valid = |id: &str| {
+        let id = id.trim();
+        (id.len() >= SUFFIX_LEN && id.chars().all(|c| c.is_ascii_hexdigit()))
+            .then(|| id[..SUFFIX_LEN].to_ascii_lowercase())
+    };
+    if let Some(id) = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok().and_then(|id| valid(&id)))
+    {
+        return id;
+    }
+    let path = super::data_dir().join("sync-machine-id");
+    if let Some(id) = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|id| valid(&id))
+    {
+        return id;
+    }
+    let mut bytes = [0u8; SUFFIX_LEN / 2];
+    if let Err(e) = std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| std::io::Read::read_exact(&mut f, &mut bytes))
+    {
+        tracing::warn!(error = %e, "failed to read /dev/urandom, using the clock");
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        bytes = (nanos ^ std::process::id()).to_be_bytes();
+    }
+    let id: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    if let Err(e) = std::fs::create_dir_all(super::data_dir())
+        .and_then(|_| std::fs::write(&path, format!("{id}\n")))
+    {
+        tracing::warn!(path = %path.display(), error = %e, "failed to save sync machine id");
+    }
+    id
+}
+
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// The configured sync directory, if sync is enabled.
+pub fn configured_dir() -> Option<PathBuf> {
+    let dir = crate::config::try_get_config()
+        .map(|c| c.history.sync_dir.trim().to_string())
+        .unwrap_or_default();
+    (!dir.is_empty()).then(|| crate::indexer::expand_tilde(&dir))
+}
+
+/// Sync on startup and then every `history.sync_interval_mins`, if enabled.
+pub fn start(db: Arc<DbState>) {
+    let Some(dir) = configured_dir() else {
+        return;
+    };
+    let machine = machine_name();
+    tracing::info!(dir = %dir.display(), machine = %machine, "history sync enabled");
+    std::thread::spawn(move || loop {
+        match sync_dir(&db, &dir, &machine) {
+            Ok(stats) => tracing::info!(
+                peers = stats.peers,
+                merged = stats.merged,
+                "history sync complete"
+            ),
+            Err(e) => tracing::warn!(error = %e, "history sync failed"),
+        }
+        let mins = crate::config::get_config().history.sync_interval_mins;
+        std::thread::sleep(std::time::Duration::from_secs(mins * 60));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::SearchResult;
+
+    /// 2023-02-24 as a Julian day
+    const DAY: f64 = 2_460_000.0;
+
+    fn test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        history::create_table(&conn).unwrap();
+        conn
+    }
+
+    fn result(id: &str, exec: &str, category: Category) -> SearchResult {
+        SearchResult {
+            id: id.into(),
+            name: id.into(),
+            description: String::new(),
+            icon: String::new(),
+            category,
+            exec: exec.into(),
+            input_spec: None,
+            output_mode: None,
+            output_format: None,
+        }
+    }
+
+    fn launch(conn: &Connection, id: &str, at: f64) {
+        history::insert_launch_at(conn, &result(id, id, Category::App), at).unwrap();
+    }
+
+    fn row(conn: &Connection, id: &str) -> Option<(String, i64, f64)> {
+        conn.query_row(
+            "SELECT exec, count, last_used FROM launches WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()
+        .unwrap()
+    }
+
+    fn events(conn: &Connection, id: &str) -> i64 {
+        conn.query_row(
+            "SELECT COUNT(*) FROM launch_events WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn import_sums_counts_and_keeps_latest_use() {
+        let laptop = test_db();
+        launch(&laptop, "term", DAY);
+        launch(&laptop, "term", DAY + 2.0);
+        let desktop = test_db();
+        launch(&desktop, "term", DAY + 1.0);
+        let old_term = SearchResult {
+            name: "Term".into(),
+            ..result("term", "old-term", Category::App)
+        };
+        history::insert_launch_at(&desktop, &old_term, DAY + 1.0).unwrap();
+        launch(&desktop, "calc", DAY);
+
+        // Through JSON, as `burrow history export | import` would
+        let json = serde_json::to_string(&export(&laptop, "laptop", false).unwrap()).unwrap();
+        let file: HistoryExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(import(&desktop, &file).unwrap(), 1);
+
+        let (exec, count, last_used) = row(&desktop, "term").unwrap();
+        assert_eq!(count, 4);
+        assert!((last_used - (DAY + 2.0)).abs() < 1e-6);
+        // The laptop used it last, so its metadata wins
+        assert_eq!(exec, "term");
+        // Launch timestamps are unioned: DAY + 1 was launched twice on the desktop
+        assert_eq!(events(&desktop, "term"), 4);
+        assert_eq!(row(&desktop, "calc").unwrap().1, 1);
+    }
+
+    #[test]
+    fn import_rejects_other_versions_and_bad_entries() {
+        let conn = test_db();
+        let bad_version = HistoryExport {
+            version: 99,
+            machine: String::new(),
+            entries: Vec::new(),
+        };
+        assert!(import(&conn, &bad_version)
+            .unwrap_err()
+            .contains("version 99"));
+
+        let file: HistoryExport = serde_json::from_str(
+            r#"{"version": 1, "entries": [
+                {"id": "", "name": "x", "exec": "x", "count": 1, "last_used": 0},
+                {"id": "neg", "name": "x", "exec": "x", "count": -5, "last_used": 0},
+                {"id": "ok", "name": "Ok", "exec": "ok", "count": 2, "last_used": 1700000000}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(import(&conn, &file).unwrap(), 1);
+        assert_eq!(row(&conn, "ok").unwrap().1, 2);
+        // An entry without timestamps still gets one at its last use
+        assert_eq!(events(&conn, "ok"), 1);
+    }
+
+    #[test]
+    fn categories_travel_and_are_inferred_for_old_files() {
+        let laptop = test_db();
+        history::insert_launch_at(
+            &laptop,
+            &result("/home/me/notes.md", "", Category::File),
+            DAY,
+        )
+        .unwrap();
+        let desktop = test_db();
+        let json = serde_json::to_string(&export(&laptop, "laptop", false).unwrap()).unwrap();
+        assert!(json.contains(r#""category":"file""#), "{json}");
+        import(&desktop, &serde_json::from_str(&json).unwrap()).unwrap();
+
+        let old: HistoryExport = serde_json::from_str(
+            r#"{"version": 1, "entries": [
+                {"id": "ssh-box", "name": "box", "exec": "box", "count": 1, "last_used": 0},
+                {"id": "x", "name": "X", "exec": "x", "count": 1, "last_used": 0, "category": "future"}
+            ]}"#,
+        )
+        .unwrap();
+        import(&desktop, &old).unwrap();
+
+        let category = |id: &str| {
+            history::find_launch(&desktop, id)
+                .unwrap()
+                .unwrap()
+                .category
+        };
+        assert_eq!(category("/home/me/notes.md"), Category::File);
+        assert_eq!(category("ssh-box"), Category::Ssh);
+        assert_eq!(category("x"), Category::History);
+    }
+
+    #[test]
+    fn sync_is_idempotent_between_machines() {
+        let dir = tempfile::tempdir().unwrap();
+        let laptop = DbState::new(test_db());
+        let desktop = DbState::new(test_db());
+        for day in 0..3 {
+            launch(&laptop.lock().unwrap(), "term", DAY + day as f64);
+        }
+        launch(&desktop.lock().unwrap(), "calc", DAY);
+
+        for _ in 0..3 {
+            sync_dir(&laptop, dir.path(), "laptop").unwrap();
+            sync_dir(&desktop, dir.path(), "desktop").unwrap();
+        }
+        for conn in [&laptop.lock().unwrap(), &desktop.lock().unwrap()] {
+            assert_eq!(row(conn, "term").unwrap().1, 3);
+            assert_eq!(row(conn, "calc").unwrap().1, 1);
+            assert_eq!(events(conn, "term"), 3);
+        }
+
+        // New launches on either side travel exactly once
+        launch(&desktop.lock().unwrap(), "term", DAY + 5.0);
+        sync_dir(&desktop, dir.path(), "desktop").unwrap();
+        let stats = sync_dir(&laptop, dir.path(), "laptop").unwrap();
+        assert_eq!(
+            stats,
+            SyncStats {
+                peers: 1,
+                merged: 1
+            }
+        );
+        sync_dir(&desktop, dir.path(), "desktop").unwrap();
+        assert_eq!(row(&laptop.lock().unwrap(), "term").unwrap().1, 4);
+        assert_eq!(row(&desktop.lock().unwrap(), "term").unwrap().1, 4);
+    }
+
+    #[test]
+    fn sync_does_not_resurrect_removed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let laptop = DbState::new(test_db());
+        let desktop = DbState::new(test_db());
+        launch(&desktop.lock().unwrap(), "calc", DAY);
+        sync_dir(&desktop, dir.path(), "desktop").unwrap();
+        sync_dir(&laptop, dir.path(), "laptop").unwrap();
+        assert!(row(&laptop.lock().unwrap(), "calc").is_some());
+
+        history::remove_from_history(&laptop.lock().unwrap(), "calc").unwrap();
+        sync_dir(&laptop, dir.path(), "laptop").unwrap();
+        assert!(row(&laptop.lock().unwrap(), "calc").is_none());
+
+        // Launching it here again is exported as our own use only
+        launch(&laptop.lock().unwrap(), "calc", DAY + 1.0);
+        let ours = export(&laptop.lock().unwrap(), "laptop", true).unwrap();
+        assert_eq!(ours.entries[0].count, 1);
+    }
+
+    #[test]
+    fn sync_skips_temporaries_and_broken_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".desktop.json.42.tmp"), "{").unwrap();
+        std::fs::write(dir.path().join(".syncthing.desktop.json"), "{").unwrap();
+        std::fs::write(dir.path().join("broken.json"), "not json").unwrap();
+        std::fs::write(
+            dir.path().join("future.json"),
+            r#"{"version": 2, "entries": []}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hi").unwrap();
+        let db = DbState::new(test_db());
+        launch(&db.lock().unwrap(), "term", DAY);
+
+        let stats = sync_dir(&db, dir.path(), "laptop").unwrap();
+        assert_eq!(stats, SyncStats::default());
+        let ours = read_export(&dir.path().join("laptop.json")).unwrap();
+        assert_eq!(ours.machine, "laptop");
+        assert_eq!(ours.entries.len(), 1);
+        assert!(!dir
+            .path()
+            .read_dir()
+            .unwrap()
+            .flatten()
+            .any(|e| e.file_name().to_string_lossy().contains(".laptop.json")));
+    }
+
+    #[test]
+    fn machine_name_is_file_safe() {
+        let name = machine_name();
+        assert!(!name.is_empty());
+        assert!(name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        let (hostname, id) = name.rsplit_once('-').unwrap();
+        assert!(!hostname.is_empty(), "{name}");
+        assert!(
+            id.len() == SUFFIX_LEN && id.chars().all(|c| c.is_ascii_hexdigit()),
+            "{name}"
+        );
+        assert_eq!(file_safe("my laptop.lan"), "my_laptop_lan");
+    }
+}
@@ -0,0 +1,203 @@
+//! Versioned schema migrations for Burrow's SQLite databases.
+//!
+//! Each database keeps its schema version in SQLite's `user_version` pragma.
+//! On open, every migration newer than the stored version runs in order, each
+//! in its own transaction together with the version bump, so an interrupted
+//! upgrade resumes at the failed step next time. Databases created before
+//! versioning report version 0, so the first migrations must accept any schema
+//! an older Burrow may have left behind (`IF NOT EXISTS`, guarded backfills).
+
+use rusqlite::Connection;
+use std::path::PathBuf;
+
+/// One schema step. `version` is the schema version after it has run.
+pub struct Migration {
+    pub version: i32,
+    pub description: &'static str,
+    /// Drops or rewrites existing data: the database file is copied to
+    /// `<name>.v<old version>.bak` before the first such step runs.
+    pub destructive: bool,
+    pub apply: fn(&Connection) -> Result<(), rusqlite::Error>,
+}
+
+/// The schema version stored in the database.
+pub fn schema_version(conn: &Connection) -> Result<i32, rusqlite::Error> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Bring `conn` up to the latest version in `migrations` (sorted by version).
+/// `db` names the database in logs.
+pub fn run(conn: &Connection, db: &str, migrations: &[Migration]) -> Result<(), rusqlite::Error> {
+    debug_assert!(migrations.windows(2).all(|w| w[0].version < w[1].version));
+    let current = schema_version(conn)?;
+    let latest = migrations.last().map_or(0, |m| m.version);
+    if current > latest {
+        tracing::warn!(
+            db,
+            version = current,
+            supported = latest,
+            "database was written by a newer Burrow, leaving its schema alone"
+        );
+        return Ok(());
+    }
+
+    let pending: Vec<&Migration> = migrations.iter().filter(|m| m.version > current).collect();
+    if pending.iter().any(|m| m.destructive) {
+        if let Some(path) = backup(conn, current)? {
+            tracing::info!(db, path = %path.display(), "backed up database before migrating");
+        }
+    }
+
+    for migration in pending {
+        let tx = conn.unchecked_transaction()?;
+        (migration.apply)(&tx)?;
+        // Pragmas take no bound parameters; the version is our own integer
+        tx.execute_batch(&format!("PRAGMA user_version = {}", migration.version))?;
+        tx.commit()?;
+        tracing::info!(
+            db,
+            version = migration.version,
+            description = migration.description,
+            "applied schema migration"
+        );
+    }
+    Ok(())
+}
+
+/// Copy the database next to itself as `<file>.v<version>.bak`.
+/// In-memory databases have nothing to back up.
+fn backup(conn: &Connection, version: i32) -> Result<Option<PathBuf>, rusqlite::Error> {
+    let Some(path) = conn.path().filter(|p| !p.is_empty()) else {
+        return Ok(None);
+    };
+    let backup = PathBuf::from(format!("{path}.v{version}.bak"));
+    // VACUUM INTO refuses to overwrite; a leftover backup of the same version is stale
+    if let Err(e) = std::fs::remove_file(&backup) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!(path = %backup.display(), error = %e, "failed to remove old backup");
+        }
+    }
+    conn.execute("VACUUM INTO ?1", [backup.to_string_lossy()])?;
+    Ok(Some(backup))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_items(conn: &Connection) -> Result<(), rusqlite::Error> {
+        conn.execute_batch("CREATE TABLE IF NOT EXISTS items (name TEXT NOT NULL)")
+    }
+
+    fn add_rank(conn: &Connection) -> Result<(), rusqlite::Error> {
+        conn.execute_batch("ALTER TABLE items ADD COLUMN rank INTEGER NOT NULL DEFAULT 0")
+    }
+
+    fn rebuild_items(conn: &Connection) -> Result<(), rusqlite::Error> {
+        conn.execute_batch(
+            "CREATE TABLE items_new (name TEXT PRIMARY KEY, rank INTEGER NOT NULL);
+             INSERT OR IGNORE INTO items_new SELECT name, rank FROM items;
+             DROP TABLE items;
+             ALTER TABLE items_new RENAME TO items;",
+        )
+    }
+
+    fn failing(conn: &Connection) -> Result<(), rusqlite::Error> {
+        conn.execute_batch("INSERT INTO items (name) VALUES ('partial'); SELECT * FROM nope")
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "create items",
+            destructive: false,
+            apply: create_items,
+        },
+        Migration {
+            version: 2,
+            description: "add rank",
+            destructive: false,
+            apply: add_rank,
+        },
+        Migration {
+            version: 3,
+            description: "dedupe items",
+            destructive: true,
+            apply: rebuild_items,
+        },
+    ];
+
+    #[test]
+    fn runs_pending_steps_once() {
+        let conn = Connection::open_in_memory().unwrap();
+        run(&conn, "test", &STEPS[..2]).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 2);
+        conn.execute("INSERT INTO items (name, rank) VALUES ('a', 1)", [])
+            .unwrap();
+        // A second ALTER TABLE would fail, so re-running proves nothing re-applies
+        run(&conn, "test", &STEPS[..2]).unwrap();
+        run(&conn, "test", STEPS).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 3);
+    }
+
+    #[test]
+    fn failed_step_rolls_back_and_keeps_version() {
+        let conn = Connection::open_in_memory().unwrap();
+        run(&conn, "test", &STEPS[..1]).unwrap();
+        let broken = [Migration {
+            version: 2,
+            description: "broken",
+            destructive: false,
+            apply: failing,
+        }];
+        assert!(run(&conn, "test", &broken).is_err());
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn newer_schema_is_left_alone() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA user_version = 9").unwrap();
+        run(&conn, "test", STEPS).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 9);
+    }
+
+    #[test]
+    fn destructive_steps_back_up_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let conn = Connection::open(&path).unwrap();
+        run(&conn, "test", &STEPS[..2]).unwrap();
+        conn.execute_batch(
+            "INSERT INTO items (name, rank) VALUES ('a', 1);
+             INSERT INTO items (name, rank) VALUES ('a', 2);",
+        )
+        .unwrap();
+
+        run(&conn, "test", STEPS).unwrap();
+
+        let backup = Connection::open(dir.path().join("test.db.v2.bak")).unwrap();
+        assert_eq!(schema_version(&backup).unwrap(), 2);
+        let rows: i64 = backup
+            .query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 2);
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn non_destructive_steps_skip_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.db");
+        let conn = Connection::open(&path).unwrap();
+        run(&conn, "test", &STEPS[..2]).unwrap();
+        assert!(!dir.path().join("test.db.v0.bak").exists());
+    }
+}
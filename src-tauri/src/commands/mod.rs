@@ -1,14 +1,23 @@
+pub mod appimage;
 pub mod apps;
 pub mod chat;
+pub mod clipboard_ai;
+pub mod desktop_exec;
 pub mod files;
 pub mod health;
 pub mod history;
+pub mod history_sync;
 pub mod math;
+pub mod migrations;
 pub mod onepass;
 pub mod onepass_vault;
+pub mod open_with;
+pub mod password_backends;
 pub mod special;
 pub mod ssh;
+pub mod totp;
 pub mod vectors;
+pub mod windows;
 
 use std::path::PathBuf;
 
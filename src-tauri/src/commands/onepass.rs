@@ -1,8 +1,10 @@
 use crate::commands::onepass_vault;
+use crate::commands::password_backends::{self, MasterPassword, PasswordBackend};
 use crate::process_timeout;
-use crate::router::{Category, SearchResult};
+use crate::router::{Category, InputSpec, SearchResult};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -50,6 +52,9 @@ pub struct OpField {
     pub id: String,
     #[serde(default)]
     pub purpose: String,
+    /// Field type, e.g. "STRING", "CONCEALED" or "OTP"
+    #[serde(rename = "type", default)]
+    pub field_type: String,
     pub value: Option<String>,
 }
 
@@ -83,6 +88,31 @@ impl OpItemDetail {
         })
     }
 
+    /// The `otpauth://` URI of the item's one-time password field.
+    pub fn otp(&self) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.field_type == "OTP")
+            .and_then(|f| f.value.as_deref())
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Labelled fields with a value beyond the built-in username, password,
+    /// notes and one-time password.
+    pub fn custom_fields(&self) -> Vec<onepass_vault::VaultField> {
+        self.fields
+            .iter()
+            .filter(|f| f.purpose.is_empty() && f.field_type != "OTP" && !f.label.is_empty())
+            .filter_map(|f| {
+                let value = f.value.as_deref().filter(|v| !v.is_empty())?;
+                Some(onepass_vault::VaultField {
+                    label: f.label.clone(),
+                    value: value.to_string(),
+                })
+            })
+            .collect()
+    }
+
     /// Extract the primary domain from the item's URLs.
     pub fn primary_domain(&self) -> Option<String> {
         self.urls
@@ -145,11 +175,11 @@ fn clear_all_sessions() {
 
 /// Sign in to a specific 1Password account.
 /// Uses a 120-second timeout to allow for user interaction (password prompts).
-fn signin(account_id: &str) -> Result<String, String> {
+fn signin(program: &Path, account_id: &str) -> Result<String, String> {
     use std::io::Read;
 
     tracing::info!(account_id, "signing in to 1Password account");
-    let mut child = Command::new("op")
+    let mut child = Command::new(program)
         .args(["signin", "--account", account_id, "--raw"])
         .stdin(std::process::Stdio::inherit())
         .stdout(std::process::Stdio::piped())
@@ -196,14 +226,14 @@ fn signin(account_id: &str) -> Result<String, String> {
 
 /// Run `op` with the given args and a `--session` flag.
 /// Uses a 30-second timeout to prevent hanging on unresponsive CLI calls.
-fn run_op_once(args: &[&str], token: &str) -> Result<std::process::Output, String> {
+fn run_op_once(program: &Path, args: &[&str], token: &str) -> Result<std::process::Output, String> {
     use std::io::Read;
 
     let session_flag = format!("--session={token}");
     let mut cmd_args: Vec<&str> = args.to_vec();
     cmd_args.push(&session_flag);
 
-    let mut child = Command::new("op")
+    let mut child = Command::new(program)
         .args(&cmd_args)
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
@@ -241,9 +271,13 @@ fn run_op_once(args: &[&str], token: &str) -> Result<std::process::Output, Strin
 
 /// Run an `op` command with the cached session for a specific account.
 /// Retries with a fresh signin if the cached session is missing or expired.
-fn run_op_with_session(account_id: &str, args: &[&str]) -> Result<std::process::Output, String> {
+fn run_op_with_session(
+    program: &Path,
+    account_id: &str,
+    args: &[&str],
+) -> Result<std::process::Output, String> {
     if let Some(token) = get_session(account_id) {
-        let output = run_op_once(args, &token)?;
+        let output = run_op_once(program, args, &token)?;
         if output.status.success() {
             return Ok(output);
         }
@@ -251,8 +285,8 @@ fn run_op_with_session(account_id: &str, args: &[&str]) -> Result<std::process::
         clear_session(account_id);
     }
 
-    let token = signin(account_id)?;
-    let output = run_op_once(args, &token)?;
+    let token = signin(program, account_id)?;
+    let output = run_op_once(program, args, &token)?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Err(format!("op command failed: {}", stderr.trim()));
@@ -261,11 +295,11 @@ fn run_op_with_session(account_id: &str, args: &[&str]) -> Result<std::process::
 }
 
 /// Fetch all account IDs via `op account list`.
-fn fetch_account_ids() -> Result<Vec<String>, String> {
+fn fetch_account_ids(program: &Path) -> Result<Vec<String>, String> {
     use std::io::Read;
 
     tracing::debug!("fetching 1Password account list");
-    let mut child = Command::new("op")
+    let mut child = Command::new(program)
         .args(["account", "list", "--format=json"])
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped())
@@ -355,134 +389,201 @@ fn fetch_icon_for_domain(domain: &str) -> Option<String> {
     Some(format!("data:image/x-icon;base64,{b64}"))
 }
 
-/// Load all 1Password items into the in-memory vault.
-/// This signs into each account, fetches item details with secrets, and caches icons.
-pub fn load_vault() -> Result<String, String> {
-    if crate::actions::dry_run::is_enabled() {
-        tracing::debug!("[dry-run] load_vault");
-        return Ok("dry-run: vault load skipped".into());
+/// Favicon for an item URL as a data URI, or empty if there is none.
+pub fn icon_for_url(href: &str) -> String {
+    url::Url::parse(href)
+        .ok()
+        .and_then(|u| u.host_str().map(String::from))
+        .and_then(|domain| fetch_icon_for_domain(&domain))
+        .unwrap_or_default()
+}
+
+/// 1Password through the `op` CLI: every account from `op account list`,
+/// signed in with `op signin`.
+pub struct OnePassword {
+    program: PathBuf,
+}
+
+impl OnePassword {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
     }
+}
 
-    let timeout_minutes = crate::config::get_config().onepass.idle_timeout_minutes;
-    let timeout = if timeout_minutes == 0 {
-        Duration::from_secs(u64::MAX / 2) // effectively never
-    } else {
-        Duration::from_secs(timeout_minutes as u64 * 60)
-    };
+impl PasswordBackend for OnePassword {
+    fn name(&self) -> &'static str {
+        "1password"
+    }
 
-    let account_ids = fetch_account_ids()?;
-    if account_ids.is_empty() {
-        return Err("No 1Password accounts found. Is `op` CLI configured?".into());
+    fn label(&self) -> &'static str {
+        "1Password"
     }
 
-    // Sign into each account
-    for account_id in &account_ids {
-        if get_session(account_id).is_none() {
-            signin(account_id)?;
+    /// Signs into each account and fetches item details with secrets.
+    /// Accounts that fail to list are skipped unless all of them do.
+    fn load_items(
+        &self,
+        _password: Option<&str>,
+    ) -> Result<Vec<onepass_vault::VaultItemInput>, String> {
+        let program = self.program.as_path();
+        let account_ids = fetch_account_ids(program)?;
+        if account_ids.is_empty() {
+            return Err("No 1Password accounts found. Is `op` CLI configured?".into());
         }
-    }
 
-    // Fetch item list for each account
-    let mut all_list_items: Vec<(String, OpListItem)> = Vec::new();
-    let mut failed_accounts: Vec<String> = Vec::new();
-    for account_id in &account_ids {
-        tracing::debug!(account_id, "fetching items for account");
-        let output = match run_op_with_session(
-            account_id,
-            &["item", "list", "--account", account_id, "--format=json"],
-        ) {
-            Ok(o) => o,
-            Err(e) => {
-                tracing::warn!(account_id, error = %e, "failed to list items for account");
-                failed_accounts.push(account_id.clone());
-                continue;
+        // Sign into each account
+        for account_id in &account_ids {
+            if get_session(account_id).is_none() {
+                signin(program, account_id)?;
             }
-        };
+        }
 
-        match serde_json::from_slice::<Vec<OpListItem>>(&output.stdout) {
-            Ok(items) => {
-                tracing::debug!(account_id, count = items.len(), "fetched items for account");
-                for item in items {
-                    all_list_items.push((account_id.clone(), item));
+        // Fetch item list for each account
+        let mut all_list_items: Vec<(String, OpListItem)> = Vec::new();
+        let mut failed_accounts: Vec<String> = Vec::new();
+        for account_id in &account_ids {
+            tracing::debug!(account_id, "fetching items for account");
+            let output = match run_op_with_session(
+                program,
+                account_id,
+                &["item", "list", "--account", account_id, "--format=json"],
+            ) {
+                Ok(o) => o,
+                Err(e) => {
+                    tracing::warn!(account_id, error = %e, "failed to list items for account");
+                    failed_accounts.push(account_id.clone());
+                    continue;
+                }
+            };
+
+            match serde_json::from_slice::<Vec<OpListItem>>(&output.stdout) {
+                Ok(items) => {
+                    tracing::debug!(account_id, count = items.len(), "fetched items for account");
+                    for item in items {
+                        all_list_items.push((account_id.clone(), item));
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(account_id, error = %e, "failed to parse items for account");
+                    failed_accounts.push(account_id.clone());
                 }
-            }
-            Err(e) => {
-                tracing::warn!(account_id, error = %e, "failed to parse items for account");
-                failed_accounts.push(account_id.clone());
             }
         }
-    }
+        if failed_accounts.len() == account_ids.len() {
+            return Err(format!(
+                "all accounts failed: {}",
+                failed_accounts.join(", ")
+            ));
+        }
+        if !failed_accounts.is_empty() {
+            tracing::warn!(failed = %failed_accounts.join(", "), "some 1Password accounts failed");
+        }
 
-    tracing::debug!(count = all_list_items.len(), "fetching item details");
+        tracing::debug!(count = all_list_items.len(), "fetching item details");
 
-    // Fetch full item details (with --reveal for secrets)
-    let mut vault_items: Vec<onepass_vault::VaultItemInput> = Vec::new();
+        // Fetch full item details (with --reveal for secrets)
+        let mut vault_items: Vec<onepass_vault::VaultItemInput> = Vec::new();
 
-    for (account_id, list_item) in &all_list_items {
-        let item_id = &list_item.id;
+        for (account_id, list_item) in &all_list_items {
+            let item_id = &list_item.id;
 
-        let detail_output = match run_op_with_session(
-            account_id,
-            &[
-                "item",
-                "get",
-                item_id,
-                "--account",
+            let detail_output = match run_op_with_session(
+                program,
                 account_id,
-                "--format=json",
-                "--reveal",
-            ],
-        ) {
-            Ok(o) => o,
-            Err(e) => {
-                tracing::debug!(item_id, error = %e, "failed to get item");
-                continue;
-            }
-        };
+                &[
+                    "item",
+                    "get",
+                    item_id,
+                    "--account",
+                    account_id,
+                    "--format=json",
+                    "--reveal",
+                ],
+            ) {
+                Ok(o) => o,
+                Err(e) => {
+                    tracing::debug!(item_id, error = %e, "failed to get item");
+                    continue;
+                }
+            };
 
-        let detail: OpItemDetail = match serde_json::from_slice(&detail_output.stdout) {
-            Ok(v) => v,
-            Err(e) => {
-                tracing::debug!(item_id, error = %e, "failed to parse item");
-                continue;
-            }
-        };
+            let detail: OpItemDetail = match serde_json::from_slice(&detail_output.stdout) {
+                Ok(v) => v,
+                Err(e) => {
+                    tracing::debug!(item_id, error = %e, "failed to parse item");
+                    continue;
+                }
+            };
+
+            let username = detail.get_field("username").unwrap_or_default().to_string();
+            let password = detail.get_field("password").unwrap_or_default().to_string();
+            let totp = detail.otp().unwrap_or_default().to_string();
+            let fields = detail.custom_fields();
+
+            // Fetch icon
+            let icon_b64 = detail
+                .primary_domain()
+                .and_then(|domain| fetch_icon_for_domain(&domain))
+                .unwrap_or_default();
+
+            vault_items.push(onepass_vault::VaultItemInput {
+                id: password_backends::item_id(self.name(), item_id),
+                title: detail.title,
+                category: detail.category,
+                icon_b64,
+                account_id: account_id.clone(),
+                source: self.label().into(),
+                username,
+                password,
+                totp,
+                fields,
+            });
+        }
+        Ok(vault_items)
+    }
+}
 
-        let username = detail.get_field("username").unwrap_or_default().to_string();
-        let password = detail.get_field("password").unwrap_or_default().to_string();
+/// Load the items of every backend in `onepass.backends` into the in-memory
+/// vault, unlocking those that take a master password with `password`.
+/// Backends that fail are reported in the message; the load only fails if
+/// none of them succeeds.
+pub fn load_vault(password: Option<&str>) -> Result<String, String> {
+    if crate::actions::dry_run::is_enabled() {
+        tracing::debug!("[dry-run] load_vault");
+        return Ok("dry-run: vault load skipped".into());
+    }
 
-        // Fetch icon
-        let icon_b64 = detail
-            .primary_domain()
-            .and_then(|domain| fetch_icon_for_domain(&domain))
-            .unwrap_or_default();
+    let config = crate::config::get_config().onepass.clone();
+    let timeout = if config.idle_timeout_minutes == 0 {
+        Duration::from_secs(u64::MAX / 2) // effectively never
+    } else {
+        Duration::from_secs(config.idle_timeout_minutes as u64 * 60)
+    };
 
-        vault_items.push(onepass_vault::VaultItemInput {
-            id: item_id.clone(),
-            title: detail.title,
-            category: detail.category,
-            icon_b64,
-            account_id: account_id.clone(),
-            username,
-            password,
-        });
+    let backends = password_backends::configured_backends(&config);
+    let (vault_items, failures) = password_backends::collect_items(&backends, password);
+    if vault_items.is_empty() && !failures.is_empty() {
+        return Err(failures.join("; "));
     }
 
     let count = vault_items.len();
     onepass_vault::store_items(vault_items, timeout);
-    tracing::info!(count, "1Password vault loaded");
-    if failed_accounts.is_empty() {
-        Ok(format!("Loaded {count} 1Password items"))
+    tracing::info!(count, "password vault loaded");
+    let labels: Vec<&str> = backends.iter().map(|b| b.label()).collect();
+    if failures.is_empty() {
+        Ok(format!("Loaded {count} {} items", labels.join(" + ")))
     } else {
         Ok(format!(
-            "Loaded {count} 1Password items ({} account(s) failed: {})",
-            failed_accounts.len(),
-            failed_accounts.join(", ")
+            "Loaded {count} items ({} failed: {})",
+            failures.len(),
+            failures.join("; ")
         ))
     }
 }
 
-/// Search 1Password items. Returns vault results if loaded, or a "Load" action.
+/// Search password manager items. Returns vault results if loaded, or a "Load" action.
 pub async fn search_onepass(query: &str) -> Result<Vec<SearchResult>, String> {
     if query.is_empty() {
         return Ok(vec![]);
@@ -497,30 +598,68 @@ pub async fn search_onepass(query: &str) -> Result<Vec<SearchResult>, String> {
         let results = onepass_vault::search_to_results(query);
         Ok(results)
     } else {
+        let backends = password_backends::configured_backends(&crate::config::get_config().onepass);
+        let name = match backends.as_slice() {
+            [only] => format!("Load {} Data", only.label()),
+            _ => "Load Password Data".into(),
+        };
         Ok(vec![SearchResult {
             id: "op-load-vault".into(),
-            name: "Load 1Password Data".into(),
+            name,
             description: "Sign in and load all vault items into memory".into(),
             icon: "".into(),
             category: Category::Onepass,
             exec: "op-load-vault".into(),
-            input_spec: None,
+            input_spec: master_password_input(&backends),
             output_mode: None,
             output_format: None,
         }])
     }
 }
 
-/// Fetch the password for a 1Password item from the vault.
+/// Masked secondary input for the master password of `backends`, asked for
+/// on Enter when one of them cannot load without it. `None` when none takes
+/// a password.
+fn master_password_input(backends: &[Box<dyn PasswordBackend>]) -> Option<InputSpec> {
+    let wanted: Vec<(&str, MasterPassword)> = backends
+        .iter()
+        .map(|b| (b.label(), b.master_password()))
+        .filter(|(_, wanted)| *wanted != MasterPassword::NotUsed)
+        .collect();
+    if wanted.is_empty() {
+        return None;
+    }
+    let labels: Vec<&str> = wanted.iter().map(|(label, _)| *label).collect();
+    Some(InputSpec {
+        placeholder: format!("{} master password", labels.join(" / ")),
+        template: "{}".into(),
+        optional: wanted
+            .iter()
+            .all(|(_, wanted)| *wanted == MasterPassword::Optional),
+        secret: true,
+    })
+}
+
+/// Fetch the password for a vault item.
 pub fn get_password(item_id: &str) -> Result<Zeroizing<String>, String> {
     onepass_vault::get_password(item_id)
 }
 
-/// Fetch the username for a 1Password item from the vault.
+/// Fetch the username for a vault item.
 pub fn get_username(item_id: &str) -> Result<Zeroizing<String>, String> {
     onepass_vault::get_username(item_id)
 }
 
+/// Compute the current one-time password for a vault item.
+pub fn get_totp(item_id: &str) -> Result<Zeroizing<String>, String> {
+    onepass_vault::get_totp(item_id)
+}
+
+/// Fetch the vault item field whose label best matches `query`.
+pub fn get_field(item_id: &str, query: &str) -> Result<(String, Zeroizing<String>), String> {
+    onepass_vault::get_field(item_id, query)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -599,6 +738,30 @@ mod tests {
         assert_eq!(item.get_field("PASSWORD"), Some("secret123"));
     }
 
+    #[test]
+    fn op_item_detail_otp_and_custom_fields() {
+        let item: OpItemDetail = serde_json::from_str(
+            r#"{
+                "id": "test-id",
+                "fields": [
+                    {"id": "username", "label": "username", "purpose": "USERNAME", "value": "me"},
+                    {"id": "notesPlain", "label": "notesPlain", "purpose": "NOTES", "value": "hi"},
+                    {"id": "x1", "label": "one-time password", "type": "OTP", "value": "otpauth://totp/x?secret=GEZA"},
+                    {"id": "x2", "label": "PIN", "type": "CONCEALED", "value": "4321"},
+                    {"id": "x3", "label": "empty", "type": "STRING", "value": ""}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(item.otp(), Some("otpauth://totp/x?secret=GEZA"));
+        let fields = item.custom_fields();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(
+            (fields[0].label.as_str(), fields[0].value.as_str()),
+            ("PIN", "4321")
+        );
+    }
+
     #[test]
     fn op_item_detail_primary_domain() {
         let item: OpItemDetail = serde_json::from_str(
@@ -646,6 +809,53 @@ mod tests {
         assert_eq!(item.category, "");
     }
 
+    #[test]
+    fn one_password_backend_signs_in_and_reads_items() {
+        let _l = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_all_sessions();
+        let backend = OnePassword::new(password_backends::tests::FAKE_CLI);
+        let items = backend.load_items(None).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, "opitem1");
+        assert_eq!(items[0].source, "1Password");
+        assert_eq!(items[0].username, "octocat");
+        assert_eq!(items[0].password, "op-secret");
+        assert_eq!(
+            items[0].totp,
+            "otpauth://totp/GitHub?secret=GEZDGNBVGY3TQOJQ"
+        );
+        assert_eq!(items[0].fields.len(), 1);
+        assert_eq!(items[0].fields[0].label, "recovery code");
+        assert_eq!(items[0].fields[0].value, "r-123");
+        assert_eq!(&*get_session("ACC1").unwrap(), "fake-op-session");
+        clear_all_sessions();
+    }
+
+    #[test]
+    fn load_result_asks_for_the_master_password_when_needed() {
+        use password_backends::{KeePassXc, Pass};
+        let cli = password_backends::tests::FAKE_CLI;
+        let agent_only: Vec<Box<dyn PasswordBackend>> =
+            vec![Box::new(OnePassword::new(cli)), Box::new(Pass::gopass(cli))];
+        assert!(master_password_input(&agent_only).is_none());
+
+        let with_keepass: Vec<Box<dyn PasswordBackend>> = vec![
+            Box::new(Pass::gopass(cli)),
+            Box::new(KeePassXc::new(cli, PathBuf::from("db.kdbx"), None)),
+        ];
+        let spec = master_password_input(&with_keepass).unwrap();
+        assert_eq!(spec.placeholder, "KeePassXC master password");
+        assert!(spec.secret && !spec.optional);
+
+        // A key file unlocks alone, so Enter loads and Tab asks
+        let key_file: Vec<Box<dyn PasswordBackend>> = vec![Box::new(KeePassXc::new(
+            cli,
+            PathBuf::from("db.kdbx"),
+            Some(PathBuf::from("db.keyx")),
+        ))];
+        assert!(master_password_input(&key_file).unwrap().optional);
+    }
+
     #[test]
     fn empty_query_returns_empty() {
         let results = tokio::runtime::Runtime::new()
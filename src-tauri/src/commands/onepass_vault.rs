@@ -2,21 +2,36 @@ use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
-use crate::router::{Category, SearchResult};
+use crate::router::{Category, InputSpec, SearchResult};
 
 #[derive(Zeroize, ZeroizeOnDrop)]
 struct SecretFields {
     username: String,
     password: String,
+    /// `otpauth://` URI or base32 secret, empty when the item has none
+    totp: String,
+    fields: Vec<VaultField>,
 }
 
-/// A single 1Password item with metadata and zeroize-protected secrets.
+/// A custom field of a vault item (PIN, recovery code, security answer, ...).
+#[derive(Zeroize)]
+pub struct VaultField {
+    pub label: String,
+    pub value: String,
+}
+
+/// Label under which an item's one-time password is listed among its fields.
+pub const TOTP_LABEL: &str = "TOTP";
+
+/// A single password manager item with metadata and zeroize-protected secrets.
 struct VaultItem {
     id: String,
     title: String,
     category: String,
     icon_b64: String,
     account_id: String,
+    /// Label of the backend the item came from
+    source: String,
     secrets: SecretFields,
 }
 
@@ -41,16 +56,12 @@ pub fn is_vault_loaded() -> bool {
         }
     };
     match guard.as_mut() {
-        Some(v) => {
-            if v.last_access.elapsed() < v.timeout {
-                // Refresh last_access to prevent TOCTOU race
-                v.last_access = Instant::now();
-                true
-            } else {
-                false
-            }
+        Some(v) if v.last_access.elapsed() < v.timeout => {
+            // Refresh last_access to prevent TOCTOU race
+            v.last_access = Instant::now();
+            true
         }
-        None => false,
+        _ => false,
     }
 }
 
@@ -65,9 +76,12 @@ pub fn store_items(items: Vec<VaultItemInput>, timeout: Duration) {
             category: i.category,
             icon_b64: i.icon_b64,
             account_id: i.account_id,
+            source: i.source,
             secrets: SecretFields {
                 username: i.username,
                 password: i.password,
+                totp: i.totp,
+                fields: i.fields,
             },
         })
         .collect();
@@ -97,8 +111,11 @@ pub struct VaultItemInput {
     pub category: String,
     pub icon_b64: String,
     pub account_id: String,
+    pub source: String,
     pub username: String,
     pub password: String,
+    pub totp: String,
+    pub fields: Vec<VaultField>,
 }
 
 /// Access the vault with expiry check and last-access touch.
@@ -151,6 +168,89 @@ pub fn get_username(id: &str) -> Result<Zeroizing<String>, String> {
     get_item_field(id, |item| item.secrets.username.clone()).map(Zeroizing::new)
 }
 
+/// The current one-time password of a vault item.
+pub fn get_totp(id: &str) -> Result<Zeroizing<String>, String> {
+    let spec = get_item_field(id, |item| item.secrets.totp.clone()).map(Zeroizing::new)?;
+    if spec.is_empty() {
+        return Err(format!("Item {id} has no one-time password"));
+    }
+    crate::commands::totp::current_code(&spec)
+}
+
+/// The field of a vault item that `query` names: an exact label
+/// (case-insensitive), else the only label starting with or containing it.
+/// [`TOTP_LABEL`] gives the current one-time password.
+pub fn get_field(id: &str, query: &str) -> Result<(String, Zeroizing<String>), String> {
+    with_vault(|vault| {
+        let item = vault
+            .items
+            .iter()
+            .find(|item| item.id == id)
+            .ok_or_else(|| format!("Item {id} not found in vault"))?;
+        let labels = field_labels(&item.secrets);
+        let label = find_label(&labels, query)?.to_string();
+        // The one-time password is listed first, ahead of a custom field of the same name
+        if label == TOTP_LABEL && !item.secrets.totp.is_empty() {
+            let code = crate::commands::totp::current_code(&item.secrets.totp)?;
+            return Ok((label, code));
+        }
+        let value = item
+            .secrets
+            .fields
+            .iter()
+            .find(|f| f.label == label)
+            .map(|f| Zeroizing::new(f.value.clone()))
+            .unwrap_or_default();
+        Ok((label, value))
+    })
+}
+
+/// Labels listed in an item's field picker: the one-time password first,
+/// then custom fields in the item's order.
+fn field_labels(secrets: &SecretFields) -> Vec<String> {
+    let totp = (!secrets.totp.is_empty()).then(|| TOTP_LABEL.to_string());
+    totp.into_iter()
+        .chain(secrets.fields.iter().map(|f| f.label.clone()))
+        .collect()
+}
+
+fn find_label<'a>(labels: &'a [String], query: &str) -> Result<&'a str, String> {
+    let query = query.trim().to_lowercase();
+    let exact = labels.iter().find(|l| l.to_lowercase() == query);
+    let only = |matches: Vec<&'a String>| match matches.as_slice() {
+        [one] => Some(*one),
+        _ => None,
+    };
+    exact
+        .or_else(|| {
+            only(
+                labels
+                    .iter()
+                    .filter(|l| l.to_lowercase().starts_with(&query))
+                    .collect(),
+            )
+        })
+        .or_else(|| {
+            only(
+                labels
+                    .iter()
+                    .filter(|l| l.to_lowercase().contains(&query))
+                    .collect(),
+            )
+        })
+        .map(String::as_str)
+        .ok_or_else(|| {
+            if labels.is_empty() {
+                "Item has no other fields".to_string()
+            } else {
+                format!(
+                    "No single field matches \"{query}\" (fields: {})",
+                    labels.join(", ")
+                )
+            }
+        })
+}
+
 /// Non-secret metadata returned by search.
 pub struct ItemMeta {
     pub id: String,
@@ -158,6 +258,9 @@ pub struct ItemMeta {
     pub category: String,
     pub icon_b64: String,
     pub account_id: String,
+    pub source: String,
+    /// Labels of the item's one-time password and custom fields
+    pub field_labels: Vec<String>,
 }
 
 /// Search the vault by title substring, returning non-secret metadata.
@@ -175,6 +278,8 @@ pub fn search_vault(query: &str) -> Vec<ItemMeta> {
                 category: item.category.clone(),
                 icon_b64: item.icon_b64.clone(),
                 account_id: item.account_id.clone(),
+                source: item.source.clone(),
+                field_labels: field_labels(&item.secrets),
             })
             .collect())
     })
@@ -185,16 +290,32 @@ pub fn search_vault(query: &str) -> Vec<ItemMeta> {
 pub fn search_to_results(query: &str) -> Vec<SearchResult> {
     search_vault(query)
         .into_iter()
-        .map(|m| SearchResult {
-            id: format!("op-{}", m.id),
-            name: m.title,
-            description: format!("{} · ⏎ type pw · ⇧ copy pw · ^C copy user", m.category),
-            icon: m.icon_b64,
-            category: Category::Onepass,
-            exec: format!("op-vault-item:{}", m.id),
-            input_spec: None,
-            output_mode: None,
-            output_format: None,
+        .map(|m| {
+            let mut description = format!(
+                "{} · {} · ⏎ auto-type · ⇧ copy pw · ^C copy user",
+                m.source, m.category
+            );
+            if m.field_labels.iter().any(|l| l == TOTP_LABEL) {
+                description.push_str(" · ⌥ copy otp");
+            }
+            // Tab picks one of the other fields to type (or copy with Shift)
+            let input_spec = (!m.field_labels.is_empty()).then(|| InputSpec {
+                placeholder: format!("Field to type, ⇧ to copy: {}", m.field_labels.join(", ")),
+                template: "{}".into(),
+                optional: true,
+                secret: false,
+            });
+            SearchResult {
+                id: format!("op-{}", m.id),
+                name: m.title,
+                description,
+                icon: m.icon_b64,
+                category: Category::Onepass,
+                exec: format!("op-vault-item:{}", m.id),
+                input_spec,
+                output_mode: None,
+                output_format: None,
+            }
         })
         .collect()
 }
@@ -228,8 +349,11 @@ mod tests {
                 category: "LOGIN".into(),
                 icon_b64: String::new(),
                 account_id: "acc-1".into(),
+                source: "1Password".into(),
                 username: format!("user{i}"),
                 password: format!("pass{i}"),
+                totp: String::new(),
+                fields: Vec::new(),
             })
             .collect()
     }
@@ -303,6 +427,58 @@ mod tests {
         assert_eq!(results[0].id, "op-id-0");
         assert_eq!(results[0].exec, "op-vault-item:id-0");
         assert_eq!(results[0].category, Category::Onepass);
+        assert!(results[0].description.starts_with("1Password · LOGIN · "));
+        clear_vault();
+    }
+
+    #[test]
+    fn fields_and_totp_are_picked_by_label() {
+        let _l = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        clear_vault();
+        let mut items = make_items(2);
+        items[0].totp = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".into();
+        items[0].fields = vec![
+            VaultField {
+                label: "PIN".into(),
+                value: "1234".into(),
+            },
+            VaultField {
+                label: "Recovery code".into(),
+                value: "abcd-efgh".into(),
+            },
+            VaultField {
+                label: "Recovery email".into(),
+                value: "me@example.com".into(),
+            },
+        ];
+        store_items(items, Duration::from_secs(600));
+
+        let (label, value) = get_field("id-0", "pin").unwrap();
+        assert_eq!((label.as_str(), value.as_str()), ("PIN", "1234"));
+        let (label, value) = get_field("id-0", "code").unwrap();
+        assert_eq!(
+            (label.as_str(), value.as_str()),
+            ("Recovery code", "abcd-efgh")
+        );
+        // "recovery" starts two labels
+        let err = get_field("id-0", "recovery").unwrap_err();
+        assert!(err.contains("fields: TOTP, PIN, Recovery code"), "{err}");
+
+        let (label, code) = get_field("id-0", "totp").unwrap();
+        assert_eq!(label, "TOTP");
+        assert_eq!(code.len(), 6);
+        assert_eq!(*get_totp("id-0").unwrap(), *code);
+        assert!(get_totp("id-1").is_err());
+        assert!(get_field("id-1", "pin").is_err());
+
+        let results = search_to_results("Item");
+        assert!(results[0].description.ends_with("⌥ copy otp"));
+        let spec = results[0].input_spec.as_ref().unwrap();
+        assert!(spec.optional);
+        assert!(spec
+            .placeholder
+            .ends_with("TOTP, PIN, Recovery code, Recovery email"));
+        assert!(results[1].input_spec.is_none());
         clear_vault();
     }
 
@@ -0,0 +1,571 @@
+//! "Open with": pick an application for a file result by MIME type.
+//!
+//! Candidates come from the app index (`MimeType=` in desktop files) combined with
+//! `mimeapps.list` defaults and added/removed associations, following the
+//! freedesktop "Association between MIME types and applications" spec.
+
+use super::apps::{self, MimeHandler};
+use crate::context::AppContext;
+use crate::icons;
+use crate::router::{Category, SearchResult};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const FALLBACK_MIME_TYPE: &str = "application/octet-stream";
+
+/// Apps that can open a file, defaults first, as app results for the picker.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenWithChoices {
+    pub path: String,
+    pub mime_type: String,
+    pub apps: Vec<SearchResult>,
+}
+
+/// Detect a file's MIME type: `xdg-mime`, then `file`, then the extension.
+pub fn detect_mime_type(path: &Path) -> String {
+    if path.is_dir() {
+        return "inode/directory".into();
+    }
+    let probes: [(&str, &[&str]); 2] = [
+        ("xdg-mime", &["query", "filetype"]),
+        ("file", &["--brief", "--mime-type"]),
+    ];
+    for (program, args) in probes {
+        let output = Command::new(program).args(args).arg(path).output();
+        if let Ok(output) = output {
+            let mime = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if output.status.success() && is_mime_type(&mime) {
+                return mime;
+            }
+        }
+    }
+    mime_type_from_extension(path)
+        .unwrap_or(FALLBACK_MIME_TYPE)
+        .to_string()
+}
+
+fn is_mime_type(value: &str) -> bool {
+    value.split_once('/').is_some_and(|(major, minor)| {
+        !major.is_empty() && !minor.is_empty() && !value.contains(' ')
+    })
+}
+
+/// Common extensions, used when neither `xdg-mime` nor `file` is available.
+fn mime_type_from_extension(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "rs" => "text/rust",
+        "py" => "text/x-python",
+        "sh" => "application/x-shellscript",
+        "json" => "application/json",
+        "toml" => "application/toml",
+        "yaml" | "yml" => "application/yaml",
+        "xml" => "application/xml",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "csv" => "text/csv",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "flac" => "audio/flac",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "mkv" => "video/x-matroska",
+        "webm" => "video/webm",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        _ => return None,
+    })
+}
+
+/// How well a declared `MimeType=` entry covers a file's type. Lower is better.
+fn match_rank(declared: &str, mime: &str) -> Option<u8> {
+    if declared == mime {
+        return Some(0);
+    }
+    let major = mime.split('/').next().unwrap_or("");
+    if declared.strip_suffix("/*") == Some(major) {
+        return Some(1);
+    }
+    // Every text/* type is a subclass of text/plain
+    if major == "text" && declared == "text/plain" {
+        return Some(2);
+    }
+    None
+}
+
+/// One parsed `mimeapps.list`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct MimeAppsList {
+    defaults: HashMap<String, Vec<String>>,
+    added: HashMap<String, Vec<String>>,
+    removed: HashMap<String, Vec<String>>,
+}
+
+impl MimeAppsList {
+    fn parse(content: &str) -> Self {
+        let mut list = Self::default();
+        let mut section = None;
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = Some(name.to_string());
+                continue;
+            }
+            let map = match section.as_deref() {
+                Some("Default Applications") => &mut list.defaults,
+                Some("Added Associations") => &mut list.added,
+                Some("Removed Associations") => &mut list.removed,
+                _ => continue,
+            };
+            let Some((mime, ids)) = line.split_once('=') else {
+                continue;
+            };
+            map.entry(mime.trim().to_string())
+                .or_default()
+                .extend(split_desktop_ids(ids));
+        }
+        list
+    }
+}
+
+/// `a.desktop;b.desktop;` → `["a", "b"]` (app ids are desktop file stems).
+fn split_desktop_ids(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split(';')
+        .map(|id| id.trim())
+        .filter(|id| !id.is_empty())
+        .map(|id| id.strip_suffix(".desktop").unwrap_or(id).to_string())
+}
+
+/// Resolved associations for one MIME type across all `mimeapps.list` files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Associations {
+    defaults: Vec<String>,
+    added: Vec<String>,
+    removed: HashSet<String>,
+}
+
+/// Merge lists in precedence order. A removal hides an association in its own
+/// file and every lower-precedence file, but not in files listed before it.
+fn associations_for(lists: &[MimeAppsList], mime: &str) -> Associations {
+    let mut result = Associations::default();
+    for list in lists {
+        if let Some(removed) = list.removed.get(mime) {
+            result.removed.extend(removed.iter().cloned());
+        }
+        for id in list.defaults.get(mime).into_iter().flatten() {
+            if !result.defaults.contains(id) {
+                result.defaults.push(id.clone());
+            }
+        }
+        for id in list.added.get(mime).into_iter().flatten() {
+            if !result.removed.contains(id) && !result.added.contains(id) {
+                result.added.push(id.clone());
+            }
+        }
+    }
+    result
+}
+
+/// `mimeapps.list` locations in precedence order, desktop-specific files first.
+fn mimeapps_paths() -> Vec<PathBuf> {
+    let desktops: Vec<String> = std::env::var("XDG_CURRENT_DESKTOP")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|d| !d.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+
+    let mut dirs = Vec::new();
+    if let Some(config) = dirs::config_dir() {
+        dirs.push(config);
+    }
+    let config_dirs = std::env::var("XDG_CONFIG_DIRS").unwrap_or_else(|_| "/etc/xdg".into());
+    dirs.extend(
+        config_dirs
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+    if let Some(data) = dirs::data_dir() {
+        dirs.push(data.join("applications"));
+    }
+    let data_dirs =
+        std::env::var("XDG_DATA_DIRS").unwrap_or_else(|_| "/usr/local/share:/usr/share".into());
+    dirs.extend(
+        data_dirs
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(|d| PathBuf::from(d).join("applications")),
+    );
+
+    dirs.iter()
+        .flat_map(|dir| {
+            desktops
+                .iter()
+                .map(|desktop| dir.join(format!("{desktop}-mimeapps.list")))
+                .chain(std::iter::once(dir.join("mimeapps.list")))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn load_mimeapps_lists(paths: &[PathBuf]) -> Vec<MimeAppsList> {
+    paths
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|content| MimeAppsList::parse(&content))
+        .collect()
+}
+
+/// Order the apps that can open `mime`: mimeapps.list defaults, then added
+/// associations, then apps declaring the type (exact before wildcard/text/plain).
+/// Ids that are not in the app index are skipped.
+fn rank_handlers(
+    handlers: &[MimeHandler],
+    lists: &[MimeAppsList],
+    mime: &str,
+) -> Vec<(MimeHandler, bool)> {
+    let assoc = associations_for(lists, mime);
+    let by_id: HashMap<&str, &MimeHandler> = handlers.iter().map(|h| (h.id.as_str(), h)).collect();
+    let mut seen = HashSet::new();
+    let mut ranked = Vec::new();
+
+    for id in &assoc.defaults {
+        if let Some(handler) = by_id.get(id.as_str()) {
+            if seen.insert(id.clone()) {
+                ranked.push(((*handler).clone(), true));
+            }
+        }
+    }
+    for id in &assoc.added {
+        if let Some(handler) = by_id.get(id.as_str()) {
+            if seen.insert(id.clone()) {
+                ranked.push(((*handler).clone(), false));
+            }
+        }
+    }
+
+    let mut declared: Vec<(u8, &MimeHandler)> = handlers
+        .iter()
+        .filter(|h| !assoc.removed.contains(&h.id))
+        .filter_map(|h| {
+            h.mime_types
+                .iter()
+                .filter_map(|declared| match_rank(declared, mime))
+                .min()
+                .map(|rank| (rank, h))
+        })
+        .collect();
+    declared.sort_by_key(|(rank, _)| *rank);
+    for (_, handler) in declared {
+        if seen.insert(handler.id.clone()) {
+            ranked.push((handler.clone(), false));
+        }
+    }
+    ranked
+}
+
+fn handler_to_result(handler: &MimeHandler, mime: &str, is_default: bool) -> SearchResult {
+    SearchResult {
+        id: handler.id.clone(),
+        name: handler.name.clone(),
+        description: if is_default {
+            format!("Default for {mime}")
+        } else {
+            mime.to_string()
+        },
+        icon: icons::resolve_icon(&handler.icon),
+        category: Category::App,
+        exec: String::new(),
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    }
+}
+
+fn choices_for(
+    path: &str,
+    mime_type: String,
+    handlers: &[MimeHandler],
+    lists: &[MimeAppsList],
+) -> OpenWithChoices {
+    let apps = rank_handlers(handlers, lists, &mime_type)
+        .iter()
+        .map(|(handler, is_default)| handler_to_result(handler, &mime_type, *is_default))
+        .collect();
+    OpenWithChoices {
+        path: path.to_string(),
+        mime_type,
+        apps,
+    }
+}
+
+/// List the apps that can open `path`.
+pub fn open_with_choices(path: &str, ctx: &AppContext) -> Result<OpenWithChoices, String> {
+    let file = Path::new(path);
+    if !file.exists() {
+        return Err(format!("File not found: {path}"));
+    }
+    let mime_type = detect_mime_type(file);
+    let lists = load_mimeapps_lists(&mimeapps_paths());
+    let choices = choices_for(path, mime_type, &ctx.apps.mime_handlers(), &lists);
+    tracing::debug!(path, mime = %choices.mime_type, count = choices.apps.len(), "open with choices");
+    Ok(choices)
+}
+
+/// Open `path` with `app_id`, which must be one of the offered choices.
+pub fn open_with(path: &str, app_id: &str, ctx: &AppContext) -> Result<(), String> {
+    let choices = open_with_choices(path, ctx)?;
+    if !choices.apps.iter().any(|app| app.id == app_id) {
+        return Err(format!("{app_id} cannot open {}", choices.mime_type));
+    }
+    ctx.hide_window();
+    apps::launch_app_by_id(app_id, &[path.to_string()], ctx)
+}
+
+#[tauri::command]
+pub fn open_with_choices_cmd(
+    path: String,
+    app: tauri::AppHandle,
+) -> Result<OpenWithChoices, String> {
+    use tauri::Manager;
+    let ctx = app.state::<AppContext>();
+    open_with_choices(&path, &ctx)
+}
+
+#[tauri::command]
+pub fn open_with_cmd(path: String, app_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+    let ctx = app.state::<AppContext>();
+    open_with(&path, &app_id, &ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handler(id: &str, mime_types: &[&str]) -> MimeHandler {
+        MimeHandler {
+            id: id.into(),
+            name: id.into(),
+            icon: String::new(),
+            mime_types: mime_types.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    fn ids(ranked: &[(MimeHandler, bool)]) -> Vec<&str> {
+        ranked.iter().map(|(h, _)| h.id.as_str()).collect()
+    }
+
+    const USER_LIST: &str = "\
+# user overrides
+[Default Applications]
+image/png=org.gnome.Loupe.desktop;feh.desktop;
+
+[Added Associations]
+image/png=gimp.desktop;
+text/plain=nvim.desktop;
+
+[Removed Associations]
+image/png=eog.desktop;
+";
+
+    #[test]
+    fn parses_mimeapps_sections() {
+        let list = MimeAppsList::parse(USER_LIST);
+        assert_eq!(list.defaults["image/png"], vec!["org.gnome.Loupe", "feh"]);
+        assert_eq!(list.added["text/plain"], vec!["nvim"]);
+        assert_eq!(list.removed["image/png"], vec!["eog"]);
+    }
+
+    #[test]
+    fn unknown_sections_are_ignored() {
+        let list =
+            MimeAppsList::parse("[Other]\nimage/png=x.desktop\n[Default Applications]\nbad line\n");
+        assert!(list.defaults.is_empty());
+        assert!(list.added.is_empty());
+    }
+
+    #[test]
+    fn removal_only_affects_same_and_lower_precedence_files() {
+        let system = MimeAppsList::parse("[Added Associations]\nimage/png=eog.desktop;\n");
+        let user = MimeAppsList::parse(USER_LIST);
+        assert!(
+            !associations_for(&[user.clone(), system.clone()], "image/png")
+                .added
+                .contains(&"eog".to_string())
+        );
+
+        let higher = MimeAppsList::parse("[Added Associations]\nimage/png=eog.desktop;\n");
+        let assoc = associations_for(&[higher, user], "image/png");
+        assert_eq!(assoc.added, vec!["eog", "gimp"]);
+    }
+
+    #[test]
+    fn ranks_defaults_then_added_then_declared() {
+        let handlers = vec![
+            handler("eog", &["image/png"]),
+            handler("gimp", &["image/x-xcf"]),
+            handler("viewer", &["image/*"]),
+            handler("shotwell", &["image/png"]),
+            handler("org.gnome.Loupe", &["image/png"]),
+        ];
+        let lists = vec![MimeAppsList::parse(USER_LIST)];
+        let ranked = rank_handlers(&handlers, &lists, "image/png");
+        // feh is a default but not installed; eog is removed
+        assert_eq!(
+            ids(&ranked),
+            vec!["org.gnome.Loupe", "gimp", "shotwell", "viewer"]
+        );
+        assert!(ranked[0].1);
+        assert!(!ranked[1].1);
+    }
+
+    #[test]
+    fn text_types_fall_back_to_text_plain_handlers() {
+        let handlers = vec![
+            handler("gedit", &["text/plain"]),
+            handler("rust-ide", &["text/rust"]),
+        ];
+        let ranked = rank_handlers(&handlers, &[], "text/rust");
+        assert_eq!(ids(&ranked), vec!["rust-ide", "gedit"]);
+        assert!(rank_handlers(&handlers, &[], "image/png").is_empty());
+    }
+
+    #[test]
+    fn extension_fallback() {
+        assert_eq!(
+            mime_type_from_extension(Path::new("/a/b.PNG")),
+            Some("image/png")
+        );
+        assert_eq!(
+            mime_type_from_extension(Path::new("/a/notes.md")),
+            Some("text/markdown")
+        );
+        assert_eq!(mime_type_from_extension(Path::new("/a/unknown.zzz")), None);
+        assert_eq!(mime_type_from_extension(Path::new("/a/Makefile")), None);
+    }
+
+    #[test]
+    fn detects_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_mime_type(dir.path()), "inode/directory");
+    }
+
+    #[test]
+    fn choices_mark_defaults_in_description() {
+        let handlers = vec![
+            handler("org.gnome.Loupe", &[]),
+            handler("shotwell", &["image/png"]),
+        ];
+        let lists = vec![MimeAppsList::parse(USER_LIST)];
+        let choices = choices_for("/tmp/a.png", "image/png".into(), &handlers, &lists);
+        assert_eq!(choices.apps.len(), 2);
+        assert_eq!(choices.apps[0].description, "Default for image/png");
+        assert_eq!(choices.apps[1].description, "image/png");
+        assert_eq!(choices.apps[0].category, Category::App);
+    }
+
+    fn write_desktop_file(dir: &Path, file_name: &str, exec: &str, mime: &str) {
+        std::fs::write(
+            dir.join(file_name),
+            format!("[Desktop Entry]\nType=Application\nName={file_name}\nExec={exec}\nMimeType={mime}\n"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn open_with_rejects_apps_that_do_not_handle_the_type() {
+        use crate::commands::history::DbState;
+        use crate::commands::vectors::VectorDbState;
+        use rusqlite::Connection;
+        use std::sync::Arc;
+
+        let apps_dir = tempfile::tempdir().unwrap();
+        write_desktop_file(
+            apps_dir.path(),
+            "dir-viewer.desktop",
+            "viewer %f",
+            "inode/directory;",
+        );
+        write_desktop_file(apps_dir.path(), "player.desktop", "player %u", "audio/ogg;");
+        let ctx = AppContext::from_arcs(
+            Arc::new(DbState::new(Connection::open_in_memory().unwrap())),
+            Arc::new(VectorDbState::new(Connection::open_in_memory().unwrap())),
+            Arc::new(crate::indexer::IndexerState::new()),
+            Arc::new(crate::output_buffers::OutputBufferState::new()),
+            Arc::new(apps::AppIndexState::new_for_test(vec![apps_dir
+                .path()
+                .to_path_buf()])),
+        );
+        let target = tempfile::tempdir().unwrap();
+        let path = target.path().to_string_lossy().to_string();
+
+        let choices = open_with_choices(&path, &ctx).unwrap();
+        assert_eq!(choices.mime_type, "inode/directory");
+        assert!(choices.apps.iter().any(|app| app.id == "dir-viewer"));
+
+        let err = open_with(&path, "player", &ctx).unwrap_err();
+        assert!(err.contains("cannot open inode/directory"), "{err}");
+        assert!(open_with_choices("/nonexistent/burrow-file", &ctx).is_err());
+    }
+
+    #[test]
+    fn no_display_default_handler_is_offered_and_launchable() {
+        let apps_dir = tempfile::tempdir().unwrap();
+        write_desktop_file(apps_dir.path(), "gedit.desktop", "gedit %U", "text/plain;");
+        let unlisted = |name: &str, extra: &str| {
+            std::fs::write(
+                apps_dir.path().join(format!("{name}.desktop")),
+                format!(
+                    "[Desktop Entry]\nType=Application\nName={name}\nExec={name} %F\n\
+                     MimeType=text/plain;\n{extra}\n"
+                ),
+            )
+            .unwrap();
+        };
+        unlisted("vim", "NoDisplay=true");
+        unlisted("gone", "NoDisplay=true\nHidden=true");
+        unlisted(
+            "missing",
+            "NoDisplay=true\nTryExec=/nonexistent/burrow-missing",
+        );
+        let index = apps::AppIndexState::new_for_test(vec![apps_dir.path().to_path_buf()]);
+        let lists = vec![MimeAppsList::parse(
+            "[Default Applications]\ntext/plain=vim.desktop;gone.desktop;\n",
+        )];
+
+        let choices = choices_for(
+            "/tmp/a.txt",
+            "text/plain".into(),
+            &index.mime_handlers(),
+            &lists,
+        );
+        let ids: Vec<&str> = choices.apps.iter().map(|app| app.id.as_str()).collect();
+        // Hidden and failed TryExec entries are still left out
+        assert_eq!(ids, vec!["vim", "gedit"]);
+        assert_eq!(choices.apps[0].description, "Default for text/plain");
+        // Not listed in search, but launchable as a handler
+        assert!(index.search("vim").iter().all(|r| r.id != "vim"));
+        let launches = index
+            .launch_commands("vim", &["/tmp/a.txt".to_string()])
+            .unwrap();
+        assert_eq!(launches[0].argv, vec!["vim", "/tmp/a.txt"]);
+    }
+}
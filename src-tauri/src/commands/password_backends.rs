@@ -0,0 +1,1003 @@
+//! Password managers behind the `!` provider.
+//!
+//! Each backend drives a password manager's CLI to unlock the store and read
+//! every item with its secrets, which `onepass::load_vault` then hands to the
+//! in-memory vault. Burrow has no terminal for the CLIs to prompt on, so a
+//! master password is asked for in Burrow's secondary input and handed to
+//! the CLI through the environment or a stdin pipe, never argv.
+
+use crate::commands::onepass_vault::{VaultField, VaultItemInput};
+use crate::config::OnePassConfig;
+use crate::indexer::expand_tilde;
+use crate::process_timeout;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Backend names accepted in `onepass.backends`.
+pub const BACKEND_NAMES: [&str; 5] = ["1password", "bitwarden", "pass", "gopass", "keepassxc"];
+
+/// Time allowed for commands that may wait on a master password prompt.
+const UNLOCK_TIMEOUT: Duration = Duration::from_secs(120);
+/// Time allowed for every other CLI call.
+const CLI_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time allowed for decrypting a whole pass store, first prompt included.
+const PASS_LOAD_TIMEOUT: Duration = Duration::from_secs(300);
+/// pass entries decrypted at once after the first.
+const PASS_WORKERS: usize = 4;
+
+/// Whether a backend needs a master password from the user to load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterPassword {
+    /// Unlocked some other way (desktop app, gpg-agent's pinentry)
+    NotUsed,
+    /// Used when given, but a key file or live session can do without
+    Optional,
+    Required,
+}
+
+/// A password manager that can be read into the vault.
+pub trait PasswordBackend {
+    /// Name used in `onepass.backends` and in item ids.
+    fn name(&self) -> &'static str;
+    /// Shown in result descriptions and load messages.
+    fn label(&self) -> &'static str;
+    /// Whether [`load_items`](Self::load_items) wants a master password.
+    fn master_password(&self) -> MasterPassword {
+        MasterPassword::NotUsed
+    }
+    /// Unlock the store if needed and read all items with their secrets.
+    /// `password` is the master password the user typed, if any.
+    fn load_items(&self, password: Option<&str>) -> Result<Vec<VaultItemInput>, String>;
+}
+
+/// Vault id for item `raw_id` of `backend`. 1Password ids stay bare so
+/// history and pins recorded before other backends existed keep resolving.
+pub fn item_id(backend: &str, raw_id: &str) -> String {
+    if backend == "1password" {
+        raw_id.to_string()
+    } else {
+        format!("{backend}:{raw_id}")
+    }
+}
+
+/// The backends listed in `onepass.backends`, in order, using the CLIs on `PATH`.
+pub fn configured_backends(config: &OnePassConfig) -> Vec<Box<dyn PasswordBackend>> {
+    config
+        .backends
+        .iter()
+        .filter_map(|name| -> Option<Box<dyn PasswordBackend>> {
+            match name.as_str() {
+                "1password" => Some(Box::new(crate::commands::onepass::OnePassword::new("op"))),
+                "bitwarden" => Some(Box::new(Bitwarden::new("bw"))),
+                "pass" => Some(Box::new(Pass::new("pass", pass_store_dir()))),
+                "gopass" => Some(Box::new(Pass::gopass("gopass"))),
+                "keepassxc" => Some(Box::new(KeePassXc::new(
+                    "keepassxc-cli",
+                    expand_tilde(&config.keepassxc_database),
+                    (!config.keepassxc_key_file.is_empty())
+                        .then(|| expand_tilde(&config.keepassxc_key_file)),
+                ))),
+                other => {
+                    tracing::warn!(backend = other, "unknown password backend, skipping");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Load every backend, giving `password` to those that take one, and keep
+/// what loaded. Returns the items and a `label: error` entry per backend
+/// that failed.
+pub fn collect_items(
+    backends: &[Box<dyn PasswordBackend>],
+    password: Option<&str>,
+) -> (Vec<VaultItemInput>, Vec<String>) {
+    let mut items = Vec::new();
+    let mut failures = Vec::new();
+    for backend in backends {
+        let password = password.filter(|_| backend.master_password() != MasterPassword::NotUsed);
+        match backend.load_items(password) {
+            Ok(loaded) => {
+                tracing::debug!(
+                    backend = backend.name(),
+                    count = loaded.len(),
+                    "loaded password items"
+                );
+                items.extend(loaded);
+            }
+            Err(e) => {
+                tracing::warn!(backend = backend.name(), error = %e, "password backend failed to load");
+                failures.push(format!("{}: {e}", backend.label()));
+            }
+        }
+    }
+    (items, failures)
+}
+
+/// Run `cmd` to completion within `timeout`, reading stdout and stderr while
+/// it runs (a whole vault easily outgrows the pipe buffer). `input` is
+/// written to stdin, which is otherwise empty so a CLI that falls back to
+/// prompting fails instead of waiting. Returns stdout, or stderr as the
+/// error when the command fails.
+fn run_cli(
+    mut cmd: Command,
+    timeout: Duration,
+    input: Option<&[u8]>,
+) -> Result<Zeroizing<Vec<u8>>, String> {
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let mut child = cmd
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+
+    // Dropping the pipe after writing closes it, ending the CLI's read
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        if let Err(e) = stdin.write_all(input) {
+            // The exit status below says why the CLI stopped reading
+            tracing::debug!(program, error = %e, "failed to write password manager stdin");
+        }
+    }
+
+    let stdout = child.stdout.take();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut buf = Zeroizing::new(Vec::new());
+        if let Some(mut out) = stdout {
+            if let Err(e) = out.read_to_end(&mut buf) {
+                tracing::warn!(error = %e, "failed to read password manager stdout");
+            }
+        }
+        buf
+    });
+    let stderr = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = String::new();
+        if let Some(mut err) = stderr {
+            if let Err(e) = err.read_to_string(&mut buf) {
+                tracing::warn!(error = %e, "failed to read password manager stderr");
+            }
+        }
+        buf
+    });
+
+    let status =
+        match process_timeout::wait_with_timeout(&mut child, timeout).map_err(|e| e.to_string())? {
+            Some(status) => status,
+            None => {
+                process_timeout::kill_and_reap(&mut child);
+                return Err(format!("{program} timed out after {}s", timeout.as_secs()));
+            }
+        };
+    let stdout = stdout_reader
+        .join()
+        .map_err(|_| format!("{program} output reader panicked"))?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(format!("{program} failed: {}", stderr.trim()));
+    }
+    Ok(stdout)
+}
+
+/// First line of CLI output, for session tokens.
+fn first_line(output: &[u8]) -> Zeroizing<String> {
+    Zeroizing::new(
+        String::from_utf8_lossy(output)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    )
+}
+
+// --- Bitwarden ---
+
+/// Session from the last `bw unlock`, reused until `bw` rejects it.
+static BW_SESSION: Mutex<Option<Zeroizing<String>>> = Mutex::new(None);
+
+/// The cached session. Only held to read or replace it, never across a `bw`
+/// call, so asking whether a password is needed doesn't wait on the CLI.
+fn bw_session() -> MutexGuard<'static, Option<Zeroizing<String>>> {
+    BW_SESSION.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Variable `bw unlock --passwordenv` reads the master password from.
+const BW_PASSWORD_ENV: &str = "BURROW_BW_PASSWORD";
+
+/// Bitwarden through the `bw` CLI. An exported `BW_SESSION` is used as is;
+/// otherwise the vault is unlocked with `bw unlock --raw --passwordenv`,
+/// the master password going through the environment. The account must
+/// already be logged in with `bw login`.
+pub struct Bitwarden {
+    program: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct BwItem {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: u8,
+    login: Option<BwLogin>,
+    #[serde(default)]
+    fields: Option<Vec<BwField>>,
+}
+
+#[derive(Deserialize)]
+struct BwLogin {
+    username: Option<String>,
+    password: Option<String>,
+    #[serde(default)]
+    uris: Option<Vec<BwUri>>,
+    totp: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BwField {
+    name: Option<String>,
+    value: Option<String>,
+    /// 0 text, 1 hidden, 2 boolean, 3 linked (no value of its own)
+    #[serde(rename = "type", default)]
+    kind: u8,
+}
+
+#[derive(Deserialize)]
+struct BwUri {
+    uri: Option<String>,
+}
+
+impl Bitwarden {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+
+    fn list_items(&self, session: &str) -> Result<Zeroizing<Vec<u8>>, String> {
+        let mut cmd = Command::new(&self.program);
+        // The session goes through the environment, not argv, to stay out of `ps`
+        cmd.args(["list", "items", "--nointeraction"])
+            .env("BW_SESSION", session);
+        run_cli(cmd, CLI_TIMEOUT, None)
+    }
+
+    fn unlock(&self, password: Option<&str>) -> Result<Zeroizing<String>, String> {
+        let password = password.ok_or("Bitwarden master password required")?;
+        tracing::info!("unlocking Bitwarden vault");
+        let mut cmd = Command::new(&self.program);
+        cmd.args([
+            "unlock",
+            "--raw",
+            "--nointeraction",
+            "--passwordenv",
+            BW_PASSWORD_ENV,
+        ])
+        .env(BW_PASSWORD_ENV, password);
+        let token = first_line(&run_cli(cmd, CLI_TIMEOUT, None)?);
+        if token.is_empty() {
+            return Err("bw unlock returned an empty session".into());
+        }
+        Ok(token)
+    }
+}
+
+fn bw_kind(kind: u8) -> &'static str {
+    match kind {
+        1 => "Login",
+        2 => "Secure Note",
+        3 => "Card",
+        4 => "Identity",
+        5 => "SSH Key",
+        _ => "Item",
+    }
+}
+
+impl PasswordBackend for Bitwarden {
+    fn name(&self) -> &'static str {
+        "bitwarden"
+    }
+
+    fn label(&self) -> &'static str {
+        "Bitwarden"
+    }
+
+    fn master_password(&self) -> MasterPassword {
+        if bw_session().is_some() || std::env::var_os("BW_SESSION").is_some() {
+            MasterPassword::Optional
+        } else {
+            MasterPassword::Required
+        }
+    }
+
+    fn load_items(&self, password: Option<&str>) -> Result<Vec<VaultItemInput>, String> {
+        let existing = std::env::var("BW_SESSION")
+            .ok()
+            .map(Zeroizing::new)
+            .or_else(|| bw_session().clone());
+        let output = match existing.map(|session| (self.list_items(&session), session)) {
+            Some((Ok(output), _)) => output,
+            previous => {
+                if let Some((Err(e), rejected)) = previous {
+                    tracing::debug!(error = %e, "Bitwarden session rejected, unlocking");
+                    // Ask for the password next time rather than offer a dead session
+                    let mut cached = bw_session();
+                    if cached.as_ref() == Some(&rejected) {
+                        *cached = None;
+                    }
+                }
+                let session = self.unlock(password)?;
+                let output = self.list_items(&session)?;
+                *bw_session() = Some(session);
+                output
+            }
+        };
+
+        let items: Vec<BwItem> = serde_json::from_slice(&output)
+            .map_err(|e| format!("Failed to parse bw items: {e}"))?;
+        Ok(items
+            .into_iter()
+            .map(|item| {
+                let (username, password, url, totp) = match item.login {
+                    Some(login) => (
+                        login.username.unwrap_or_default(),
+                        login.password.unwrap_or_default(),
+                        login
+                            .uris
+                            .unwrap_or_default()
+                            .into_iter()
+                            .find_map(|u| u.uri),
+                        login.totp.unwrap_or_default(),
+                    ),
+                    None => Default::default(),
+                };
+                let fields = item
+                    .fields
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|f| f.kind != 3)
+                    .filter_map(|f| match (f.name, f.value) {
+                        (Some(label), Some(value)) if !label.is_empty() && !value.is_empty() => {
+                            Some(VaultField { label, value })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                VaultItemInput {
+                    id: item_id(self.name(), &item.id),
+                    title: item.name,
+                    category: bw_kind(item.kind).into(),
+                    icon_b64: url
+                        .as_deref()
+                        .map(crate::commands::onepass::icon_for_url)
+                        .unwrap_or_default(),
+                    account_id: String::new(),
+                    source: self.label().into(),
+                    username,
+                    password,
+                    totp,
+                    fields,
+                }
+            })
+            .collect())
+    }
+}
+
+// --- pass / gopass ---
+
+/// `$PASSWORD_STORE_DIR`, else `~/.password-store`.
+fn pass_store_dir() -> PathBuf {
+    std::env::var_os("PASSWORD_STORE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".password-store")))
+        .unwrap_or_else(|| PathBuf::from(".password-store"))
+}
+
+/// The standard Unix password store through `pass`, or gopass. Entries use
+/// the usual layout: the password on the first line, then `key: value`
+/// lines, of which `login`, `username` or `user` is the username. A
+/// pass-otp `otpauth://` line or a `totp:` key holds the one-time password
+/// secret; any other key is offered as a field.
+pub struct Pass {
+    program: PathBuf,
+    /// Walked for `*.gpg` entries; gopass lists its stores itself
+    store_dir: Option<PathBuf>,
+}
+
+impl Pass {
+    pub fn new(program: impl Into<PathBuf>, store_dir: PathBuf) -> Self {
+        Self {
+            program: program.into(),
+            store_dir: Some(store_dir),
+        }
+    }
+
+    pub fn gopass(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            store_dir: None,
+        }
+    }
+
+    fn entries(&self) -> Result<Vec<String>, String> {
+        match &self.store_dir {
+            Some(dir) => Ok(store_entries(dir)),
+            None => {
+                let mut cmd = Command::new(&self.program);
+                cmd.args(["ls", "--flat"]);
+                let output = run_cli(cmd, CLI_TIMEOUT, None)?;
+                Ok(String::from_utf8_lossy(&output)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect())
+            }
+        }
+    }
+
+    fn show(&self, entry: &str, timeout: Duration) -> Result<Zeroizing<Vec<u8>>, String> {
+        let mut cmd = Command::new(&self.program);
+        cmd.arg("show");
+        if self.store_dir.is_none() {
+            // Print the password even with gopass's safecontent setting on
+            cmd.arg("-f");
+        }
+        cmd.arg("--").arg(entry);
+        run_cli(cmd, timeout, None)
+    }
+
+    /// Decrypt `entries` from index `next` on, claiming one at a time, until
+    /// none are left or one fails. A failed pinentry would fail, and prompt
+    /// again for, every later entry too, so a failure stops the other workers.
+    fn decrypt_shared(
+        &self,
+        entries: &[String],
+        next: &AtomicUsize,
+        deadline: Instant,
+    ) -> Result<Vec<(usize, VaultItemInput)>, String> {
+        let mut done = Vec::new();
+        loop {
+            let index = next.fetch_add(1, Ordering::SeqCst);
+            let Some(entry) = entries.get(index) else {
+                return Ok(done);
+            };
+            match self.item(entry, deadline) {
+                Ok(item) => done.push((index, item)),
+                Err(e) => {
+                    next.store(entries.len(), Ordering::SeqCst);
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Decrypt and parse `entry`, giving up at `deadline`.
+    fn item(&self, entry: &str, deadline: Instant) -> Result<VaultItemInput, String> {
+        // The first decryption may wait on pinentry
+        let timeout = deadline
+            .saturating_duration_since(Instant::now())
+            .min(UNLOCK_TIMEOUT);
+        if timeout.is_zero() {
+            return Err(format!(
+                "{entry}: reading the store took longer than {}s",
+                PASS_LOAD_TIMEOUT.as_secs()
+            ));
+        }
+        let output = self
+            .show(entry, timeout)
+            .map_err(|e| format!("{entry}: {e}"))?;
+        let mut content = String::from_utf8_lossy(&output).into_owned();
+        let parsed = parse_pass_entry(&content);
+        content.zeroize();
+        Ok(VaultItemInput {
+            id: item_id(self.name(), entry),
+            title: entry.to_string(),
+            category: "Password".into(),
+            icon_b64: parsed
+                .url
+                .as_deref()
+                .map(crate::commands::onepass::icon_for_url)
+                .unwrap_or_default(),
+            account_id: String::new(),
+            source: self.label().into(),
+            username: parsed.username,
+            password: parsed.password,
+            totp: parsed.totp,
+            fields: parsed.fields,
+        })
+    }
+}
+
+/// Entry names (`email/gmail.com`) of the `*.gpg` files under `dir`,
+/// skipping hidden files and directories such as `.git`.
+fn store_entries(dir: &Path) -> Vec<String> {
+    let mut entries: Vec<String> = walkdir::WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(dir).ok()?.to_str()?;
+            relative.strip_suffix(".gpg").map(String::from)
+        })
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// The parts of a decrypted pass entry.
+#[derive(Default)]
+struct PassEntry {
+    password: String,
+    username: String,
+    url: Option<String>,
+    totp: String,
+    fields: Vec<VaultField>,
+}
+
+fn parse_pass_entry(content: &str) -> PassEntry {
+    let mut lines = content.lines();
+    let mut entry = PassEntry {
+        password: lines.next().unwrap_or_default().to_string(),
+        ..Default::default()
+    };
+    for line in lines {
+        let line = line.trim();
+        if line.starts_with("otpauth://") {
+            if entry.totp.is_empty() {
+                entry.totp = line.to_string();
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key.to_ascii_lowercase().as_str() {
+            "login" | "username" | "user" if entry.username.is_empty() => {
+                entry.username = value.to_string()
+            }
+            "url" if entry.url.is_none() => entry.url = Some(value.to_string()),
+            "totp" | "otp" if entry.totp.is_empty() => entry.totp = value.to_string(),
+            // Later duplicates of the keys above are not offered as fields
+            "login" | "username" | "user" | "url" | "totp" | "otp" => {}
+            _ if !key.is_empty() && !value.is_empty() => entry.fields.push(VaultField {
+                label: key.to_string(),
+                value: value.to_string(),
+            }),
+            _ => {}
+        }
+    }
+    entry
+}
+
+impl PasswordBackend for Pass {
+    fn name(&self) -> &'static str {
+        if self.store_dir.is_some() {
+            "pass"
+        } else {
+            "gopass"
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        if self.store_dir.is_some() {
+            "pass"
+        } else {
+            "gopass"
+        }
+    }
+
+    fn load_items(&self, _password: Option<&str>) -> Result<Vec<VaultItemInput>, String> {
+        let entries = self.entries()?;
+        let deadline = Instant::now() + PASS_LOAD_TIMEOUT;
+        let Some((first, rest)) = entries.split_first() else {
+            return Ok(Vec::new());
+        };
+        // The first entry alone, so gpg-agent asks for the passphrase once;
+        // the rest are decrypted in parallel with the unlocked key
+        let mut items = vec![self.item(first, deadline)?];
+
+        let next = AtomicUsize::new(0);
+        let decrypted: Vec<_> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..PASS_WORKERS.min(rest.len()))
+                .map(|_| scope.spawn(|| self.decrypt_shared(rest, &next, deadline)))
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|_| Err("pass decryption panicked".into()))
+                })
+                .collect()
+        });
+        let mut rest_items = Vec::with_capacity(rest.len());
+        for result in decrypted {
+            rest_items.extend(result?);
+        }
+        rest_items.sort_by_key(|(index, _)| *index);
+        items.extend(rest_items.into_iter().map(|(_, item)| item));
+        Ok(items)
+    }
+}
+
+// --- KeePassXC ---
+
+/// A KeePassXC database read with `keepassxc-cli export --format csv`. The
+/// database password is piped to its stdin; with a key file and no password
+/// the key file alone unlocks it (`--no-password`).
+pub struct KeePassXc {
+    program: PathBuf,
+    database: PathBuf,
+    key_file: Option<PathBuf>,
+}
+
+impl KeePassXc {
+    pub fn new(program: impl Into<PathBuf>, database: PathBuf, key_file: Option<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            database,
+            key_file,
+        }
+    }
+}
+
+/// Split CSV `text` into records of fields, honouring double-quoted fields
+/// with `""` escapes and embedded newlines.
+fn parse_csv(text: &str) -> Vec<Vec<Zeroizing<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = Zeroizing::new(String::new());
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            ('\r', false) => {}
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+impl PasswordBackend for KeePassXc {
+    fn name(&self) -> &'static str {
+        "keepassxc"
+    }
+
+    fn label(&self) -> &'static str {
+        "KeePassXC"
+    }
+
+    fn master_password(&self) -> MasterPassword {
+        if self.key_file.is_some() {
+            MasterPassword::Optional
+        } else {
+            MasterPassword::Required
+        }
+    }
+
+    fn load_items(&self, password: Option<&str>) -> Result<Vec<VaultItemInput>, String> {
+        if self.database.as_os_str().is_empty() {
+            return Err("onepass.keepassxc_database is not set".into());
+        }
+        let mut cmd = Command::new(&self.program);
+        cmd.args(["export", "--format", "csv"]);
+        if let Some(key_file) = &self.key_file {
+            cmd.arg("--key-file").arg(key_file);
+        }
+        let input = match (password, &self.key_file) {
+            (Some(password), _) => Zeroizing::new(format!("{password}\n")),
+            (None, Some(_)) => {
+                cmd.arg("--no-password");
+                Zeroizing::new(String::new())
+            }
+            (None, None) => return Err("KeePassXC database password required".into()),
+        };
+        cmd.arg("--").arg(&self.database);
+        let output = run_cli(
+            cmd,
+            CLI_TIMEOUT,
+            (!input.is_empty()).then_some(input.as_bytes()),
+        )?;
+
+        let text = Zeroizing::new(String::from_utf8_lossy(&output).into_owned());
+        let mut records = parse_csv(&text).into_iter();
+        let header = records.next().ok_or("keepassxc-cli exported nothing")?;
+        let column = |name: &str| header.iter().position(|h| h.as_str() == name);
+        let (Some(group), Some(title), Some(username), Some(password)) = (
+            column("Group"),
+            column("Title"),
+            column("Username"),
+            column("Password"),
+        ) else {
+            return Err("Unexpected keepassxc-cli export columns".into());
+        };
+        let url = column("URL");
+        let totp = column("TOTP");
+
+        let mut seen = std::collections::HashSet::new();
+        let mut items = Vec::new();
+        for record in records {
+            let field = |index: usize| record.get(index).map(|f| f.as_str()).unwrap_or_default();
+            // Entries have no id in the export; group path and title name them
+            let path = format!("{}/{}", field(group), field(title));
+            let mut raw_id = path.clone();
+            let mut n = 1;
+            while !seen.insert(raw_id.clone()) {
+                n += 1;
+                raw_id = format!("{path}#{n}");
+            }
+            items.push(VaultItemInput {
+                id: item_id(self.name(), &raw_id),
+                title: field(title).to_string(),
+                category: field(group).to_string(),
+                icon_b64: url
+                    .map(field)
+                    .filter(|u| !u.is_empty())
+                    .map(crate::commands::onepass::icon_for_url)
+                    .unwrap_or_default(),
+                account_id: String::new(),
+                source: self.label().into(),
+                username: field(username).to_string(),
+                password: field(password).to_string(),
+                totp: totp.map(field).unwrap_or_default().to_string(),
+                fields: Vec::new(),
+            });
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const FAKE_CLI: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/fake-password-cli.sh"
+    );
+
+    fn secrets(items: &[VaultItemInput]) -> Vec<(&str, &str, &str, &str)> {
+        items
+            .iter()
+            .map(|i| {
+                (
+                    i.id.as_str(),
+                    i.title.as_str(),
+                    i.username.as_str(),
+                    i.password.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bitwarden_unlocks_and_reads_items() {
+        let bw = Bitwarden::new(FAKE_CLI);
+        *BW_SESSION.lock().unwrap() = Some(Zeroizing::new("stale".into()));
+        assert_eq!(bw.master_password(), MasterPassword::Optional);
+        // The rejected session is dropped, so the next load asks for the password
+        let err = bw.load_items(None).err().unwrap();
+        assert!(err.contains("master password required"), "{err}");
+        assert_eq!(bw.master_password(), MasterPassword::Required);
+        let err = bw.load_items(Some("wrong")).err().unwrap();
+        assert!(err.contains("Invalid master password"), "{err}");
+
+        let items = bw.load_items(Some("bw-master")).unwrap();
+        assert_eq!(
+            secrets(&items),
+            vec![
+                ("bitwarden:bw-1", "Forum", "alice", "bw-secret"),
+                ("bitwarden:bw-2", "Door code", "", ""),
+            ]
+        );
+        assert_eq!(items[0].category, "Login");
+        assert_eq!(items[1].category, "Secure Note");
+        assert_eq!(items[0].source, "Bitwarden");
+        assert_eq!(items[0].totp, "GEZDGNBVGY3TQOJQ");
+        // Linked fields have no value of their own
+        assert_eq!(items[0].fields.len(), 1);
+        assert_eq!(
+            (
+                items[0].fields[0].label.as_str(),
+                items[0].fields[0].value.as_str()
+            ),
+            ("PIN", "4321")
+        );
+        assert!(items[1].totp.is_empty() && items[1].fields.is_empty());
+        // The unlocked session is kept for the next load
+        assert_eq!(bw.master_password(), MasterPassword::Optional);
+        assert_eq!(
+            BW_SESSION.lock().unwrap().as_deref().map(String::as_str),
+            Some("fake-bw-session")
+        );
+    }
+
+    #[test]
+    fn pass_walks_the_store_and_parses_entries() {
+        let store = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(store.path().join("email")).unwrap();
+        std::fs::create_dir_all(store.path().join(".git")).unwrap();
+        for file in ["email/gmail.com.gpg", "bank.gpg", ".gpg-id", ".git/x.gpg"] {
+            std::fs::write(store.path().join(file), "").unwrap();
+        }
+        let items = Pass::new(FAKE_CLI, store.path().to_path_buf())
+            .load_items(None)
+            .unwrap();
+        assert_eq!(
+            secrets(&items),
+            vec![
+                ("pass:bank", "bank", "", "bank-secret"),
+                (
+                    "pass:email/gmail.com",
+                    "email/gmail.com",
+                    "alice@gmail.com",
+                    "pass-secret"
+                ),
+            ]
+        );
+        assert_eq!(
+            items[1].totp,
+            "otpauth://totp/Gmail?secret=GEZDGNBVGY3TQOJQ"
+        );
+        assert_eq!(items[1].fields[0].label, "recovery");
+        assert_eq!(items[1].fields[0].value, "abc-def");
+        assert!(items[0].totp.is_empty() && items[0].fields.is_empty());
+
+        // One entry that fails to decrypt fails the load instead of being skipped
+        std::fs::write(store.path().join("locked.gpg"), "").unwrap();
+        let err = Pass::new(FAKE_CLI, store.path().to_path_buf())
+            .load_items(None)
+            .err()
+            .unwrap();
+        assert!(err.starts_with("locked: "), "{err}");
+
+        // The whole store shares one deadline
+        let pass = Pass::new(FAKE_CLI, store.path().to_path_buf());
+        let err = pass.item("bank", Instant::now()).err().unwrap();
+        assert!(err.contains("took longer than"), "{err}");
+    }
+
+    #[test]
+    fn gopass_lists_its_own_entries() {
+        let items = Pass::gopass(FAKE_CLI).load_items(None).unwrap();
+        let ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec!["gopass:email/gmail.com", "gopass:bank"]);
+        assert_eq!(items[0].source, "gopass");
+    }
+
+    #[test]
+    fn keepassxc_reads_the_csv_export() {
+        let kp = KeePassXc::new(FAKE_CLI, PathBuf::from("/tmp/test.kdbx"), None);
+        assert_eq!(kp.master_password(), MasterPassword::Required);
+        let items = kp.load_items(Some("kp-master")).unwrap();
+        assert_eq!(
+            secrets(&items),
+            vec![
+                ("keepassxc:Root/Work/VPN", "VPN", "jdoe", "kp-secret"),
+                ("keepassxc:Root/Router", "Router", "admin", "say \"hi\""),
+                ("keepassxc:Root/Router#2", "Router", "guest", "guest-secret"),
+            ]
+        );
+        assert_eq!(items[0].category, "Root/Work");
+        assert_eq!(items[0].totp, "otpauth://totp/VPN?secret=GEZDGNBVGY3TQOJQ");
+        assert!(items[1].totp.is_empty());
+
+        let err = kp.load_items(Some("wrong")).err().unwrap();
+        assert!(err.contains("Invalid credentials"), "{err}");
+        let err = kp.load_items(None).err().unwrap();
+        assert!(err.contains("password required"), "{err}");
+
+        let unset = KeePassXc::new(FAKE_CLI, PathBuf::new(), None);
+        let err = unset.load_items(Some("kp-master")).err().unwrap();
+        assert!(err.contains("keepassxc_database"), "{err}");
+    }
+
+    #[test]
+    fn keepassxc_key_file_alone_unlocks_without_a_password() {
+        let kp = KeePassXc::new(
+            FAKE_CLI,
+            PathBuf::from("/tmp/test.kdbx"),
+            Some(PathBuf::from("/tmp/test.keyx")),
+        );
+        assert_eq!(kp.master_password(), MasterPassword::Optional);
+        assert_eq!(kp.load_items(None).unwrap().len(), 3);
+        // A password given with the key file is still piped for composite keys
+        assert_eq!(kp.load_items(Some("kp-master")).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn parses_pass_entries() {
+        let entry = parse_pass_entry(
+            "pw: with colon\nURL: https://x.org\nuser: bob\nlogin: ignored\n\
+             otpauth://totp/x?secret=GEZA\ntotp: ignored\nPIN: 1234\nempty:\nnot a field",
+        );
+        assert_eq!(entry.password, "pw: with colon");
+        assert_eq!(entry.username, "bob");
+        assert_eq!(entry.url.as_deref(), Some("https://x.org"));
+        assert_eq!(entry.totp, "otpauth://totp/x?secret=GEZA");
+        let fields: Vec<(&str, &str)> = entry
+            .fields
+            .iter()
+            .map(|f| (f.label.as_str(), f.value.as_str()))
+            .collect();
+        assert_eq!(fields, vec![("PIN", "1234")]);
+
+        let empty = parse_pass_entry("");
+        assert!(empty.password.is_empty() && empty.username.is_empty() && empty.url.is_none());
+        assert!(empty.totp.is_empty() && empty.fields.is_empty());
+    }
+
+    #[test]
+    fn parses_quoted_csv() {
+        let records = parse_csv("a,\"b,c\",\"d\"\"e\"\r\n\"multi\nline\",,\n");
+        let plain: Vec<Vec<&str>> = records
+            .iter()
+            .map(|r| r.iter().map(|f| f.as_str()).collect())
+            .collect();
+        assert_eq!(
+            plain,
+            vec![vec!["a", "b,c", "d\"e"], vec!["multi\nline", "", ""]]
+        );
+    }
+
+    #[test]
+    fn failed_backends_are_reported_and_others_kept() {
+        let backends: Vec<Box<dyn PasswordBackend>> = vec![
+            Box::new(Pass::gopass("/nonexistent/burrow-test-gopass")),
+            Box::new(KeePassXc::new(FAKE_CLI, PathBuf::from("db.kdbx"), None)),
+        ];
+        let (items, failures) = collect_items(&backends, Some("kp-master"));
+        assert_eq!(items.len(), 3);
+        assert_eq!(failures.len(), 1);
+        assert!(
+            failures[0].starts_with("gopass: Failed to run"),
+            "{failures:?}"
+        );
+    }
+
+    #[test]
+    fn configured_backends_follow_the_config() {
+        let config = OnePassConfig {
+            backends: vec!["bitwarden".into(), "1password".into(), "nope".into()],
+            ..OnePassConfig::default()
+        };
+        let names: Vec<&str> = configured_backends(&config)
+            .iter()
+            .map(|b| b.name())
+            .collect();
+        assert_eq!(names, vec!["bitwarden", "1password"]);
+        assert_eq!(item_id("1password", "abc"), "abc");
+        assert_eq!(item_id("pass", "a/b"), "pass:a/b");
+    }
+}
@@ -25,6 +25,52 @@ const COMMANDS: &[SpecialCommand] = &[
         output_mode: None,
         output_format: None,
     },
+    SpecialCommand {
+        name: "summarize",
+        description: "Summarize the clipboard with AI (Shift: primary selection)",
+        icon: "",
+        // Sentinel exec: answered through clipboard_ai, never run as a shell command.
+        exec_command: "clipboard-ai:summarize",
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    },
+    SpecialCommand {
+        name: "translate",
+        description: "Translate the clipboard with AI (Shift: primary selection)",
+        icon: "",
+        exec_command: "clipboard-ai:translate",
+        input_spec: Some(("Target language (Enter for English)", "{}")),
+        output_mode: None,
+        output_format: None,
+    },
+    SpecialCommand {
+        name: "explain",
+        description: "Explain the clipboard with AI (Shift: primary selection)",
+        icon: "",
+        exec_command: "clipboard-ai:explain",
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    },
+    SpecialCommand {
+        name: "fix-grammar",
+        description: "Fix grammar and spelling of the clipboard (Shift: primary selection)",
+        icon: "",
+        exec_command: "clipboard-ai:fix-grammar",
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    },
+    SpecialCommand {
+        name: "to-command",
+        description: "Rewrite the clipboard as a shell command (Shift: primary selection)",
+        icon: "",
+        exec_command: "clipboard-ai:to-command",
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    },
     SpecialCommand {
         name: "cowork",
         description: "Open kitty in ~/cowork and run Codex",
@@ -70,6 +116,8 @@ fn command_to_result(cmd: &SpecialCommand) -> SearchResult {
         input_spec: cmd.input_spec.map(|(placeholder, template)| InputSpec {
             placeholder: placeholder.to_string(),
             template: template.to_string(),
+            optional: false,
+            secret: false,
         }),
         output_mode: cmd.output_mode,
         output_format: cmd.output_format.map(|s| s.to_string()),
@@ -1,121 +1,751 @@
-use crate::router::{Category, SearchResult};
+//! SSH hosts (`ssh ` prefix) from `~/.ssh/config` and, optionally, `~/.ssh/known_hosts`.
+//!
+//! The config is read the way `ssh` reads it: `Include` directives are
+//! followed (globs, relative to `~/.ssh`, nested up to 16 deep), `Host` and
+//! `Match` blocks apply to every host they match, and the first value given
+//! for an option wins. Every concrete `Host` alias is listed with its
+//! effective options.
+
+use crate::commands::history;
+use crate::context::AppContext;
+use crate::router::{Category, InputSpec, SearchResult};
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::Matcher;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+
+/// `Include` nesting limit, as in OpenSSH.
+const MAX_INCLUDE_DEPTH: usize = 16;
+/// Files read through `Include` per load, against runaway globs.
+const MAX_INCLUDED_FILES: usize = 256;
+/// Config and known_hosts files larger than this are skipped.
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_RESULTS: usize = 10;
+/// How much frecency can lift a host's match score: a host connected to
+/// often ranks up to twice as high as an unused one that matches as well.
+const FRECENCY_WEIGHT: f64 = 1.0;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct SshHost {
     pub name: String,
     pub hostname: String,
     pub user: String,
+    pub port: Option<u16>,
+    pub proxy_jump: Option<String>,
+    pub identity_files: Vec<String>,
+    /// Found in known_hosts rather than declared with `Host`
+    pub known_host: bool,
 }
 
-pub fn parse_ssh_config_content(content: &str) -> Vec<SshHost> {
-    let mut hosts = Vec::new();
-    let mut current_name = String::new();
-    let mut current_hostname = String::new();
-    let mut current_user = String::new();
+impl SshHost {
+    /// The destination argument for `ssh`. Aliases are passed as is so ssh
+    /// applies the config itself; known_hosts entries on another port
+    /// (`[host]:port`) become an `ssh://` URI.
+    pub fn destination(&self) -> String {
+        if self.known_host && self.name.starts_with('[') {
+            format!("ssh://{}", self.name)
+        } else {
+            self.name.clone()
+        }
+    }
 
-    let flush_host = |name: &str, hostname: &str, user: &str, hosts: &mut Vec<SshHost>| {
-        if name.is_empty() || name == "*" {
-            return;
+    /// The `sftp://` URI file managers open the host with. Aliases stay
+    /// aliases, as GVfs and KIO hand them to `ssh`, which applies the config.
+    pub fn sftp_uri(&self) -> String {
+        if self.known_host && !self.name.starts_with('[') && self.name.contains(':') {
+            // A bare IPv6 address
+            format!("sftp://[{}]/", self.name)
+        } else {
+            format!("sftp://{}/", self.name)
         }
-        for alias in name.split_whitespace() {
-            if alias == "*" || alias.contains('?') {
-                continue;
+    }
+
+    /// Refuse hosts behind `ProxyJump`: their `HostName` is resolved by the
+    /// jump host, so a local lookup would give the wrong address.
+    pub fn ensure_direct(&self) -> Result<(), String> {
+        match &self.proxy_jump {
+            Some(jump) => Err(format!(
+                "{} is reached through ProxyJump {jump}; its address is only known there",
+                self.name
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The host's IP address: `HostName` itself when it is one, otherwise
+    /// the first address it resolves to, IPv4 preferred. May block on DNS.
+    pub fn ip_address(&self) -> Result<IpAddr, String> {
+        self.ensure_direct()?;
+        let hostname = self.hostname.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = hostname.parse() {
+            return Ok(ip);
+        }
+        let addrs: Vec<IpAddr> = (hostname, self.port.unwrap_or(22))
+            .to_socket_addrs()
+            .map_err(|e| format!("Could not resolve {hostname}: {e}"))?
+            .map(|addr| addr.ip())
+            .collect();
+        addrs
+            .iter()
+            .find(|ip| ip.is_ipv4())
+            .or(addrs.first())
+            .copied()
+            .ok_or_else(|| format!("{hostname} has no addresses"))
+    }
+}
+
+/// A condition a config block applies under.
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    /// `Host` patterns, matched against the alias
+    Host(Vec<String>),
+    /// `Match` criteria, all of which must hold
+    Match(Vec<Criterion>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Criterion {
+    negated: bool,
+    kind: CriterionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CriterionKind {
+    All,
+    Host(Vec<String>),
+    OriginalHost(Vec<String>),
+    User(Vec<String>),
+    LocalUser(Vec<String>),
+    /// `exec`, `canonical`, `final`, `localnetwork`, `tagged` or unknown:
+    /// a launcher can't evaluate them, so the block never matches.
+    Unsupported,
+}
+
+/// Options in file order under the conditions they apply to. A block read
+/// through an `Include` inside a `Host`/`Match` block also carries the
+/// enclosing block's conditions.
+#[derive(Debug, Clone, Default)]
+struct Block {
+    conditions: Vec<Condition>,
+    /// (lowercase keyword, first argument)
+    options: Vec<(String, String)>,
+}
+
+/// Effective options for one host while evaluating blocks.
+#[derive(Debug, Default)]
+struct Options {
+    hostname: Option<String>,
+    user: Option<String>,
+    port: Option<u16>,
+    proxy_jump: Option<String>,
+    identity_files: Vec<String>,
+}
+
+impl Options {
+    /// First value wins, except `IdentityFile`, which accumulates.
+    fn set(&mut self, keyword: &str, value: &str, alias: &str) {
+        match keyword {
+            "hostname" if self.hostname.is_none() => {
+                self.hostname = Some(expand_tokens(value, alias));
             }
-            hosts.push(SshHost {
-                name: alias.to_string(),
-                hostname: if hostname.is_empty() {
-                    alias.to_string()
-                } else {
-                    hostname.to_string()
-                },
-                user: user.to_string(),
-            });
+            "user" if self.user.is_none() => self.user = Some(value.to_string()),
+            "port" if self.port.is_none() => self.port = value.parse().ok(),
+            "proxyjump" if self.proxy_jump.is_none() => self.proxy_jump = Some(value.to_string()),
+            "identityfile" => self.identity_files.push(value.to_string()),
+            _ => {}
         }
-    };
+    }
+}
+
+/// Expand the `%h` (alias) and `%%` tokens of a `HostName`.
+fn expand_tokens(value: &str, alias: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => out.push_str(alias),
+            Some('%') => out.push('%'),
+            Some(other) => {
+                out.push('%');
+                out.push(other);
+            }
+            None => out.push('%'),
+        }
+    }
+    out
+}
+
+/// `*`/`?` glob match, case-insensitive like ssh's host matching.
+fn pattern_matches(pattern: &str, text: &str) -> bool {
+    fn matches(p: &[char], t: &[char]) -> bool {
+        match p.split_first() {
+            None => t.is_empty(),
+            Some(('*', rest)) => (0..=t.len()).any(|i| matches(rest, &t[i..])),
+            Some(('?', rest)) => !t.is_empty() && matches(rest, &t[1..]),
+            Some((c, rest)) => t.first() == Some(c) && matches(rest, &t[1..]),
+        }
+    }
+    let p: Vec<char> = pattern.to_lowercase().chars().collect();
+    let t: Vec<char> = text.to_lowercase().chars().collect();
+    matches(&p, &t)
+}
+
+/// A pattern list matches if any pattern does and no `!pattern` does.
+fn pattern_list_matches<S: AsRef<str>>(patterns: &[S], text: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        match pattern.as_ref().strip_prefix('!') {
+            Some(negated) if pattern_matches(negated, text) => return false,
+            Some(_) => {}
+            None => matched |= pattern_matches(pattern.as_ref(), text),
+        }
+    }
+    matched
+}
+
+fn local_user() -> String {
+    std::env::var("USER").unwrap_or_default()
+}
+
+impl Condition {
+    fn matches(&self, alias: &str, options: &Options) -> bool {
+        match self {
+            Condition::Host(patterns) => pattern_list_matches(patterns, alias),
+            Condition::Match(criteria) => criteria.iter().all(|criterion| {
+                let holds = match &criterion.kind {
+                    CriterionKind::All => true,
+                    CriterionKind::Host(list) => {
+                        pattern_list_matches(list, options.hostname.as_deref().unwrap_or(alias))
+                    }
+                    CriterionKind::OriginalHost(list) => pattern_list_matches(list, alias),
+                    CriterionKind::User(list) => match &options.user {
+                        Some(user) => pattern_list_matches(list, user),
+                        None => pattern_list_matches(list, &local_user()),
+                    },
+                    CriterionKind::LocalUser(list) => pattern_list_matches(list, &local_user()),
+                    CriterionKind::Unsupported => return false,
+                };
+                holds != criterion.negated
+            }),
+        }
+    }
+}
+
+/// Parse the arguments of a `Match` line.
+fn parse_match(args: &[String]) -> Vec<Criterion> {
+    let mut criteria = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let lower = arg.to_lowercase();
+        let (negated, keyword) = match lower.strip_prefix('!') {
+            Some(keyword) => (true, keyword.to_string()),
+            None => (false, lower),
+        };
+        let mut list = || -> Vec<String> {
+            args.next()
+                .map(|list| list.split(',').map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+        let kind = match keyword.as_str() {
+            "all" => CriterionKind::All,
+            "host" => CriterionKind::Host(list()),
+            "originalhost" => CriterionKind::OriginalHost(list()),
+            "user" => CriterionKind::User(list()),
+            "localuser" => CriterionKind::LocalUser(list()),
+            "canonical" | "final" => CriterionKind::Unsupported,
+            _ => {
+                // exec, localnetwork, tagged and unknown criteria take an argument
+                list();
+                CriterionKind::Unsupported
+            }
+        };
+        criteria.push(Criterion { negated, kind });
+    }
+    criteria
+}
+
+/// Split a config line into its lowercase keyword and arguments.
+/// Keywords are separated by whitespace or `=`; arguments may be double-quoted.
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(line.len());
+    let keyword = line[..end].to_lowercase();
+    let rest = line[end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
 
-    // Try to strip a keyword prefix (space or tab delimited)
-    let strip_key = |line: &str, keys: &[&str]| -> Option<String> {
-        for key in keys {
-            for sep in [" ", "\t"] {
-                let prefix = format!("{key}{sep}");
-                if let Some(val) = line.strip_prefix(&prefix) {
-                    return Some(val.trim().to_string());
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    for c in rest.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
                 }
             }
+            c => {
+                current.push(c);
+                in_word = true;
+            }
         }
-        None
-    };
+    }
+    if in_word {
+        args.push(current);
+    }
+    Some((keyword, args))
+}
 
-    for line in content.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+/// Reads config files into blocks, following `Include`.
+struct Loader {
+    /// Base directory for relative `Include` paths; `None` ignores `Include`
+    ssh_dir: Option<PathBuf>,
+    home: Option<PathBuf>,
+    included: usize,
+    /// Files being read, to stop include cycles
+    stack: Vec<PathBuf>,
+}
+
+impl Loader {
+    fn new(ssh_dir: Option<PathBuf>, home: Option<PathBuf>) -> Self {
+        Self {
+            ssh_dir,
+            home,
+            included: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    fn read(&mut self, content: &str, outer: &[Condition], depth: usize, blocks: &mut Vec<Block>) {
+        let mut block = Block {
+            conditions: outer.to_vec(),
+            options: Vec::new(),
+        };
+        let start_block = |condition: Condition| Block {
+            conditions: outer.iter().cloned().chain([condition]).collect(),
+            options: Vec::new(),
+        };
+        for line in content.lines() {
+            let Some((keyword, args)) = split_line(line) else {
+                continue;
+            };
+            match keyword.as_str() {
+                "host" => {
+                    blocks.push(std::mem::replace(
+                        &mut block,
+                        start_block(Condition::Host(args)),
+                    ));
+                }
+                "match" => {
+                    let criteria = parse_match(&args);
+                    blocks.push(std::mem::replace(
+                        &mut block,
+                        start_block(Condition::Match(criteria)),
+                    ));
+                }
+                "include" => {
+                    // Included files apply under the current block's conditions,
+                    // and the block carries on after them
+                    let conditions = block.conditions.clone();
+                    blocks.push(std::mem::replace(
+                        &mut block,
+                        Block {
+                            conditions: conditions.clone(),
+                            options: Vec::new(),
+                        },
+                    ));
+                    for path in self.include_paths(&args) {
+                        self.read_file(&path, &conditions, depth + 1, blocks);
+                    }
+                }
+                _ => {
+                    if let Some(value) = args.into_iter().next() {
+                        block.options.push((keyword, value));
+                    }
+                }
+            }
+        }
+        blocks.push(block);
+    }
+
+    fn read_file(
+        &mut self,
+        path: &Path,
+        outer: &[Condition],
+        depth: usize,
+        blocks: &mut Vec<Block>,
+    ) {
+        if depth > MAX_INCLUDE_DEPTH {
+            tracing::warn!(path = %path.display(), "ssh config includes nested too deeply, skipping");
+            return;
+        }
+        if depth > 0 {
+            if self.included >= MAX_INCLUDED_FILES {
+                tracing::warn!(path = %path.display(), "too many included ssh config files, skipping");
+                return;
+            }
+            self.included += 1;
+        }
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.stack.contains(&canonical) {
+            tracing::warn!(path = %path.display(), "ssh config includes itself, skipping");
+            return;
+        }
+        let Some(content) = read_limited(path) else {
+            return;
+        };
+        self.stack.push(canonical);
+        self.read(&content, outer, depth, blocks);
+        self.stack.pop();
+    }
+
+    /// Files named by an `Include` line: `~` expanded, relative to `~/.ssh`,
+    /// globs sorted like ssh's.
+    fn include_paths(&self, args: &[String]) -> Vec<PathBuf> {
+        let Some(ssh_dir) = &self.ssh_dir else {
+            return Vec::new();
+        };
+        let mut paths = Vec::new();
+        for arg in args {
+            let pattern = match (arg.strip_prefix("~/"), &self.home) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ if Path::new(arg).is_absolute() => PathBuf::from(arg),
+                _ => ssh_dir.join(arg),
+            };
+            match glob::glob(&pattern.to_string_lossy()) {
+                Ok(matches) => paths.extend(matches.flatten().filter(|p| p.is_file())),
+                Err(e) => tracing::warn!(pattern = %arg, error = %e, "invalid ssh config Include"),
+            }
         }
+        paths
+    }
+}
+
+fn read_limited(path: &Path) -> Option<String> {
+    let meta = fs::metadata(path).ok()?;
+    if meta.len() > MAX_FILE_BYTES {
+        tracing::warn!(path = %path.display(), "ssh file too large, skipping");
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
 
-        if let Some(host) = strip_key(line, &["Host"]) {
-            flush_host(&current_name, &current_hostname, &current_user, &mut hosts);
-            current_name = host;
-            current_hostname.clear();
-            current_user.clear();
-        } else if let Some(val) = strip_key(line, &["HostName", "Hostname"]) {
-            current_hostname = val;
-        } else if let Some(val) = strip_key(line, &["User"]) {
-            current_user = val;
+/// Options that apply to `alias`, evaluating blocks in order.
+fn effective_options(blocks: &[Block], alias: &str) -> Options {
+    let mut options = Options::default();
+    for block in blocks {
+        if block
+            .conditions
+            .iter()
+            .all(|condition| condition.matches(alias, &options))
+        {
+            for (keyword, value) in &block.options {
+                options.set(keyword, value, alias);
+            }
         }
     }
+    options
+}
 
-    flush_host(&current_name, &current_hostname, &current_user, &mut hosts);
+fn to_host(name: String, alias: &str, options: Options, known_host: bool) -> SshHost {
+    SshHost {
+        hostname: options.hostname.unwrap_or_else(|| alias.to_string()),
+        user: options.user.unwrap_or_default(),
+        port: options.port,
+        proxy_jump: options.proxy_jump.filter(|jump| jump != "none"),
+        identity_files: options.identity_files,
+        known_host,
+        name,
+    }
+}
+
+/// Every concrete `Host` alias (no wildcards or negation), in config order.
+fn hosts_from_blocks(blocks: &[Block]) -> Vec<SshHost> {
+    let mut seen = HashSet::new();
+    let mut hosts = Vec::new();
+    for block in blocks {
+        let Some(Condition::Host(patterns)) = block.conditions.last() else {
+            continue;
+        };
+        for alias in patterns {
+            if alias.contains(['*', '?', '!']) || !seen.insert(alias.clone()) {
+                continue;
+            }
+            let options = effective_options(blocks, alias);
+            // Hosts from an Include under a block that doesn't match never apply
+            if block
+                .conditions
+                .iter()
+                .all(|condition| condition.matches(alias, &options))
+            {
+                hosts.push(to_host(alias.clone(), alias, options, false));
+            }
+        }
+    }
     hosts
 }
 
-fn parse_ssh_config() -> Vec<SshHost> {
-    let config_path = dirs::home_dir()
-        .map(|h| h.join(".ssh/config"))
-        .unwrap_or_default();
+pub fn parse_ssh_config_content(content: &str) -> Vec<SshHost> {
+    let mut blocks = Vec::new();
+    Loader::new(None, None).read(content, &[], 0, &mut blocks);
+    hosts_from_blocks(&blocks)
+}
+
+/// Hosts named in a known_hosts file with their port (`[host]:port`
+/// entries). Only the first name of each line is taken, since ssh writes
+/// the host name before its address; hashed names, patterns and
+/// `@cert-authority`/`@revoked` lines are skipped.
+pub fn parse_known_hosts(content: &str) -> Vec<(String, Option<u16>)> {
+    let mut seen = HashSet::new();
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+                return None;
+            }
+            let name = line.split_whitespace().next()?.split(',').next()?;
+            if name.starts_with('|') || name.contains(['*', '?', '!']) {
+                return None;
+            }
+            let entry = match name.strip_prefix('[').and_then(|n| n.split_once("]:")) {
+                Some((host, port)) => (host.to_string(), Some(port.parse::<u16>().ok()?)),
+                None => (name.to_string(), None),
+            };
+            seen.insert(entry.clone()).then_some(entry)
+        })
+        .collect()
+}
 
-    match fs::read_to_string(&config_path) {
-        Ok(content) => parse_ssh_config_content(&content),
-        Err(_) => vec![],
+/// Add known_hosts entries not already covered by a config alias or its
+/// `HostName`. Config blocks still apply to them, as they would in ssh.
+fn add_known_hosts(hosts: &mut Vec<SshHost>, blocks: &[Block], known: &[(String, Option<u16>)]) {
+    for (host, port) in known {
+        let port = port.filter(|&p| p != 22);
+        let covered = hosts.iter().any(|h| {
+            (h.name.eq_ignore_ascii_case(host) || h.hostname.eq_ignore_ascii_case(host))
+                && h.port.filter(|&p| p != 22) == port
+        });
+        if covered {
+            continue;
+        }
+        let mut options = effective_options(blocks, host);
+        let name = match port {
+            Some(port) => {
+                options.port = Some(port);
+                format!("[{host}]:{port}")
+            }
+            None => host.clone(),
+        };
+        hosts.push(to_host(name, host, options, true));
+    }
+}
+
+/// Hosts from `<home>/.ssh/config` and its includes, plus
+/// `<home>/.ssh/known_hosts` with `known_hosts`.
+fn load_hosts(home: &Path, known_hosts: bool) -> Vec<SshHost> {
+    let ssh_dir = home.join(".ssh");
+    let mut loader = Loader::new(Some(ssh_dir.clone()), Some(home.to_path_buf()));
+    let mut blocks = Vec::new();
+    loader.read_file(&ssh_dir.join("config"), &[], 0, &mut blocks);
+    let mut hosts = hosts_from_blocks(&blocks);
+    if known_hosts {
+        if let Some(content) = read_limited(&ssh_dir.join("known_hosts")) {
+            add_known_hosts(&mut hosts, &blocks, &parse_known_hosts(&content));
+        }
     }
+    hosts
+}
+
+fn parse_ssh_config() -> Vec<SshHost> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let known_hosts = crate::config::try_get_config().is_some_and(|c| c.ssh.known_hosts);
+    load_hosts(&home, known_hosts)
 }
 
 pub fn filter_hosts(hosts: Vec<SshHost>, query: &str) -> Vec<SearchResult> {
-    let query_lower = query.to_lowercase();
+    rank_hosts(hosts, query, &HashMap::new())
+}
 
-    hosts
+/// Fuzzy-match `query` against each host's alias and `HostName`, scaling
+/// match scores by the host's connection frecency (`result id → score`).
+/// An empty query lists recently used hosts first, then the rest in config
+/// order.
+pub fn rank_hosts(
+    hosts: Vec<SshHost>,
+    query: &str,
+    frecency: &HashMap<String, f64>,
+) -> Vec<SearchResult> {
+    let mut matcher = Matcher::new(nucleo::Config::DEFAULT);
+    let pattern = Pattern::new(
+        query,
+        CaseMatching::Ignore,
+        Normalization::Smart,
+        nucleo::pattern::AtomKind::Fuzzy,
+    );
+    let mut buf = Vec::new();
+    let mut scored: Vec<(f64, SshHost)> = hosts
         .into_iter()
-        .filter(|h| {
-            query.is_empty()
-                || h.name.to_lowercase().contains(&query_lower)
-                || h.hostname.to_lowercase().contains(&query_lower)
-        })
-        .take(10)
-        .map(|h| {
-            let user_prefix = if h.user.is_empty() {
-                String::new()
-            } else {
-                format!("{}@", h.user)
-            };
-            SearchResult {
-                id: format!("ssh-{}", h.name),
-                name: h.name.clone(),
-                description: format!("{}{}", user_prefix, h.hostname),
-                icon: "".into(),
-                category: Category::Ssh,
-                // Store host alias only; handler uses safe Command args (no shell interpolation)
-                exec: h.name.clone(),
-                input_spec: None,
-                output_mode: None,
-                output_format: None,
+        .filter_map(|host| {
+            let used = frecency
+                .get(&result_id(&host.name))
+                .copied()
+                .unwrap_or(0.0)
+                .max(0.0);
+            if query.is_empty() {
+                return Some((used, host));
             }
+            let score = [host.name.as_str(), host.hostname.as_str()]
+                .into_iter()
+                .filter_map(|text| {
+                    pattern.score(nucleo::Utf32Str::new(text, &mut buf), &mut matcher)
+                })
+                .max()?;
+            let boost = 1.0 + FRECENCY_WEIGHT * used / (used + 1.0);
+            Some((f64::from(score) * boost, host))
         })
+        .collect();
+    // Stable, so ties keep config order
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    scored
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, host)| host_to_result(host))
         .collect()
 }
 
-pub fn search_ssh(query: &str) -> Result<Vec<SearchResult>, String> {
+fn result_id(alias: &str) -> String {
+    format!("ssh-{alias}")
+}
+
+/// `user@hostname:port via jump`, leaving out what isn't set.
+fn describe(h: &SshHost) -> String {
+    let mut description = String::new();
+    if !h.user.is_empty() {
+        description.push_str(&h.user);
+        description.push('@');
+    }
+    description.push_str(&h.hostname);
+    if let Some(port) = h.port.filter(|&p| p != 22) {
+        description.push_str(&format!(":{port}"));
+    }
+    if let Some(jump) = &h.proxy_jump {
+        description.push_str(&format!(" via {jump}"));
+    }
+    description
+}
+
+/// Secondary input on a host runs a one-off command over a non-interactive
+/// session (`-L …` input starts a port forward instead, see the SSH handler).
+fn command_input(destination: &str) -> Option<InputSpec> {
+    let destination = shlex::try_quote(destination).ok()?;
+    Some(InputSpec {
+        placeholder: "Command to run, or -L port[:host:hostport] to forward a port".into(),
+        template: format!("ssh -o BatchMode=yes -T -- {destination} {{}}"),
+        optional: true,
+        secret: false,
+    })
+}
+
+fn host_to_result(h: SshHost) -> SearchResult {
+    let exec = h.destination();
+    SearchResult {
+        id: result_id(&h.name),
+        description: describe(&h),
+        icon: "".into(),
+        category: Category::Ssh,
+        // Store the ssh destination only; handler uses safe Command args (no shell interpolation)
+        input_spec: command_input(&exec),
+        exec,
+        name: h.name,
+        output_mode: None,
+        output_format: None,
+    }
+}
+
+/// The result for the host with result id `id` (`ssh-<alias>`), if it is in `hosts`.
+pub fn find_host(hosts: Vec<SshHost>, id: &str) -> Option<SearchResult> {
+    find_ssh_host(hosts, id).map(host_to_result)
+}
+
+fn find_ssh_host(hosts: Vec<SshHost>, id: &str) -> Option<SshHost> {
+    let alias = id.strip_prefix("ssh-")?;
+    hosts.into_iter().find(|h| h.name == alias)
+}
+
+/// Results for every configured or known host keyed by result id, for
+/// resolving many ids against one parse of the config.
+pub fn resolve_all_ssh() -> HashMap<String, SearchResult> {
+    results_by_id(parse_ssh_config())
+}
+
+/// The first host with a name wins, as in [`find_host`].
+fn results_by_id(hosts: Vec<SshHost>) -> HashMap<String, SearchResult> {
+    let mut results = HashMap::new();
+    for host in hosts {
+        results
+            .entry(result_id(&host.name))
+            .or_insert_with(|| host_to_result(host));
+    }
+    results
+}
+
+/// Rebuild an SSH result from `~/.ssh/config` (and known_hosts, if enabled)
+/// by id, so a stored or client-supplied result can only connect to a
+/// configured or known host. The host is returned alongside for actions
+/// that need more than the result carries.
+pub fn resolve_ssh_by_id(id: &str) -> Option<(SearchResult, SshHost)> {
+    let host = find_ssh_host(parse_ssh_config(), id)?;
+    Some((host_to_result(host.clone()), host))
+}
+
+pub fn search_ssh(query: &str, ctx: &AppContext) -> Result<Vec<SearchResult>, String> {
     let hosts = parse_ssh_config();
-    Ok(filter_hosts(hosts, query))
+    let frecency = history::get_category_scores(&[Category::Ssh], ctx).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "failed to read SSH frecency");
+        HashMap::new()
+    });
+    Ok(rank_hosts(hosts, query, &frecency))
+}
+
+/// Turn the text after `-L` into an `ssh -L` forward spec. A lone port
+/// forwards that port to the same port on the remote's localhost; anything
+/// else is passed on as `[bind:]port:host:hostport` or a socket path.
+pub fn forward_spec(input: &str) -> Result<String, String> {
+    let spec = input.trim();
+    if spec.is_empty() {
+        return Err("Port forward needs a port, e.g. -L 8080".into());
+    }
+    if let Ok(port) = spec.parse::<u16>() {
+        if port == 0 {
+            return Err("Port forward needs a port, e.g. -L 8080".into());
+        }
+        return Ok(format!("{port}:localhost:{port}"));
+    }
+    let allowed = |c: char| c.is_ascii_alphanumeric() || ".-_:[]/*".contains(c);
+    if spec.starts_with('-') || !spec.chars().all(allowed) {
+        return Err(format!("Invalid port forward: {spec}"));
+    }
+    Ok(spec.to_string())
 }
 
 #[cfg(test)]
@@ -289,6 +919,94 @@ Host *
         assert_eq!(results.len(), 10);
     }
 
+    #[test]
+    fn ranks_fuzzy_matches_by_frecency() {
+        let hosts = parse_ssh_config_content(
+            "Host prod-web\nHost prod-db\nHost staging\n    HostName stage.example.com\n",
+        );
+        let ids = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.id).collect()
+        };
+
+        // Fuzzy on alias and HostName; equal matches keep config order
+        assert_eq!(
+            ids(rank_hosts(hosts.clone(), "prod", &HashMap::new())),
+            vec!["ssh-prod-web", "ssh-prod-db"]
+        );
+        assert_eq!(
+            ids(rank_hosts(hosts.clone(), "stgexample", &HashMap::new())),
+            vec!["ssh-staging"]
+        );
+
+        let frecency = HashMap::from([("ssh-prod-db".to_string(), 4.0)]);
+        assert_eq!(
+            ids(rank_hosts(hosts.clone(), "prod", &frecency)),
+            vec!["ssh-prod-db", "ssh-prod-web"]
+        );
+        // No query: used hosts first, then config order
+        assert_eq!(
+            ids(rank_hosts(hosts, "", &frecency)),
+            vec!["ssh-prod-db", "ssh-prod-web", "ssh-staging"]
+        );
+    }
+
+    #[test]
+    fn secondary_input_runs_a_quoted_one_off_command() {
+        let hosts = parse_ssh_config_content("Host evil$(whoami)\n    HostName 127.0.0.1\n");
+        let results = filter_hosts(hosts, "evil");
+        let spec = results[0].input_spec.as_ref().unwrap();
+        assert_eq!(
+            spec.template,
+            "ssh -o BatchMode=yes -T -- 'evil$(whoami)' {}"
+        );
+    }
+
+    #[test]
+    fn sftp_uris_and_literal_addresses() {
+        let hosts = parse_ssh_config_content(SAMPLE_CONFIG);
+        assert_eq!(hosts[0].sftp_uri(), "sftp://server1/");
+        assert_eq!(hosts[0].ip_address().unwrap().to_string(), "192.168.1.10");
+
+        let known = |name: &str, hostname: &str| SshHost {
+            name: name.into(),
+            hostname: hostname.into(),
+            user: String::new(),
+            port: None,
+            proxy_jump: None,
+            identity_files: Vec::new(),
+            known_host: true,
+        };
+        assert_eq!(
+            known("[git.example.com]:2222", "git.example.com").sftp_uri(),
+            "sftp://[git.example.com]:2222/"
+        );
+        let v6 = known("fe80::1", "fe80::1");
+        assert_eq!(v6.sftp_uri(), "sftp://[fe80::1]/");
+        assert_eq!(v6.ip_address().unwrap().to_string(), "fe80::1");
+
+        let jumped = SshHost {
+            proxy_jump: Some("bastion".into()),
+            ..known("inner", "10.0.0.5")
+        };
+        let err = jumped.ip_address().unwrap_err();
+        assert!(err.contains("ProxyJump bastion"), "{err}");
+    }
+
+    #[test]
+    fn forward_specs() {
+        assert_eq!(forward_spec(" 8080").unwrap(), "8080:localhost:8080");
+        assert_eq!(forward_spec("5433:db:5432").unwrap(), "5433:db:5432");
+        assert_eq!(
+            forward_spec("127.0.0.1:8080:[::1]:80").unwrap(),
+            "127.0.0.1:8080:[::1]:80"
+        );
+        assert!(forward_spec("").is_err());
+        assert!(forward_spec("0").is_err());
+        assert!(forward_spec("-oProxyCommand=sh").is_err());
+        assert!(forward_spec("8080:db:5432 -R 1:x:1").is_err());
+        assert!(forward_spec("8080:$(id):80").is_err());
+    }
+
     #[test]
     fn malicious_hostname_stored_as_literal() {
         // Verify that shell metacharacters are stored literally, not escaped for shell execution
@@ -310,4 +1028,245 @@ Host *
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].exec, "evil`id`");
     }
+
+    #[test]
+    fn find_host_rebuilds_result_from_config() {
+        let host = find_host(parse_ssh_config_content(SAMPLE_CONFIG), "ssh-server2").unwrap();
+        assert_eq!(host.exec, "server2");
+        assert_eq!(host.description, "deploy@example.com");
+        assert!(find_host(parse_ssh_config_content(SAMPLE_CONFIG), "ssh-gone").is_none());
+        assert!(find_host(parse_ssh_config_content(SAMPLE_CONFIG), "server1").is_none());
+
+        let by_id = results_by_id(parse_ssh_config_content(SAMPLE_CONFIG));
+        assert_eq!(by_id["ssh-server2"], host);
+        assert!(!by_id.contains_key("ssh-gone"));
+    }
+
+    #[test]
+    fn options_come_from_every_matching_block_first_value_wins() {
+        let config = "\
+Host web
+    HostName %h.example.com
+    Port 2222
+
+Host *.internal db
+    User deploy
+    ProxyJump bastion
+    Port 22
+
+Host db
+    User ignored
+    HostName=10.0.0.9
+
+Host *
+    User fallback
+    IdentityFile ~/.ssh/id_ed25519
+";
+        let hosts = parse_ssh_config_content(config);
+        let names: Vec<&str> = hosts.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["web", "db"]);
+
+        assert_eq!(hosts[0].hostname, "web.example.com");
+        assert_eq!(hosts[0].port, Some(2222));
+        assert_eq!(hosts[0].user, "fallback");
+        assert_eq!(hosts[0].proxy_jump, None);
+        assert_eq!(hosts[0].identity_files, vec!["~/.ssh/id_ed25519"]);
+
+        assert_eq!(hosts[1].user, "deploy");
+        assert_eq!(hosts[1].hostname, "10.0.0.9");
+        assert_eq!(hosts[1].proxy_jump.as_deref(), Some("bastion"));
+        assert_eq!(hosts[1].port, Some(22));
+    }
+
+    #[test]
+    fn description_shows_port_and_jump_host() {
+        let config = "\
+Host db
+    HostName 10.0.0.9
+    User deploy
+    Port 2222
+    ProxyJump admin@bastion
+Host plain
+    Port 22
+";
+        let results = filter_hosts(parse_ssh_config_content(config), "");
+        assert_eq!(
+            results[0].description,
+            "deploy@10.0.0.9:2222 via admin@bastion"
+        );
+        assert_eq!(results[0].exec, "db");
+        assert_eq!(results[1].description, "plain");
+    }
+
+    #[test]
+    fn negated_patterns_and_match_blocks() {
+        let config = "\
+Host prod-* !prod-legacy
+    User ops
+Match originalhost prod-legacy
+    User legacy
+Match host *.corp.example !user root
+    ProxyJump gateway
+Match exec \"true\"
+    User never
+Host prod-api prod-legacy
+    HostName %h.corp.example
+";
+        let hosts = parse_ssh_config_content(config);
+        assert_eq!(hosts[0].name, "prod-api");
+        assert_eq!(hosts[0].user, "ops");
+        // `Match host` sees the HostName only once a block has set it
+        assert_eq!(hosts[0].proxy_jump, None);
+        assert_eq!(hosts[1].user, "legacy");
+
+        let config = "\
+Host api
+    HostName api.corp.example
+Match host *.corp.example
+    ProxyJump gateway
+Match all
+    Port 2200
+";
+        let hosts = parse_ssh_config_content(config);
+        assert_eq!(hosts[0].proxy_jump.as_deref(), Some("gateway"));
+        assert_eq!(hosts[0].port, Some(2200));
+    }
+
+    #[test]
+    fn patterns_match_like_ssh() {
+        assert!(pattern_matches("*.example.com", "a.EXAMPLE.com"));
+        assert!(pattern_matches("web-??", "web-01"));
+        assert!(!pattern_matches("web-??", "web-1"));
+        assert!(pattern_list_matches(&["*", "!db"], "web"));
+        assert!(!pattern_list_matches(&["*", "!db"], "db"));
+        assert!(!pattern_list_matches(&["!db"], "web"));
+    }
+
+    fn home_with(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let home = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            let path = home.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        home
+    }
+
+    #[test]
+    fn follows_include_globs_in_order() {
+        let home = home_with(&[
+            (
+                ".ssh/config",
+                "Include conf.d/*.conf ~/extra/ssh.conf\nHost main\n    User me\n",
+            ),
+            (".ssh/conf.d/b.conf", "Host beta\n"),
+            (
+                ".ssh/conf.d/a.conf",
+                "Include nested\nHost alpha\n    Port 2200\n",
+            ),
+            (".ssh/conf.d/skip.txt", "Host skipped\n"),
+            (".ssh/nested", "Host nested\n"),
+            ("extra/ssh.conf", "Host extra\n"),
+        ]);
+        let hosts = load_hosts(home.path(), false);
+        let names: Vec<&str> = hosts.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["nested", "alpha", "beta", "extra", "main"]);
+        assert_eq!(hosts[1].port, Some(2200));
+    }
+
+    #[test]
+    fn include_inside_a_block_is_conditional() {
+        let home = home_with(&[
+            (
+                ".ssh/config",
+                "Host work-*\n    Include work.conf\n    Port 2200\nHost work-db home\n",
+            ),
+            (".ssh/work.conf", "User employee\n"),
+        ]);
+        let hosts = load_hosts(home.path(), false);
+        assert_eq!(hosts[0].name, "work-db");
+        assert_eq!(hosts[0].user, "employee");
+        assert_eq!(hosts[0].port, Some(2200));
+        assert_eq!(hosts[1].user, "");
+        assert_eq!(hosts[1].port, None);
+    }
+
+    #[test]
+    fn include_cycles_and_depth_are_bounded() {
+        let home = home_with(&[
+            (".ssh/config", "Include loop\nHost top\n"),
+            (".ssh/loop", "Host looped\nInclude config\nInclude loop\n"),
+        ]);
+        let hosts = load_hosts(home.path(), false);
+        let names: Vec<&str> = hosts.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["looped", "top"]);
+
+        // A chain deeper than the limit stops instead of recursing forever
+        let mut files: Vec<(String, String)> = (0..MAX_INCLUDE_DEPTH + 5)
+            .map(|i| {
+                (
+                    format!(".ssh/d{i}"),
+                    format!("Include d{}\nHost h{i}\n", i + 1),
+                )
+            })
+            .collect();
+        files.push((".ssh/config".into(), "Include d0\n".into()));
+        let refs: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(p, c)| (p.as_str(), c.as_str()))
+            .collect();
+        let home = home_with(&refs);
+        assert_eq!(load_hosts(home.path(), false).len(), MAX_INCLUDE_DEPTH);
+    }
+
+    #[test]
+    fn parses_unhashed_known_hosts() {
+        let known = "\
+github.com,140.82.121.4 ssh-ed25519 AAAA
+|1|JfKTdBh7rNbXkVAQCRp4OQoPfmI=|USECr3SWf1JUPsms5AqfD5QfxkM= ssh-rsa AAAA
+[db.example.com]:2222 ssh-ed25519 AAAA
+[ssh.example.com]:22 ssh-ed25519 AAAA
+@cert-authority *.example.com ssh-rsa AAAA
+*.wild ssh-rsa AAAA
+github.com ssh-rsa AAAA
+# comment
+";
+        assert_eq!(
+            parse_known_hosts(known),
+            vec![
+                ("github.com".to_string(), None),
+                ("db.example.com".to_string(), Some(2222)),
+                ("ssh.example.com".to_string(), Some(22)),
+            ]
+        );
+    }
+
+    #[test]
+    fn known_hosts_are_added_when_enabled() {
+        let home = home_with(&[
+            (
+                ".ssh/config",
+                "Host gh\n    HostName github.com\nHost *.example.com\n    User deploy\n",
+            ),
+            (
+                ".ssh/known_hosts",
+                "github.com ssh-ed25519 AAAA\n[db.example.com]:2222 ssh-ed25519 AAAA\n\
+                 gitlab.com ssh-ed25519 AAAA\n",
+            ),
+        ]);
+        assert_eq!(load_hosts(home.path(), false).len(), 1);
+
+        let hosts = load_hosts(home.path(), true);
+        let names: Vec<&str> = hosts.iter().map(|h| h.name.as_str()).collect();
+        // github.com is already reachable as `gh`
+        assert_eq!(names, vec!["gh", "[db.example.com]:2222", "gitlab.com"]);
+        let db = &hosts[1];
+        assert!(db.known_host);
+        assert_eq!(db.user, "deploy");
+        assert_eq!(db.destination(), "ssh://[db.example.com]:2222");
+        assert_eq!(hosts[2].destination(), "gitlab.com");
+
+        let result = find_host(hosts, "ssh-[db.example.com]:2222").unwrap();
+        assert_eq!(result.description, "deploy@db.example.com:2222");
+    }
 }
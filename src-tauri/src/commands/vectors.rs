@@ -1,3 +1,4 @@
+use super::migrations::Migration;
 use crate::context::AppContext;
 use crate::ollama;
 use crate::router::{Category, SearchResult};
@@ -40,21 +41,31 @@ pub fn open_vector_db() -> Result<Connection, rusqlite::Error> {
     Ok(conn)
 }
 
+/// Vector schema steps. The first tolerates tables from before versioning.
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "vectors table",
+    destructive: false,
+    apply: |conn| {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS vectors (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                file_path TEXT NOT NULL UNIQUE,
+                content_preview TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                dimension INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                indexed_at REAL NOT NULL,
+                file_mtime REAL NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_vectors_path ON vectors(file_path);
+            CREATE INDEX IF NOT EXISTS idx_vectors_mtime ON vectors(file_mtime);",
+        )
+    },
+}];
+
 fn create_vector_table(conn: &Connection) -> Result<(), rusqlite::Error> {
-    conn.execute_batch(
-        "CREATE TABLE IF NOT EXISTS vectors (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            file_path TEXT NOT NULL UNIQUE,
-            content_preview TEXT NOT NULL,
-            embedding BLOB NOT NULL,
-            dimension INTEGER NOT NULL,
-            model TEXT NOT NULL,
-            indexed_at REAL NOT NULL,
-            file_mtime REAL NOT NULL
-        );
-        CREATE INDEX IF NOT EXISTS idx_vectors_path ON vectors(file_path);
-        CREATE INDEX IF NOT EXISTS idx_vectors_mtime ON vectors(file_mtime);",
-    )
+    super::migrations::run(conn, "vectors", MIGRATIONS)
 }
 
 pub fn init_vector_db(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
@@ -204,6 +215,32 @@ mod tests {
         assert!(create_vector_table(&conn).is_ok());
     }
 
+    #[test]
+    fn upgrades_unversioned_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE vectors (
+                id INTEGER PRIMARY KEY AUTOINCREMENT, file_path TEXT NOT NULL UNIQUE,
+                content_preview TEXT NOT NULL, embedding BLOB NOT NULL,
+                dimension INTEGER NOT NULL, model TEXT NOT NULL,
+                indexed_at REAL NOT NULL, file_mtime REAL NOT NULL
+            );
+            CREATE INDEX idx_vectors_path ON vectors(file_path);
+            CREATE INDEX idx_vectors_mtime ON vectors(file_mtime);",
+        )
+        .unwrap();
+        insert_vector(&conn, "/home/user/a.txt", "a", &[1.0, 0.0], "m", 1.0).unwrap();
+
+        create_vector_table(&conn).unwrap();
+
+        assert_eq!(
+            crate::commands::migrations::schema_version(&conn).unwrap(),
+            1
+        );
+        let results = search_vectors(&conn, &[1.0, 0.0], 10, 0.0).unwrap();
+        assert_eq!(results[0].id, "/home/user/a.txt");
+    }
+
     #[test]
     fn insert_and_search() {
         let conn = test_db();
@@ -0,0 +1,246 @@
+//! Window switcher (`w ` prefix): fuzzy-find open compositor windows by title
+//! and app id, then focus, move or close them.
+
+use crate::actions::modifier::Modifier;
+use crate::context::AppContext;
+use crate::icons;
+use crate::router::{Category, SearchResult};
+use crate::wm::{self, Window, WindowManager};
+use nucleo::pattern::{CaseMatching, Normalization, Pattern};
+use nucleo::Matcher;
+
+const MAX_RESULTS: usize = 20;
+
+/// What Enter does to the selected window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAction {
+    Focus,
+    MoveHere,
+    Close,
+}
+
+impl WindowAction {
+    pub fn from_modifier(modifier: Modifier) -> Self {
+        match modifier {
+            Modifier::Shift => Self::MoveHere,
+            Modifier::Ctrl => Self::Close,
+            _ => Self::Focus,
+        }
+    }
+}
+
+pub fn search_windows(query: &str, ctx: &AppContext) -> Result<Vec<SearchResult>, String> {
+    search_windows_with(wm::detect().as_ref(), query, ctx)
+}
+
+fn search_windows_with(
+    wm: &dyn WindowManager,
+    query: &str,
+    ctx: &AppContext,
+) -> Result<Vec<SearchResult>, String> {
+    if !wm.can_focus() {
+        return Ok(vec![info_result(
+            "No supported window manager",
+            "The window switcher needs Hyprland or Sway",
+        )]);
+    }
+    let own_pid = std::process::id();
+    let windows: Vec<Window> = wm
+        .windows()?
+        .into_iter()
+        .filter(|window| window.pid != Some(own_pid))
+        .collect();
+
+    let mut matcher = Matcher::new(nucleo::Config::DEFAULT);
+    let pattern = Pattern::new(
+        query,
+        CaseMatching::Ignore,
+        Normalization::Smart,
+        nucleo::pattern::AtomKind::Fuzzy,
+    );
+    let mut buf = Vec::new();
+    let mut scored: Vec<(u32, usize, &Window)> = windows
+        .iter()
+        .enumerate()
+        .filter_map(|(index, window)| {
+            if query.is_empty() {
+                return Some((0, index, window));
+            }
+            let score = [window.title.as_str(), window.class.as_str()]
+                .into_iter()
+                .filter_map(|text| {
+                    pattern.score(nucleo::Utf32Str::new(text, &mut buf), &mut matcher)
+                })
+                .max()?;
+            Some((score, index, window))
+        })
+        .collect();
+    // Best match first; ties keep the compositor's order
+    scored.sort_by_key(|(score, index, _)| (std::cmp::Reverse(*score), *index));
+
+    let app_keys = ctx.apps.window_keys();
+    Ok(scored
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, _, window)| window_to_result(window, &app_keys, ctx))
+        .collect())
+}
+
+fn window_to_result(
+    window: &Window,
+    app_keys: &[(String, Vec<String>)],
+    ctx: &AppContext,
+) -> SearchResult {
+    let icon_name = app_keys
+        .iter()
+        .find(|(_, keys)| wm::window_matches(window, keys))
+        .and_then(|(id, _)| ctx.apps.icon_name(id))
+        .unwrap_or_else(|| window.class.clone());
+    let mut description = window.class.clone();
+    if !window.workspace.is_empty() {
+        description.push_str(&format!(" · workspace {}", window.workspace));
+    }
+    SearchResult {
+        id: window.id.clone(),
+        name: if window.title.is_empty() {
+            window.class.clone()
+        } else {
+            window.title.clone()
+        },
+        description,
+        icon: icons::resolve_icon(&icon_name),
+        category: Category::Window,
+        exec: String::new(),
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    }
+}
+
+fn info_result(name: &str, description: &str) -> SearchResult {
+    SearchResult {
+        id: "window-unsupported".into(),
+        name: name.into(),
+        description: description.into(),
+        icon: String::new(),
+        category: Category::Info,
+        exec: String::new(),
+        input_spec: None,
+        output_mode: None,
+        output_format: None,
+    }
+}
+
+/// Apply `action` to the window with backend id `window_id`. The window is
+/// looked up again so a stale or forged id never reaches the compositor.
+pub fn act_on_window(
+    wm: &dyn WindowManager,
+    window_id: &str,
+    action: WindowAction,
+) -> Result<(), String> {
+    let window = wm
+        .windows()?
+        .into_iter()
+        .find(|window| window.id == window_id)
+        .ok_or_else(|| format!("Window is no longer open: {window_id}"))?;
+    if crate::actions::dry_run::is_enabled() {
+        return crate::actions::dry_run::window_action(action, &window.class, &window.id);
+    }
+    tracing::info!(wm = wm.name(), window = %window.id, ?action, "window action");
+    match action {
+        WindowAction::Focus => wm.focus(&window),
+        WindowAction::MoveHere => wm.move_to_current_workspace(&window),
+        WindowAction::Close => wm.close(&window),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wm::tests::{ctx_with_apps, fake, window};
+
+    fn titled(id: &str, class: &str, title: &str) -> Window {
+        Window {
+            title: title.into(),
+            ..window(id, class, false)
+        }
+    }
+
+    #[test]
+    fn matches_title_and_app_id() {
+        let (ctx, _dir) = ctx_with_apps(&[("foot", "Exec=foot\nIcon=utilities-terminal")]);
+        let wm = fake(
+            vec![
+                titled("1", "firefox", "Rust docs — Mozilla Firefox"),
+                titled("2", "foot", "~/src/burrow"),
+                titled("3", "org.gnome.Nautilus", ""),
+            ],
+            true,
+        );
+
+        let by_title = search_windows_with(&wm, "burrow", &ctx).unwrap();
+        assert_eq!(by_title.len(), 1);
+        assert_eq!(by_title[0].id, "2");
+        assert_eq!(by_title[0].category, Category::Window);
+        assert_eq!(by_title[0].description, "foot · workspace 1");
+
+        let by_class = search_windows_with(&wm, "nautilus", &ctx).unwrap();
+        assert_eq!(by_class[0].id, "3");
+        // Untitled windows fall back to the app id
+        assert_eq!(by_class[0].name, "org.gnome.Nautilus");
+
+        let all = search_windows_with(&wm, "", &ctx).unwrap();
+        let ids: Vec<&str> = all.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn skips_own_windows_and_unsupported_backends() {
+        let (ctx, _dir) = ctx_with_apps(&[]);
+        let mut own = window("1", "burrow", false);
+        own.pid = Some(std::process::id());
+        let wm = fake(vec![own, window("2", "foot", false)], true);
+        let results = search_windows_with(&wm, "", &ctx).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, "2");
+
+        let no_ipc = fake(vec![window("2", "foot", false)], false);
+        let results = search_windows_with(&no_ipc, "", &ctx).unwrap();
+        assert_eq!(results[0].category, Category::Info);
+    }
+
+    #[test]
+    fn modifiers_pick_focus_move_or_close() {
+        assert_eq!(
+            WindowAction::from_modifier(Modifier::None),
+            WindowAction::Focus
+        );
+        assert_eq!(
+            WindowAction::from_modifier(Modifier::Shift),
+            WindowAction::MoveHere
+        );
+        assert_eq!(
+            WindowAction::from_modifier(Modifier::Ctrl),
+            WindowAction::Close
+        );
+
+        let wm = fake(
+            vec![window("a", "foot", false), window("b", "firefox", false)],
+            true,
+        );
+        act_on_window(&wm, "a", WindowAction::Focus).unwrap();
+        act_on_window(&wm, "b", WindowAction::MoveHere).unwrap();
+        act_on_window(&wm, "a", WindowAction::Close).unwrap();
+        assert_eq!(*wm.focused.lock().unwrap(), vec!["a"]);
+        assert_eq!(*wm.moved.lock().unwrap(), vec!["b"]);
+        assert_eq!(*wm.closed.lock().unwrap(), vec!["a"]);
+    }
+
+    #[test]
+    fn acting_on_a_closed_window_fails() {
+        let wm = fake(vec![window("a", "foot", false)], true);
+        let err = act_on_window(&wm, "gone", WindowAction::Close).unwrap_err();
+        assert!(err.contains("no longer open"), "{err}");
+        assert!(wm.closed.lock().unwrap().is_empty());
+    }
+}
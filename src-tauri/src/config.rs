@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 
@@ -10,6 +11,7 @@ pub struct AppConfig {
     pub models: ModelsConfig,
     pub ollama: OllamaConfig,
     pub openrouter: OpenRouterConfig,
+    pub openai_compatible: OpenAiCompatibleConfig,
     pub chat: ChatConfig,
     pub vector_search: VectorSearchConfig,
     pub indexer: IndexerConfig,
@@ -17,11 +19,82 @@ pub struct AppConfig {
     pub search: SearchConfig,
     pub onepass: OnePassConfig,
     pub daemon: DaemonConfig,
+    pub launch: LaunchConfig,
+    pub apps: AppsConfig,
+    pub ssh: SshConfig,
 }
 
+/// Supported chat providers, for messages.
+const PROVIDER_NAMES: &str = "\"ollama\", \"openrouter\" or \"openai_compatible\"";
+
 /// Check if a provider string is one of the supported values.
 fn is_valid_provider(s: &str) -> bool {
-    matches!(s, "ollama" | "openrouter")
+    matches!(s, "ollama" | "openrouter" | "openai_compatible")
+}
+
+/// Validate per-model options, resetting invalid values to provider defaults.
+/// `context_length` and `keep_alive` are Ollama-only: setting them for another
+/// provider is kept but warned about, since the request body drops them.
+fn validate_model_options(
+    warnings: &mut Vec<String>,
+    prefix: &str,
+    provider: &str,
+    options: &mut ModelOptions,
+) {
+    if options.temperature.is_some_and(f32::is_nan) {
+        warnings.push(format!(
+            "config: {prefix}.temperature is invalid — expected 0.0–2.0, got NaN, reset to provider default"
+        ));
+        options.temperature = None;
+    }
+    if let Some(temperature) = options.temperature.as_mut() {
+        validate_range(
+            warnings,
+            &format!("{prefix}.temperature"),
+            temperature,
+            0.0,
+            2.0,
+        );
+    }
+    if let Some(max_tokens) = options.max_tokens.as_mut() {
+        validate_range(
+            warnings,
+            &format!("{prefix}.max_tokens"),
+            max_tokens,
+            1,
+            1_000_000,
+        );
+    }
+    if let Some(context_length) = options.context_length.as_mut() {
+        validate_range(
+            warnings,
+            &format!("{prefix}.context_length"),
+            context_length,
+            256,
+            10_000_000,
+        );
+    }
+    if let Some(keep_alive) = options.keep_alive.as_mut() {
+        *keep_alive = keep_alive.trim().to_string();
+        if keep_alive.is_empty() {
+            warnings.push(format!(
+                "config: {prefix}.keep_alive is empty — reset to provider default"
+            ));
+            options.keep_alive = None;
+        }
+    }
+    if provider != "ollama" {
+        for (field, set) in [
+            ("context_length", options.context_length.is_some()),
+            ("keep_alive", options.keep_alive.is_some()),
+        ] {
+            if set {
+                warnings.push(format!(
+                    "config: {prefix}.{field} is only used by the \"ollama\" provider — ignored for \"{provider}\""
+                ));
+            }
+        }
+    }
 }
 
 /// Validate a numeric field is within [min, max], clamp if not, and push a warning.
@@ -51,6 +124,15 @@ fn validate_range<T: PartialOrd + std::fmt::Display + Copy>(
 }
 
 impl AppConfig {
+    /// The longest chat request timeout across providers, so a daemon client
+    /// never gives up before the server-side request does.
+    pub fn max_chat_timeout_secs(&self) -> u64 {
+        self.ollama
+            .chat_timeout_secs
+            .max(self.openrouter.chat_timeout_secs)
+            .max(self.openai_compatible.timeout_secs)
+    }
+
     /// Validate config fields with bounded ranges or constrained values, clamping
     /// out-of-bounds values and resetting invalid strings to defaults.
     /// Returns a list of warnings for any fields that were corrected or that have
@@ -74,6 +156,13 @@ impl AppConfig {
             10,
             600,
         );
+        validate_range(
+            &mut w,
+            "openrouter.chat_timeout_secs",
+            &mut self.openrouter.chat_timeout_secs,
+            10,
+            600,
+        );
         validate_range(
             &mut w,
             "vector_search.top_k",
@@ -123,6 +212,51 @@ impl AppConfig {
             1,
             100,
         );
+        for (field, value, default, min, max) in [
+            (
+                "history.selection_weight",
+                &mut self.history.selection_weight,
+                defaults.history.selection_weight,
+                0.0,
+                10.0,
+            ),
+            (
+                "history.selection_half_life_days",
+                &mut self.history.selection_half_life_days,
+                defaults.history.selection_half_life_days,
+                0.5,
+                365.0,
+            ),
+            (
+                "history.frecency_half_life_days",
+                &mut self.history.frecency_half_life_days,
+                defaults.history.frecency_half_life_days,
+                0.5,
+                3650.0,
+            ),
+        ] {
+            if value.is_nan() {
+                w.push(format!(
+                    "config: {field} is invalid — expected {min}–{max}, got NaN, reset to default {default}"
+                ));
+                *value = default;
+            }
+            validate_range(&mut w, field, value, min, max);
+        }
+        validate_range(
+            &mut w,
+            "history.selection_max_prefix",
+            &mut self.history.selection_max_prefix,
+            1,
+            64,
+        );
+        validate_range(
+            &mut w,
+            "history.sync_interval_mins",
+            &mut self.history.sync_interval_mins,
+            1,
+            1440,
+        );
         validate_range(
             &mut w,
             "search.max_results",
@@ -144,6 +278,13 @@ impl AppConfig {
             1,
             50,
         );
+        validate_range(
+            &mut w,
+            "chat.max_tool_steps",
+            &mut self.chat.max_tool_steps,
+            1,
+            20,
+        );
         validate_range(
             &mut w,
             "onepass.idle_timeout_minutes",
@@ -151,6 +292,20 @@ impl AppConfig {
             0,
             1440,
         );
+        validate_range(
+            &mut w,
+            "onepass.clipboard_clear_secs",
+            &mut self.onepass.clipboard_clear_secs,
+            0,
+            3600,
+        );
+        validate_range(
+            &mut w,
+            "onepass.autotype_delay_ms",
+            &mut self.onepass.autotype_delay_ms,
+            0,
+            10000,
+        );
         validate_range(
             &mut w,
             "daemon.startup_timeout_secs",
@@ -186,6 +341,72 @@ impl AppConfig {
             self.vector_search.index_mode = "all".into();
         }
 
+        self.history.frecency_model = self.history.frecency_model.trim().to_string();
+        if !matches!(
+            self.history.frecency_model.as_str(),
+            "buckets" | "half_life"
+        ) {
+            w.push(format!(
+                "config: history.frecency_model is invalid — expected \"buckets\" or \"half_life\", got \"{}\", reset to default \"buckets\"",
+                self.history.frecency_model
+            ));
+            self.history.frecency_model = defaults.history.frecency_model.clone();
+        }
+
+        self.launch.backend = self.launch.backend.trim().to_string();
+        if !matches!(
+            self.launch.backend.as_str(),
+            "direct" | "detached" | "systemd"
+        ) {
+            w.push(format!(
+                "config: launch.backend is invalid — expected \"direct\", \"detached\" or \"systemd\", got \"{}\", reset to default \"direct\"",
+                self.launch.backend
+            ));
+            self.launch.backend = defaults.launch.backend.clone();
+        }
+
+        let known = crate::commands::password_backends::BACKEND_NAMES;
+        let mut backends = Vec::new();
+        for name in &self.onepass.backends {
+            let name = name.trim();
+            if !known.contains(&name) {
+                w.push(format!(
+                    "config: onepass.backends has unknown backend \"{name}\" — expected one of {}, skipped",
+                    known.join(", ")
+                ));
+            } else if !backends.iter().any(|b| b == name) {
+                backends.push(name.to_string());
+            }
+        }
+        if backends.is_empty() {
+            w.push(
+                "config: onepass.backends is invalid — expected at least one backend, reset to default [\"1password\"]".into()
+            );
+            backends = defaults.onepass.backends.clone();
+        }
+        self.onepass.backends = backends;
+
+        if let Err(e) = crate::actions::autotype::parse(&self.onepass.autotype) {
+            w.push(format!(
+                "config: onepass.autotype is invalid — {e}, reset to default \"{}\"",
+                defaults.onepass.autotype
+            ));
+            self.onepass.autotype = defaults.onepass.autotype.clone();
+        }
+        self.onepass
+            .autotype_items
+            .retain(
+                |item, template| match crate::actions::autotype::parse(template) {
+                    Ok(_) => true,
+                    Err(e) => {
+                        w.push(format!(
+                            "config: onepass.autotype_items.\"{item}\" is invalid — {e}, skipped"
+                        ));
+                        false
+                    }
+                },
+            );
+
         if self.indexer.file_extensions.is_empty() {
             w.push(
                 "config: indexer.file_extensions is invalid — expected non-empty list, got empty list, reset to defaults".into()
@@ -236,11 +457,49 @@ impl AppConfig {
             spec.provider = spec.provider.trim().to_string();
             if !is_valid_provider(&spec.provider) {
                 w.push(format!(
-                    "config: {prefix}.provider is invalid — expected \"ollama\" or \"openrouter\", got \"{}\", reset to default \"{}\"",
+                    "config: {prefix}.provider is invalid — expected {PROVIDER_NAMES}, got \"{}\", reset to default \"{}\"",
                     spec.provider, default_spec.provider
                 ));
                 spec.provider = default_spec.provider.clone();
             }
+            validate_model_options(&mut w, prefix, &spec.provider, &mut spec.options);
+        }
+
+        // ── Chat personas ────────────────────────────────────────
+        for (name, persona) in &mut self.chat.personas {
+            if persona.temperature.is_some_and(f32::is_nan) {
+                w.push(format!(
+                    "config: chat.personas.{name}.temperature is invalid — expected 0.0–2.0, got NaN, reset to model default"
+                ));
+                persona.temperature = None;
+            }
+            if let Some(temperature) = persona.temperature.as_mut() {
+                validate_range(
+                    &mut w,
+                    &format!("chat.personas.{name}.temperature"),
+                    temperature,
+                    0.0,
+                    2.0,
+                );
+            }
+            if let Some(spec) = persona.model.as_mut() {
+                spec.name = spec.name.trim().to_string();
+                spec.provider = spec.provider.trim().to_string();
+                if spec.name.is_empty() || !is_valid_provider(&spec.provider) {
+                    w.push(format!(
+                        "config: chat.personas.{name}.model is invalid — expected non-empty name and provider {PROVIDER_NAMES}, got \"{}\" / \"{}\", using default model",
+                        spec.name, spec.provider
+                    ));
+                    persona.model = None;
+                } else {
+                    validate_model_options(
+                        &mut w,
+                        &format!("chat.personas.{name}.model"),
+                        &spec.provider,
+                        &mut spec.options,
+                    );
+                }
+            }
         }
 
         // ── Cross-field: OpenRouter API key ──────────────────────
@@ -249,7 +508,8 @@ impl AppConfig {
             &self.models.chat,
             &self.models.chat_large,
         ]
-        .iter()
+        .into_iter()
+        .chain(self.chat.personas.values().filter_map(|p| p.model.as_ref()))
         .any(|m| m.provider == "openrouter");
         self.openrouter.api_key = self.openrouter.api_key.trim().to_string();
         if uses_openrouter && self.openrouter.api_key.is_empty() {
@@ -258,6 +518,46 @@ impl AppConfig {
             );
         }
 
+        // ── Cross-field: OpenAI-compatible endpoint ─────────────
+        let uses_openai_compatible = [
+            &self.models.embedding,
+            &self.models.chat,
+            &self.models.chat_large,
+        ]
+        .into_iter()
+        .chain(self.chat.personas.values().filter_map(|p| p.model.as_ref()))
+        .any(|m| m.provider == "openai_compatible");
+        self.openai_compatible.base_url = self
+            .openai_compatible
+            .base_url
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        if self.openai_compatible.base_url.is_empty() {
+            w.push(format!(
+                "config: openai_compatible.base_url is empty — reset to default \"{}\"",
+                defaults.openai_compatible.base_url
+            ));
+            self.openai_compatible.base_url = defaults.openai_compatible.base_url;
+        }
+        validate_range(
+            &mut w,
+            "openai_compatible.timeout_secs",
+            &mut self.openai_compatible.timeout_secs,
+            1,
+            3600,
+        );
+        self.openai_compatible.api_key_env = self.openai_compatible.api_key_env.trim().to_string();
+        if uses_openai_compatible
+            && !self.openai_compatible.api_key_env.is_empty()
+            && self.openai_compatible.api_key().is_none()
+        {
+            w.push(format!(
+                "config: openai_compatible.api_key_env is \"{}\" but that environment variable is unset or empty — requests will be sent without an API key",
+                self.openai_compatible.api_key_env
+            ));
+        }
+
         w
     }
 }
@@ -267,8 +567,11 @@ impl AppConfig {
 pub struct ModelSpec {
     /// Model name (e.g., "qwen3-embedding:8b", "anthropic/claude-sonnet-4")
     pub name: String,
-    /// Provider: "ollama" or "openrouter"
+    /// Provider: "ollama", "openrouter" or "openai_compatible"
     pub provider: String,
+    /// Generation options, written inline (`{ name = "...", temperature = 0.2 }`)
+    #[serde(flatten)]
+    pub options: ModelOptions,
 }
 
 impl ModelSpec {
@@ -276,6 +579,7 @@ impl ModelSpec {
         Self {
             name: name.into(),
             provider: "ollama".into(),
+            options: ModelOptions::default(),
         }
     }
 
@@ -283,6 +587,63 @@ impl ModelSpec {
         Self {
             name: name.into(),
             provider: "openrouter".into(),
+            options: ModelOptions::default(),
+        }
+    }
+}
+
+/// Per-model generation options. Unset options use the provider's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelOptions {
+    /// Sampling temperature (0.0–2.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Maximum tokens to generate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Context window size (Ollama `num_ctx`; ignored by OpenAI-style APIs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_length: Option<u32>,
+    /// How long Ollama keeps the model loaded, e.g. "10m" or "-1" (Ollama only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+}
+
+/// Any server speaking the OpenAI chat completions API (llama.cpp server,
+/// vLLM, LM Studio, LiteLLM, ...), used by models with provider "openai_compatible"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiCompatibleConfig {
+    /// API base URL including the version path, e.g. "http://localhost:8080/v1"
+    pub base_url: String,
+    /// Name of the environment variable holding the API key (empty for no auth)
+    pub api_key_env: String,
+    /// Extra HTTP headers sent with every request
+    pub headers: BTreeMap<String, String>,
+    /// Timeout for chat requests (seconds)
+    pub timeout_secs: u64,
+}
+
+impl OpenAiCompatibleConfig {
+    /// The API key from `api_key_env`, if set and non-empty.
+    pub fn api_key(&self) -> Option<String> {
+        if self.api_key_env.is_empty() {
+            return None;
+        }
+        std::env::var(&self.api_key_env)
+            .ok()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+    }
+}
+
+impl Default for OpenAiCompatibleConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://localhost:8080/v1".into(),
+            api_key_env: String::new(),
+            headers: BTreeMap::new(),
+            timeout_secs: 120,
         }
     }
 }
@@ -317,6 +678,27 @@ pub struct ChatConfig {
     pub rag_enabled: bool,
     /// Maximum context snippets to include in RAG prompt
     pub max_context_snippets: usize,
+    /// Let chat models call Burrow tools (content search, file read, calculator,
+    /// app/history lookup). Launching and typing always require user confirmation.
+    pub tools_enabled: bool,
+    /// Maximum model ↔ tool round-trips before the model must answer
+    pub max_tool_steps: usize,
+    /// Named personas, selected with `?@<name> ...` or `burrow chat --persona <name>`
+    pub personas: BTreeMap<String, PersonaConfig>,
+}
+
+/// A named chat persona (`[chat.personas.<name>]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonaConfig {
+    /// System prompt template. Supports `{{date}}`, `{{clipboard}}` and `{{snippets}}`.
+    pub system_prompt: String,
+    /// Model override (defaults to the model the caller would otherwise use)
+    pub model: Option<ModelSpec>,
+    /// Sampling temperature override (0.0–2.0)
+    pub temperature: Option<f32>,
+    /// RAG override (defaults to `chat.rag_enabled`)
+    pub rag: Option<bool>,
 }
 
 impl Default for ChatConfig {
@@ -324,6 +706,9 @@ impl Default for ChatConfig {
         Self {
             rag_enabled: true,
             max_context_snippets: 5,
+            tools_enabled: false,
+            max_tool_steps: 5,
+            personas: BTreeMap::new(),
         }
     }
 }
@@ -333,12 +718,37 @@ impl Default for ChatConfig {
 pub struct OnePassConfig {
     /// Minutes of idle time before the vault is cleared. Set to 0 to disable idle timeout.
     pub idle_timeout_minutes: u32,
+    /// Password managers loaded into the vault, in order: "1password",
+    /// "bitwarden", "pass", "gopass" and "keepassxc"
+    pub backends: Vec<String>,
+    /// KeePassXC database (`.kdbx`) read by the "keepassxc" backend
+    pub keepassxc_database: String,
+    /// Optional key file for the KeePassXC database
+    pub keepassxc_key_file: String,
+    /// Seconds before a copied secret leaves the clipboard. Set to 0 to keep it.
+    pub clipboard_clear_secs: u32,
+    /// Put back what the clipboard held before the secret instead of clearing it
+    pub clipboard_restore: bool,
+    /// What Enter types, e.g. "{USERNAME}{TAB}{PASSWORD}{ENTER}"
+    pub autotype: String,
+    /// Templates for single items, keyed by vault item id or title
+    pub autotype_items: BTreeMap<String, String>,
+    /// Milliseconds to wait after hiding before typing, so focus can return
+    pub autotype_delay_ms: u64,
 }
 
 impl Default for OnePassConfig {
     fn default() -> Self {
         Self {
             idle_timeout_minutes: 10,
+            backends: vec!["1password".into()],
+            keepassxc_database: String::new(),
+            keepassxc_key_file: String::new(),
+            clipboard_clear_secs: 30,
+            clipboard_restore: true,
+            autotype: crate::actions::autotype::DEFAULT_TEMPLATE.into(),
+            autotype_items: BTreeMap::new(),
+            autotype_delay_ms: 1000,
         }
     }
 }
@@ -361,6 +771,45 @@ impl Default for DaemonConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchConfig {
+    /// How apps and commands are started: "direct" (child of Burrow), "detached"
+    /// (double fork + setsid) or "systemd" (transient `systemd-run --user --scope`
+    /// unit per launch, falling back to "detached" without a user manager).
+    pub backend: String,
+}
+
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        Self {
+            backend: "direct".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshConfig {
+    /// Also list hosts from `~/.ssh/known_hosts` (unhashed entries only)
+    pub known_hosts: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppsConfig {
+    /// Directories scanned (non-recursively) for `*.AppImage` files
+    pub appimage_dirs: Vec<String>,
+}
+
+impl Default for AppsConfig {
+    fn default() -> Self {
+        Self {
+            appimage_dirs: vec!["~/Applications".into()],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct OllamaConfig {
@@ -397,7 +846,25 @@ pub struct IndexerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct HistoryConfig {
+    /// Files, hosts and other non-app history entries listed on the empty query.
     pub max_results: usize,
+    /// How much past picks for the typed query boost an app: its match score is
+    /// multiplied by up to `1 + selection_weight`. 0 disables learned ranking.
+    pub selection_weight: f64,
+    /// Days after which a past pick counts half as much
+    pub selection_half_life_days: f64,
+    /// Longest query prefix remembered per pick
+    pub selection_max_prefix: usize,
+    /// How launch recency is weighted: "buckets" (Firefox-style age buckets)
+    /// or "half_life" (each launch decays exponentially)
+    pub frecency_model: String,
+    /// Days after which a launch counts half as much (`half_life` model)
+    pub frecency_half_life_days: f64,
+    /// Shared directory (e.g. a Syncthing folder) to sync history through.
+    /// Empty disables sync.
+    pub sync_dir: String,
+    /// Minutes between syncs while Burrow runs
+    pub sync_interval_mins: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -407,12 +874,22 @@ pub struct SearchConfig {
     pub debounce_ms: u64,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct OpenRouterConfig {
     /// Never serialize back to config.toml to avoid leaking secrets to disk.
     #[serde(skip_serializing)]
     pub api_key: String,
+    pub chat_timeout_secs: u64,
+}
+
+impl Default for OpenRouterConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            chat_timeout_secs: 120,
+        }
+    }
 }
 
 impl Default for OllamaConfig {
@@ -504,7 +981,16 @@ impl Default for IndexerConfig {
 
 impl Default for HistoryConfig {
     fn default() -> Self {
-        Self { max_results: 6 }
+        Self {
+            max_results: 6,
+            selection_weight: 1.0,
+            selection_half_life_days: 14.0,
+            selection_max_prefix: 12,
+            frecency_model: "buckets".into(),
+            frecency_half_life_days: 30.0,
+            sync_dir: String::new(),
+            sync_interval_mins: 10,
+        }
     }
 }
 
@@ -654,7 +1140,7 @@ pub fn update_config_model(
     }
     if !is_valid_provider(provider) {
         return Err(format!(
-            "Invalid provider: \"{provider}\". Must be \"ollama\" or \"openrouter\""
+            "Invalid provider: \"{provider}\". Must be {PROVIDER_NAMES}"
         ));
     }
 
@@ -763,6 +1249,7 @@ mod tests {
         assert_eq!(cfg.vector_search.top_k, 10);
         assert_eq!(cfg.history.max_results, 6);
         assert_eq!(cfg.search.max_results, 10);
+        assert!(!cfg.ssh.known_hosts);
     }
 
     #[test]
@@ -781,6 +1268,229 @@ mod tests {
         let cfg = AppConfig::default();
         assert!(cfg.chat.rag_enabled);
         assert_eq!(cfg.chat.max_context_snippets, 5);
+        assert!(!cfg.chat.tools_enabled);
+        assert_eq!(cfg.chat.max_tool_steps, 5);
+        assert!(cfg.chat.personas.is_empty());
+    }
+
+    #[test]
+    fn parse_chat_tools_config() {
+        let cfg = parse_config(
+            r#"
+[chat]
+tools_enabled = true
+max_tool_steps = 3
+"#,
+        );
+        assert!(cfg.chat.tools_enabled);
+        assert_eq!(cfg.chat.max_tool_steps, 3);
+        assert!(cfg.chat.rag_enabled); // default
+    }
+
+    #[test]
+    fn parse_chat_personas() {
+        let cfg = parse_config(
+            r#"
+[chat.personas.reviewer]
+system_prompt = "You review code. Today is {{date}}."
+model = { name = "anthropic/claude-sonnet-4", provider = "openrouter" }
+temperature = 0.2
+rag = false
+
+[chat.personas.terse]
+system_prompt = "Answer in one sentence."
+"#,
+        );
+        assert_eq!(cfg.chat.personas.len(), 2);
+        let reviewer = &cfg.chat.personas["reviewer"];
+        assert!(reviewer.system_prompt.contains("{{date}}"));
+        assert_eq!(
+            reviewer.model.as_ref().unwrap().name,
+            "anthropic/claude-sonnet-4"
+        );
+        assert_eq!(reviewer.temperature, Some(0.2));
+        assert_eq!(reviewer.rag, Some(false));
+
+        let terse = &cfg.chat.personas["terse"];
+        assert!(terse.model.is_none());
+        assert!(terse.temperature.is_none());
+        assert!(terse.rag.is_none());
+    }
+
+    #[test]
+    fn validate_persona_temperature_and_model() {
+        let mut cfg = AppConfig::default();
+        cfg.chat.personas.insert(
+            "hot".into(),
+            PersonaConfig {
+                temperature: Some(5.0),
+                ..Default::default()
+            },
+        );
+        cfg.chat.personas.insert(
+            "nan".into(),
+            PersonaConfig {
+                temperature: Some(f32::NAN),
+                model: Some(ModelSpec {
+                    name: "x".into(),
+                    provider: "bogus".into(),
+                    options: ModelOptions::default(),
+                }),
+                ..Default::default()
+            },
+        );
+        let warnings = cfg.validate();
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("chat.personas.hot.temperature")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("chat.personas.nan.temperature")));
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("chat.personas.nan.model")));
+        assert_eq!(cfg.chat.personas["hot"].temperature, Some(2.0));
+        assert_eq!(cfg.chat.personas["nan"].temperature, None);
+        assert!(cfg.chat.personas["nan"].model.is_none());
+    }
+
+    #[test]
+    fn parse_model_options_and_openai_compatible() {
+        let cfg = parse_config(
+            r#"
+[models]
+chat = { name = "qwen2.5:7b", provider = "ollama", temperature = 0.3, context_length = 8192, keep_alive = "30m" }
+chat_large = { name = "local-model", provider = "openai_compatible", max_tokens = 2048 }
+
+[openai_compatible]
+base_url = "http://localhost:1234/v1/"
+api_key_env = "LMSTUDIO_KEY"
+
+[openai_compatible.headers]
+X-Team = "burrow"
+"#,
+        );
+        assert_eq!(cfg.models.chat.options.temperature, Some(0.3));
+        assert_eq!(cfg.models.chat.options.context_length, Some(8192));
+        assert_eq!(cfg.models.chat.options.keep_alive.as_deref(), Some("30m"));
+        assert_eq!(cfg.models.chat_large.provider, "openai_compatible");
+        assert_eq!(cfg.models.chat_large.options.max_tokens, Some(2048));
+        assert_eq!(cfg.models.embedding.options, ModelOptions::default());
+        assert_eq!(cfg.openai_compatible.api_key_env, "LMSTUDIO_KEY");
+        assert_eq!(cfg.openai_compatible.headers["X-Team"], "burrow");
+
+        let mut cfg = cfg;
+        let warnings = cfg.validate();
+        assert!(
+            !warnings.iter().any(|w| w.contains("provider is invalid")),
+            "{warnings:?}"
+        );
+        assert_eq!(cfg.openai_compatible.base_url, "http://localhost:1234/v1");
+    }
+
+    #[test]
+    fn model_options_are_omitted_when_unset() {
+        let toml_str = toml::to_string(&ModelSpec::ollama("m")).unwrap();
+        assert!(!toml_str.contains("temperature"), "{toml_str}");
+        assert!(!toml_str.contains("keep_alive"), "{toml_str}");
+    }
+
+    #[test]
+    fn validate_model_options() {
+        assert_clamps(
+            "models.chat.temperature",
+            |c| c.models.chat.options.temperature = Some(3.0),
+            |c| c.models.chat.options.temperature,
+            Some(2.0),
+        );
+        assert_clamps(
+            "models.chat.temperature",
+            |c| c.models.chat.options.temperature = Some(f32::NAN),
+            |c| c.models.chat.options.temperature,
+            None,
+        );
+        assert_clamps(
+            "models.chat_large.max_tokens",
+            |c| c.models.chat_large.options.max_tokens = Some(0),
+            |c| c.models.chat_large.options.max_tokens,
+            Some(1),
+        );
+        assert_clamps(
+            "models.chat.context_length",
+            |c| c.models.chat.options.context_length = Some(16),
+            |c| c.models.chat.options.context_length,
+            Some(256),
+        );
+        assert_clamps(
+            "models.chat.keep_alive",
+            |c| c.models.chat.options.keep_alive = Some("  ".into()),
+            |c| c.models.chat.options.keep_alive.clone(),
+            None,
+        );
+        assert_clamps(
+            "models.chat.context_length",
+            |c| {
+                c.models.chat.provider = "openrouter".into();
+                c.models.chat.options.context_length = Some(4096);
+            },
+            |c| c.models.chat.options.context_length,
+            Some(4096),
+        );
+        assert_clamps(
+            "models.chat.keep_alive",
+            |c| {
+                c.models.chat.provider = "openai_compatible".into();
+                c.models.chat.options.keep_alive = Some("5m".into());
+            },
+            |c| c.models.chat.options.keep_alive.clone(),
+            Some("5m".to_string()),
+        );
+        assert_valid("models.chat", |c| {
+            c.models.chat.provider = "ollama".into();
+            c.models.chat.options = ModelOptions {
+                temperature: Some(0.7),
+                max_tokens: Some(512),
+                context_length: Some(4096),
+                keep_alive: Some("-1".into()),
+            }
+        });
+    }
+
+    #[test]
+    fn validate_openai_compatible_endpoint() {
+        assert_clamps(
+            "openai_compatible.base_url",
+            |c| c.openai_compatible.base_url = " ".into(),
+            |c| c.openai_compatible.base_url.clone(),
+            "http://localhost:8080/v1".to_string(),
+        );
+        assert_clamps(
+            "openai_compatible.api_key_env",
+            |c| {
+                c.models.chat.provider = "openai_compatible".into();
+                c.openai_compatible.api_key_env = "BURROW_TEST_UNSET_KEY_VAR".into();
+            },
+            |c| c.openai_compatible.api_key(),
+            None,
+        );
+        // Unused endpoint: a missing key variable is not worth a warning
+        assert_valid("openai_compatible.api_key_env", |c| {
+            c.openai_compatible.api_key_env = "BURROW_TEST_UNSET_KEY_VAR".into();
+        });
+    }
+
+    #[test]
+    fn persona_openrouter_model_requires_api_key() {
+        let mut cfg = AppConfig::default();
+        cfg.chat.personas.insert(
+            "cloud".into(),
+            PersonaConfig {
+                model: Some(ModelSpec::openrouter("openai/gpt-4o")),
+                ..Default::default()
+            },
+        );
+        let warnings = cfg.validate();
+        assert!(warnings.iter().any(|w| w.contains("openrouter.api_key")));
     }
 
     #[test]
@@ -1090,6 +1800,11 @@ max_content_chars = 2048
     fn default_onepass_config() {
         let cfg = AppConfig::default();
         assert_eq!(cfg.onepass.idle_timeout_minutes, 10);
+        assert_eq!(cfg.onepass.backends, vec!["1password"]);
+        assert_eq!(cfg.onepass.clipboard_clear_secs, 30);
+        assert!(cfg.onepass.clipboard_restore);
+        assert_eq!(cfg.onepass.autotype, "{PASSWORD}");
+        assert_eq!(cfg.onepass.autotype_delay_ms, 1000);
     }
 
     #[test]
@@ -1098,9 +1813,25 @@ max_content_chars = 2048
             r#"
 [onepass]
 idle_timeout_minutes = 30
+backends = ["pass", "keepassxc"]
+keepassxc_database = "~/vault.kdbx"
+clipboard_clear_secs = 0
+clipboard_restore = false
+autotype = "{USERNAME}{TAB}{PASSWORD}{ENTER}"
+autotype_delay_ms = 300
+
+[onepass.autotype_items]
+"pass:bank" = "{TOTP}"
 "#,
         );
         assert_eq!(cfg.onepass.idle_timeout_minutes, 30);
+        assert_eq!(cfg.onepass.backends, vec!["pass", "keepassxc"]);
+        assert_eq!(cfg.onepass.keepassxc_database, "~/vault.kdbx");
+        assert_eq!(cfg.onepass.clipboard_clear_secs, 0);
+        assert!(!cfg.onepass.clipboard_restore);
+        assert_eq!(cfg.onepass.autotype, "{USERNAME}{TAB}{PASSWORD}{ENTER}");
+        assert_eq!(cfg.onepass.autotype_delay_ms, 300);
+        assert_eq!(cfg.onepass.autotype_items["pass:bank"], "{TOTP}");
     }
 
     #[test]
@@ -1235,6 +1966,12 @@ startup_timeout_secs = 10
             |c| c.ollama.chat_timeout_secs,
             10,
         );
+        assert_clamps(
+            "openrouter.chat_timeout_secs",
+            |c| c.openrouter.chat_timeout_secs = 1000,
+            |c| c.openrouter.chat_timeout_secs,
+            600,
+        );
     }
 
     #[test]
@@ -1305,6 +2042,139 @@ startup_timeout_secs = 10
         );
     }
 
+    #[test]
+    fn test_validate_password_backends() {
+        let mut cfg = AppConfig::default();
+        cfg.onepass.backends = vec![" pass ".into(), "lastpass".into(), "pass".into()];
+        let warnings = cfg.validate();
+        assert_eq!(cfg.onepass.backends, vec!["pass"]);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("lastpass"));
+
+        assert_clamps(
+            "onepass.backends",
+            |c| c.onepass.backends = Vec::new(),
+            |c| c.onepass.backends.clone(),
+            vec!["1password".to_string()],
+        );
+    }
+
+    #[test]
+    fn test_validate_autotype_templates() {
+        assert_clamps(
+            "onepass.autotype",
+            |c| c.onepass.autotype = "{USERNAME}{TAB}{PASWORD}".into(),
+            |c| c.onepass.autotype.clone(),
+            "{PASSWORD}".to_string(),
+        );
+        assert_valid("onepass.autotype", |c| {
+            c.onepass.autotype = "{USERNAME}{TAB}{PASSWORD}{ENTER}".into()
+        });
+
+        let mut cfg = AppConfig::default();
+        cfg.onepass.autotype_items.insert(
+            "GitHub".into(),
+            "{USERNAME}{ENTER}{DELAY 500}{PASSWORD}".into(),
+        );
+        cfg.onepass
+            .autotype_items
+            .insert("pass:bank".into(), "{PASSWORD".into());
+        let warnings = cfg.validate();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("pass:bank"));
+        assert_eq!(
+            cfg.onepass.autotype_items.keys().collect::<Vec<_>>(),
+            vec!["GitHub"]
+        );
+
+        assert_clamps(
+            "onepass.autotype_delay_ms",
+            |c| c.onepass.autotype_delay_ms = 60000,
+            |c| c.onepass.autotype_delay_ms,
+            10000,
+        );
+    }
+
+    #[test]
+    fn test_validate_launch_backend() {
+        assert_clamps(
+            "launch.backend",
+            |c| c.launch.backend = "fork".into(),
+            |c| c.launch.backend.clone(),
+            "direct".into(),
+        );
+        assert_valid("launch.backend", |c| c.launch.backend = " systemd ".into());
+    }
+
+    #[test]
+    fn test_validate_selection_ranking() {
+        assert_clamps(
+            "history.selection_weight",
+            |c| c.history.selection_weight = -1.0,
+            |c| c.history.selection_weight,
+            0.0,
+        );
+        assert_clamps(
+            "history.selection_weight",
+            |c| c.history.selection_weight = f64::NAN,
+            |c| c.history.selection_weight,
+            1.0,
+        );
+        assert_clamps(
+            "history.selection_half_life_days",
+            |c| c.history.selection_half_life_days = 0.0,
+            |c| c.history.selection_half_life_days,
+            0.5,
+        );
+        assert_clamps(
+            "history.selection_max_prefix",
+            |c| c.history.selection_max_prefix = 0,
+            |c| c.history.selection_max_prefix,
+            1,
+        );
+        assert_valid("history.selection_weight", |c| {
+            c.history.selection_weight = 0.0
+        });
+    }
+
+    #[test]
+    fn test_validate_frecency_model() {
+        assert_clamps(
+            "history.frecency_model",
+            |c| c.history.frecency_model = "linear".into(),
+            |c| c.history.frecency_model.clone(),
+            "buckets".into(),
+        );
+        assert_clamps(
+            "history.frecency_half_life_days",
+            |c| c.history.frecency_half_life_days = f64::NAN,
+            |c| c.history.frecency_half_life_days,
+            30.0,
+        );
+        assert_clamps(
+            "history.frecency_half_life_days",
+            |c| c.history.frecency_half_life_days = 0.1,
+            |c| c.history.frecency_half_life_days,
+            0.5,
+        );
+        assert_valid("history.frecency_model", |c| {
+            c.history.frecency_model = " half_life ".into()
+        });
+    }
+
+    #[test]
+    fn test_validate_sync_interval() {
+        assert_clamps(
+            "history.sync_interval_mins",
+            |c| c.history.sync_interval_mins = 0,
+            |c| c.history.sync_interval_mins,
+            1,
+        );
+        assert_valid("history.sync_dir", |c| {
+            c.history.sync_dir = "~/Sync/burrow".into()
+        });
+    }
+
     #[test]
     fn test_validate_numeric_field_clamping() {
         assert_clamps(
@@ -1349,12 +2219,27 @@ startup_timeout_secs = 10
             |c| c.chat.max_context_snippets,
             1,
         );
+        assert_clamps(
+            "chat.max_tool_steps",
+            |c| c.chat.max_tool_steps = 0,
+            |c| c.chat.max_tool_steps,
+            1,
+        );
         assert_clamps(
             "onepass.idle_timeout_minutes",
             |c| c.onepass.idle_timeout_minutes = 9999,
             |c| c.onepass.idle_timeout_minutes,
             1440,
         );
+        assert_clamps(
+            "onepass.clipboard_clear_secs",
+            |c| c.onepass.clipboard_clear_secs = 86400,
+            |c| c.onepass.clipboard_clear_secs,
+            3600,
+        );
+        assert_valid("onepass.clipboard_clear_secs", |c| {
+            c.onepass.clipboard_clear_secs = 0
+        });
         assert_clamps(
             "daemon.startup_timeout_secs",
             |c| c.daemon.startup_timeout_secs = 0,
@@ -1432,6 +2317,7 @@ startup_timeout_secs = 10
         cfg.search.max_results = 1;
         cfg.search.debounce_ms = 0;
         cfg.chat.max_context_snippets = 1;
+        cfg.chat.max_tool_steps = 1;
         cfg.onepass.idle_timeout_minutes = 0;
         cfg.daemon.startup_timeout_secs = 1;
         let warnings = cfg.validate();
@@ -1453,6 +2339,7 @@ startup_timeout_secs = 10
         cfg.search.max_results = 100;
         cfg.search.debounce_ms = 2000;
         cfg.chat.max_context_snippets = 50;
+        cfg.chat.max_tool_steps = 20;
         cfg.onepass.idle_timeout_minutes = 1440;
         cfg.daemon.startup_timeout_secs = 60;
         let warnings = cfg.validate();
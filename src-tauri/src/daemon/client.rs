@@ -47,7 +47,7 @@ impl DaemonClient {
     /// Falls back to default timeout if config not initialized.
     pub fn with_chat_timeout() -> Self {
         let timeout_secs = config::try_get_config()
-            .map(|cfg| cfg.ollama.chat_timeout_secs)
+            .map(|cfg| cfg.max_chat_timeout_secs())
             .unwrap_or(DEFAULT_CHAT_TIMEOUT_SECS);
         Self {
             timeout: Duration::from_secs(timeout_secs + CHAT_TIMEOUT_BUFFER_SECS),
@@ -189,24 +189,36 @@ impl DaemonClient {
     }
 
     /// Chat without document context.
-    pub async fn chat(&self, query: &str, small: bool) -> Result<ChatResponse, String> {
+    pub async fn chat(
+        &self,
+        query: &str,
+        small: bool,
+        persona: Option<&str>,
+    ) -> Result<ChatResponse, String> {
         self.post(
             "/chat",
             &ChatRequest {
                 query: query.to_string(),
                 small,
+                persona: persona.map(str::to_string),
             },
         )
         .await
     }
 
     /// Chat with document context (RAG).
-    pub async fn chat_docs(&self, query: &str, small: bool) -> Result<ChatResponse, String> {
+    pub async fn chat_docs(
+        &self,
+        query: &str,
+        small: bool,
+        persona: Option<&str>,
+    ) -> Result<ChatResponse, String> {
         self.post(
             "/chat/docs",
             &ChatRequest {
                 query: query.to_string(),
                 small,
+                persona: persona.map(str::to_string),
             },
         )
         .await
@@ -240,7 +252,7 @@ mod tests {
         config::init_config();
         let client = DaemonClient::with_chat_timeout();
         let cfg = config::get_config();
-        let expected = Duration::from_secs(cfg.ollama.chat_timeout_secs + CHAT_TIMEOUT_BUFFER_SECS);
+        let expected = Duration::from_secs(cfg.max_chat_timeout_secs() + CHAT_TIMEOUT_BUFFER_SECS);
         assert_eq!(client.timeout, expected);
     }
 
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::Instant;
 
-use crate::chat::{self, ContextSnippet};
+use crate::chat::{self, persona::Persona, ContextSnippet};
 use crate::commands::{health, vectors};
 use crate::config;
 use crate::indexer::{self, IndexStats, IndexerProgress, IndexerState};
@@ -70,6 +70,9 @@ pub struct ChatRequest {
     /// Use small model instead of large
     #[serde(default)]
     pub small: bool,
+    /// Persona name from `[chat.personas]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persona: Option<String>,
 }
 
 /// Response for chat endpoints.
@@ -456,54 +459,63 @@ fn select_chat_model(cfg: &config::AppConfig, small: bool) -> &config::ModelSpec
     }
 }
 
+/// Resolve the request's persona; `default_rag` applies when the persona doesn't set `rag`.
+fn select_persona(
+    cfg: &config::AppConfig,
+    body: &ChatRequest,
+    default_rag: bool,
+) -> Result<Persona, (StatusCode, String)> {
+    let model = select_chat_model(cfg, body.small);
+    match body.persona.as_deref() {
+        Some(name) => Persona::resolve(name, cfg, model, default_rag)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e)),
+        None => Ok(Persona::default_with(model, default_rag)),
+    }
+}
+
 async fn chat_handler(
     Json(body): Json<ChatRequest>,
 ) -> Result<Json<ChatResponse>, (StatusCode, String)> {
-    if body.query.trim().is_empty() {
-        return Err((StatusCode::BAD_REQUEST, "Query cannot be empty".to_string()));
-    }
-
-    tracing::info!(query = %body.query.chars().take(50).collect::<String>(), small = body.small, "chat request");
-
-    let cfg = config::get_config();
-    let model = select_chat_model(cfg, body.small);
-
-    let answer = chat::generate_chat(&body.query, &[], model)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
-
-    Ok(Json(ChatResponse {
-        answer,
-        model: model.name.clone(),
-        provider: model.provider.clone(),
-    }))
+    answer_chat(body, false, "chat request").await
 }
 
 async fn chat_docs_handler(
     Json(body): Json<ChatRequest>,
+) -> Result<Json<ChatResponse>, (StatusCode, String)> {
+    answer_chat(body, true, "chat-docs request").await
+}
+
+async fn answer_chat(
+    body: ChatRequest,
+    default_rag: bool,
+    label: &'static str,
 ) -> Result<Json<ChatResponse>, (StatusCode, String)> {
     if body.query.trim().is_empty() {
         return Err((StatusCode::BAD_REQUEST, "Query cannot be empty".to_string()));
     }
 
-    tracing::info!(query = %body.query.chars().take(50).collect::<String>(), small = body.small, "chat-docs request");
+    tracing::info!(query = %body.query.chars().take(50).collect::<String>(), small = body.small, persona = ?body.persona, "{label}");
 
     let cfg = config::get_config();
-    let model = select_chat_model(cfg, body.small);
+    let persona = select_persona(cfg, &body, default_rag)?;
 
     // Fetch context from vector DB
-    let context = fetch_context_for_query(&body.query, cfg)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+    let context = if persona.rag {
+        fetch_context_for_query(&body.query, cfg)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+    } else {
+        vec![]
+    };
 
-    let answer = chat::generate_chat(&body.query, &context, model)
+    let answer = chat::generate_persona_chat(&body.query, &context, &persona)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
     Ok(Json(ChatResponse {
         answer,
-        model: model.name.clone(),
-        provider: model.provider.clone(),
+        model: persona.model.name.clone(),
+        provider: persona.model.provider.clone(),
     }))
 }
 
@@ -654,6 +666,14 @@ mod tests {
         assert!(!req.small);
     }
 
+    #[test]
+    fn chat_request_deserializes_with_persona() {
+        let req: ChatRequest =
+            serde_json::from_str(r#"{"query": "hi", "persona": "reviewer"}"#).unwrap();
+        assert_eq!(req.persona.as_deref(), Some("reviewer"));
+        assert!(!req.small);
+    }
+
     #[test]
     fn chat_request_deserializes_with_small() {
         let req: ChatRequest = serde_json::from_str(r#"{"query": "hi", "small": true}"#).unwrap();
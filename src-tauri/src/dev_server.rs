@@ -9,7 +9,8 @@ use std::sync::Arc;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
 use crate::actions::{self, modifier::Modifier};
-use crate::commands::{apps, chat, health, history};
+use crate::chat::{tools::PendingAction, ChatReply};
+use crate::commands::{apps, chat, clipboard_ai, health, history, open_with};
 use crate::context::AppContext;
 use crate::router::{self, SearchResult};
 
@@ -35,25 +36,31 @@ async fn record_launch(
     State(ctx): State<AppState>,
     Json(body): Json<RecordLaunchBody>,
 ) -> Result<Json<()>, (StatusCode, String)> {
-    history::record_launch(
-        &body.id,
-        &body.name,
-        &body.exec,
-        &body.icon,
-        &body.description,
-        &ctx,
-    )
-    .map(Json)
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    history::record_launch(&body.result, body.query.as_deref(), &ctx)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
 }
 
 #[derive(Deserialize)]
 struct RecordLaunchBody {
-    id: String,
-    name: String,
-    exec: String,
-    icon: String,
-    description: String,
+    result: SearchResult,
+    #[serde(default)]
+    query: Option<String>,
+}
+
+async fn mark_result(
+    State(ctx): State<AppState>,
+    Json(body): Json<MarkResultBody>,
+) -> Result<Json<history::MarkState>, (StatusCode, String)> {
+    history::mark_result(&body.result, body.action, &ctx)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Deserialize)]
+struct MarkResultBody {
+    result: SearchResult,
+    action: history::MarkAction,
 }
 
 async fn launch_app(Json(body): Json<LaunchAppBody>) -> Result<Json<()>, (StatusCode, String)> {
@@ -70,7 +77,7 @@ struct LaunchAppBody {
 async fn chat_ask(
     State(ctx): State<AppState>,
     Json(body): Json<ChatAskBody>,
-) -> Result<Json<String>, (StatusCode, String)> {
+) -> Result<Json<ChatReply>, (StatusCode, String)> {
     chat::chat_ask(body.query, &ctx)
         .await
         .map(Json)
@@ -82,6 +89,37 @@ struct ChatAskBody {
     query: String,
 }
 
+async fn chat_confirm_action(
+    State(ctx): State<AppState>,
+    Json(body): Json<ChatConfirmActionBody>,
+) -> Result<Json<()>, (StatusCode, String)> {
+    chat::chat_confirm_action(body.action, &ctx)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Deserialize)]
+struct ChatConfirmActionBody {
+    action: PendingAction,
+}
+
+async fn clipboard_ai(
+    Json(body): Json<ClipboardAiBody>,
+) -> Result<Json<ChatReply>, (StatusCode, String)> {
+    clipboard_ai::clipboard_ai(&body.id, body.primary, body.language)
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Deserialize)]
+struct ClipboardAiBody {
+    id: String,
+    #[serde(default)]
+    primary: bool,
+    language: Option<String>,
+}
+
 async fn health_check(
     State(ctx): State<AppState>,
 ) -> Result<Json<health::HealthStatus>, (StatusCode, String)> {
@@ -106,6 +144,40 @@ async fn refresh_app_cache(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
 }
 
+async fn running_apps(State(ctx): State<AppState>) -> Json<Vec<String>> {
+    Json(crate::wm::running_apps(&ctx))
+}
+
+async fn open_with_choices(
+    State(ctx): State<AppState>,
+    Json(body): Json<OpenWithBody>,
+) -> Result<Json<open_with::OpenWithChoices>, (StatusCode, String)> {
+    open_with::open_with_choices(&body.path, &ctx)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+async fn open_with_app(
+    State(ctx): State<AppState>,
+    Json(body): Json<OpenWithAppBody>,
+) -> Result<Json<()>, (StatusCode, String)> {
+    open_with::open_with(&body.path, &body.app_id, &ctx)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Deserialize)]
+struct OpenWithBody {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct OpenWithAppBody {
+    path: String,
+    #[serde(alias = "appId")]
+    app_id: String,
+}
+
 async fn execute_action(
     State(ctx): State<AppState>,
     Json(body): Json<ExecuteActionBody>,
@@ -126,7 +198,7 @@ struct ExecuteActionBody {
 }
 
 async fn load_vault() -> Result<Json<String>, (StatusCode, String)> {
-    crate::commands::onepass::load_vault()
+    crate::commands::onepass::load_vault(None)
         .map(Json)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
 }
@@ -195,11 +267,17 @@ pub fn build_router(ctx: Arc<AppContext>) -> Router {
     Router::new()
         .route("/api/search", post(search))
         .route("/api/record_launch", post(record_launch))
+        .route("/api/mark_result", post(mark_result))
         .route("/api/launch_app", post(launch_app))
         .route("/api/chat_ask", post(chat_ask))
+        .route("/api/chat_confirm_action", post(chat_confirm_action))
+        .route("/api/clipboard_ai", post(clipboard_ai))
         .route("/api/health_check", post(health_check))
         .route("/api/app_cache_status", post(app_cache_status))
         .route("/api/refresh_app_cache", post(refresh_app_cache))
+        .route("/api/running_apps", post(running_apps))
+        .route("/api/open_with_choices", post(open_with_choices))
+        .route("/api/open_with", post(open_with_app))
         .route("/api/execute_action", post(execute_action))
         .route("/api/get_output", post(get_output))
         .route("/api/hide_window", post(hide_window_noop))
@@ -15,8 +15,9 @@ pub mod process_timeout;
 pub mod router;
 pub(crate) mod text_extract;
 pub mod window_manager;
+pub mod wm;
 
-use commands::{apps, history, vectors};
+use commands::{apps, history, open_with, vectors};
 use context::AppContext;
 use std::sync::Arc;
 use tauri::Manager;
@@ -72,6 +73,7 @@ pub fn run() {
             // Create shared state instances once — used by both Tauri managed state and AppContext.
             let db = Arc::new(history::DbState::new(history::open_history_db()?));
             let vector_db = Arc::new(vectors::VectorDbState::new(vectors::open_vector_db()?));
+            commands::history_sync::start(db.clone());
             let indexer_state = Arc::new(indexer::IndexerState::new());
             let output_buffers = Arc::new(output_buffers::OutputBufferState::new());
             let apps_state = Arc::new(apps::AppIndexState::new());
@@ -114,10 +116,16 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             router::search_cmd,
             history::record_launch_cmd,
+            history::mark_result_cmd,
             apps::launch_app,
             apps::app_cache_status_cmd,
             apps::refresh_app_cache_cmd,
+            open_with::open_with_choices_cmd,
+            open_with::open_with_cmd,
+            wm::running_apps_cmd,
             commands::chat::chat_ask_cmd,
+            commands::chat::chat_confirm_action_cmd,
+            commands::clipboard_ai::clipboard_ai_cmd,
             commands::health::health_check_cmd,
             actions::execute_action_cmd,
             output_buffers::get_output_cmd,
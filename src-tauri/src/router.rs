@@ -1,4 +1,4 @@
-use crate::commands::{apps, files, math, onepass, special, ssh, vectors};
+use crate::commands::{apps, files, history, math, onepass, special, ssh, vectors, windows};
 use crate::context::AppContext;
 use serde::{Deserialize, Serialize};
 
@@ -12,6 +12,12 @@ pub struct InputSpec {
     /// Example: "kitty --directory ~/cowork codex 'Use $init-cowork for topic:'\ {}"
     /// If input is empty, the base exec is used instead.
     pub template: String,
+    /// Enter runs the result without asking; Tab asks for the input.
+    #[serde(default)]
+    pub optional: bool,
+    /// The input is a password: masked while typed.
+    #[serde(default)]
+    pub secret: bool,
 }
 
 /// How the output of a launched command should be presented.
@@ -38,6 +44,45 @@ pub enum Category {
     Chat,
     Info,
     Special,
+    Window,
+}
+
+impl Category {
+    const ALL: [Category; 11] = [
+        Category::App,
+        Category::History,
+        Category::File,
+        Category::Ssh,
+        Category::Onepass,
+        Category::Math,
+        Category::Vector,
+        Category::Chat,
+        Category::Info,
+        Category::Special,
+        Category::Window,
+    ];
+
+    /// The serialized (lowercase) name, as stored in the history database.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::App => "app",
+            Category::History => "history",
+            Category::File => "file",
+            Category::Ssh => "ssh",
+            Category::Onepass => "onepass",
+            Category::Math => "math",
+            Category::Vector => "vector",
+            Category::Chat => "chat",
+            Category::Info => "info",
+            Category::Special => "special",
+            Category::Window => "window",
+        }
+    }
+
+    /// Inverse of [`Category::as_str`].
+    pub fn from_name(name: &str) -> Option<Category> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -68,6 +113,7 @@ pub enum RouteKind {
     Math,
     Chat,
     Special,
+    Window,
 }
 
 pub fn classify_query(query: &str) -> RouteKind {
@@ -99,6 +145,10 @@ pub fn classify_query(query: &str) -> RouteKind {
         return RouteKind::Ssh;
     }
 
+    if query.starts_with("w ") {
+        return RouteKind::Window;
+    }
+
     if math::try_calculate(query).is_some() {
         return RouteKind::Math;
     }
@@ -120,10 +170,14 @@ fn build_chat_results(q: &str) -> Vec<SearchResult> {
             output_format: None,
         }]
     } else {
+        let description = match crate::chat::persona::split_persona(q) {
+            (Some(name), _) => format!("Press Enter to ask the {name} persona"),
+            (None, _) => "Press Enter to get an AI answer".into(),
+        };
         vec![SearchResult {
             id: "chat-ask".into(),
             name: format!("Ask: {q}"),
-            description: "Press Enter to get an AI answer".into(),
+            description,
             icon: "".into(),
             category: Category::Chat,
             exec: "".into(),
@@ -134,13 +188,19 @@ fn build_chat_results(q: &str) -> Vec<SearchResult> {
     }
 }
 
+/// Files found by name and by content share their history.
+const FILE_CATEGORIES: &[Category] = &[Category::File, Category::Vector];
+
 /// Primary search implementation — Tauri-free.
 pub async fn search(query: String, ctx: &AppContext) -> Result<Vec<SearchResult>, String> {
+    let rank = |categories: &[Category], results: Result<Vec<SearchResult>, String>| {
+        results.map(|results| history::rank_by_frecency(results, categories, ctx))
+    };
     match classify_query(&query) {
         RouteKind::History => apps::get_all_apps_with_frecency(ctx),
         RouteKind::Special => {
             let q = query.trim_start_matches('#').trim();
-            special::search_special(q)
+            rank(&[Category::Special], special::search_special(q))
         }
         RouteKind::Chat => {
             let q = query.trim_start_matches('?').trim();
@@ -151,20 +211,27 @@ pub async fn search(query: String, ctx: &AppContext) -> Result<Vec<SearchResult>
             if content_query.is_empty() {
                 Ok(vec![])
             } else {
-                vectors::search_by_content(content_query, ctx).await
+                rank(
+                    FILE_CATEGORIES,
+                    vectors::search_by_content(content_query, ctx).await,
+                )
             }
         }
         RouteKind::FileSearch => {
             let q = query.trim_start();
-            files::search_files(q)
+            rank(FILE_CATEGORIES, files::search_files(q))
         }
         RouteKind::OnePassword => {
             let q = query.trim_start_matches('!').trim();
-            onepass::search_onepass(q).await
+            rank(&[Category::Onepass], onepass::search_onepass(q).await)
         }
         RouteKind::Ssh => {
             let q = query.strip_prefix("ssh").unwrap_or("").trim();
-            ssh::search_ssh(q)
+            ssh::search_ssh(q, ctx)
+        }
+        RouteKind::Window => {
+            let q = query.strip_prefix("w ").unwrap_or("").trim();
+            windows::search_windows(q, ctx)
         }
         RouteKind::Math => {
             if let Some(result) = math::try_calculate(&query) {
@@ -286,6 +353,14 @@ mod tests {
         assert_eq!(classify_query("#cowork"), RouteKind::Special);
     }
 
+    #[test]
+    fn w_space_prefix_routes_to_window() {
+        assert_eq!(classify_query("w "), RouteKind::Window);
+        assert_eq!(classify_query("w firefox"), RouteKind::Window);
+        assert_eq!(classify_query("w"), RouteKind::App);
+        assert_eq!(classify_query("wezterm"), RouteKind::App);
+    }
+
     #[test]
     fn hash_alone_routes_to_special() {
         assert_eq!(classify_query("#"), RouteKind::Special);
@@ -330,6 +405,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn chat_results_mention_selected_persona() {
+        let results = build_chat_results("@reviewer check this");
+        assert_eq!(results[0].id, "chat-ask");
+        assert!(
+            results[0].description.contains("reviewer persona"),
+            "expected persona in description, got: {}",
+            results[0].description
+        );
+    }
+
     // --- Category serialization ---
 
     #[test]
@@ -358,6 +444,20 @@ mod tests {
             serde_json::to_string(&Category::Special).unwrap(),
             "\"special\""
         );
+        assert_eq!(
+            serde_json::to_string(&Category::Window).unwrap(),
+            "\"window\""
+        );
+    }
+
+    #[test]
+    fn category_names_match_serde() {
+        for category in Category::ALL {
+            let json = serde_json::to_string(&category).unwrap();
+            assert_eq!(json, format!("\"{}\"", category.as_str()));
+            assert_eq!(Category::from_name(category.as_str()), Some(category));
+        }
+        assert_eq!(Category::from_name("App"), None);
     }
 
     #[test]
@@ -432,6 +532,8 @@ mod tests {
             input_spec: Some(InputSpec {
                 placeholder: "Enter value".into(),
                 template: "command --arg \"{}\"".into(),
+                optional: false,
+                secret: false,
             }),
             output_mode: None,
             output_format: None,
@@ -0,0 +1,125 @@
+//! Fallback for compositors without a supported IPC: reports the current user's
+//! processes from `/proc` as pseudo-windows so running apps still get a badge.
+//! Focusing is not possible, so Enter launches a new instance.
+
+use super::{Window, WindowManager};
+use std::os::unix::fs::MetadataExt;
+use std::path::PathBuf;
+
+const UNSUPPORTED: &str = "Window control is not supported by this window manager";
+
+pub struct ProcFallback {
+    proc_root: PathBuf,
+    uid: u32,
+}
+
+impl ProcFallback {
+    pub fn new() -> Self {
+        // SAFETY: getuid has no preconditions and cannot fail.
+        let uid = unsafe { libc::getuid() };
+        Self::with_root(PathBuf::from("/proc"), uid)
+    }
+
+    fn with_root(proc_root: PathBuf, uid: u32) -> Self {
+        Self { proc_root, uid }
+    }
+}
+
+impl Default for ProcFallback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WindowManager for ProcFallback {
+    fn name(&self) -> &'static str {
+        "proc"
+    }
+
+    fn windows(&self) -> Result<Vec<Window>, String> {
+        let entries = std::fs::read_dir(&self.proc_root)
+            .map_err(|e| format!("Failed to read {}: {e}", self.proc_root.display()))?;
+        let mut windows = Vec::new();
+        for entry in entries.flatten() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|s| s.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            let owned = entry.metadata().is_ok_and(|meta| meta.uid() == self.uid);
+            if !owned {
+                continue;
+            }
+            let Ok(comm) = std::fs::read_to_string(entry.path().join("comm")) else {
+                continue;
+            };
+            let comm = comm.trim();
+            if comm.is_empty() {
+                continue;
+            }
+            windows.push(Window {
+                id: pid.to_string(),
+                class: comm.to_string(),
+                title: String::new(),
+                pid: Some(pid),
+                workspace: String::new(),
+                focused: false,
+            });
+        }
+        Ok(windows)
+    }
+
+    fn can_focus(&self) -> bool {
+        false
+    }
+
+    fn focus(&self, _window: &Window) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    fn move_to_current_workspace(&self, _window: &Window) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    fn close(&self, _window: &Window) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uid() -> u32 {
+        // SAFETY: getuid has no preconditions and cannot fail.
+        unsafe { libc::getuid() }
+    }
+
+    #[test]
+    fn lists_processes_from_proc_root() {
+        let root = tempfile::tempdir().unwrap();
+        for (pid, comm) in [
+            ("100", "firefox\n"),
+            ("200", "gnome-system-mo\n"),
+            ("300", "\n"),
+        ] {
+            std::fs::create_dir(root.path().join(pid)).unwrap();
+            std::fs::write(root.path().join(pid).join("comm"), comm).unwrap();
+        }
+        std::fs::create_dir(root.path().join("self")).unwrap();
+
+        let wm = ProcFallback::with_root(root.path().to_path_buf(), uid());
+        let mut windows = wm.windows().unwrap();
+        windows.sort_by(|a, b| a.id.cmp(&b.id));
+        let classes: Vec<&str> = windows.iter().map(|w| w.class.as_str()).collect();
+        assert_eq!(classes, vec!["firefox", "gnome-system-mo"]);
+        assert_eq!(windows[0].pid, Some(100));
+        assert!(!wm.can_focus());
+        assert!(wm.focus(&windows[0]).is_err());
+
+        let other_user = ProcFallback::with_root(root.path().to_path_buf(), uid().wrapping_add(1));
+        assert!(other_user.windows().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,273 @@
+//! Hyprland backend over the `hyprctl` request socket (`.socket.sock`).
+//!
+//! Each request is one connection: write the command, read the reply until EOF.
+
+use super::{Window, WindowManager};
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const IPC_TIMEOUT: Duration = Duration::from_secs(1);
+
+pub struct Hyprland {
+    socket: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Client {
+    address: String,
+    #[serde(default = "default_true")]
+    mapped: bool,
+    #[serde(default)]
+    class: String,
+    #[serde(default)]
+    initial_class: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    pid: i64,
+    #[serde(default)]
+    workspace: Workspace,
+    /// 0 for the focused window
+    #[serde(default = "default_focus_history", rename = "focusHistoryID")]
+    focus_history_id: i64,
+}
+
+#[derive(Deserialize, Default)]
+struct Workspace {
+    #[serde(default)]
+    name: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_focus_history() -> i64 {
+    -1
+}
+
+impl Hyprland {
+    pub fn new(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+
+    /// The instance socket from `HYPRLAND_INSTANCE_SIGNATURE`, under
+    /// `$XDG_RUNTIME_DIR/hypr` (Hyprland ≥ 0.40) or `/tmp/hypr` (older releases).
+    pub fn from_env() -> Option<Self> {
+        let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok()?;
+        let runtime = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+        runtime
+            .into_iter()
+            .chain(std::iter::once(PathBuf::from("/tmp")))
+            .map(|dir| dir.join("hypr").join(&signature).join(".socket.sock"))
+            .find(|path| path.exists())
+            .map(Self::new)
+    }
+
+    fn dispatch(&self, args: &str) -> Result<(), String> {
+        let reply = self.request(&format!("dispatch {args}"))?;
+        if reply.trim() == "ok" {
+            Ok(())
+        } else {
+            Err(format!("Hyprland dispatch failed: {}", reply.trim()))
+        }
+    }
+
+    fn request(&self, command: &str) -> Result<String, String> {
+        let mut stream = UnixStream::connect(&self.socket)
+            .map_err(|e| format!("Failed to connect to Hyprland socket: {e}"))?;
+        stream.set_read_timeout(Some(IPC_TIMEOUT)).ok();
+        stream.set_write_timeout(Some(IPC_TIMEOUT)).ok();
+        stream
+            .write_all(command.as_bytes())
+            .map_err(|e| format!("Hyprland request failed: {e}"))?;
+        let mut reply = String::new();
+        stream
+            .read_to_string(&mut reply)
+            .map_err(|e| format!("Hyprland reply failed: {e}"))?;
+        Ok(reply)
+    }
+}
+
+fn parse_clients(json: &str) -> Result<Vec<Window>, String> {
+    let clients: Vec<Client> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid Hyprland clients JSON: {e}"))?;
+    Ok(clients
+        .into_iter()
+        .filter(|client| client.mapped)
+        .map(|client| Window {
+            id: client.address,
+            class: if client.class.is_empty() {
+                client.initial_class
+            } else {
+                client.class
+            },
+            title: client.title,
+            pid: u32::try_from(client.pid).ok().filter(|pid| *pid > 0),
+            workspace: client.workspace.name,
+            focused: client.focus_history_id == 0,
+        })
+        .collect())
+}
+
+impl WindowManager for Hyprland {
+    fn name(&self) -> &'static str {
+        "hyprland"
+    }
+
+    fn windows(&self) -> Result<Vec<Window>, String> {
+        parse_clients(&self.request("j/clients")?)
+    }
+
+    fn focus(&self, window: &Window) -> Result<(), String> {
+        self.dispatch(&format!("focuswindow address:{}", address(window)?))
+    }
+
+    fn move_to_current_workspace(&self, window: &Window) -> Result<(), String> {
+        let address = address(window)?;
+        let active: ActiveWorkspace = serde_json::from_str(&self.request("j/activeworkspace")?)
+            .map_err(|e| format!("Invalid Hyprland workspace JSON: {e}"))?;
+        self.dispatch(&format!("movetoworkspace {},address:{address}", active.id))?;
+        self.dispatch(&format!("focuswindow address:{address}"))
+    }
+
+    fn close(&self, window: &Window) -> Result<(), String> {
+        self.dispatch(&format!("closewindow address:{}", address(window)?))
+    }
+}
+
+#[derive(Deserialize)]
+struct ActiveWorkspace {
+    id: i64,
+}
+
+/// Addresses are hex handles from `j/clients`; refuse anything else so a
+/// window id can't smuggle extra dispatcher arguments.
+fn address(window: &Window) -> Result<&str, String> {
+    let valid = window
+        .id
+        .strip_prefix("0x")
+        .is_some_and(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()));
+    if valid {
+        Ok(&window.id)
+    } else {
+        Err(format!("Invalid Hyprland window address: {}", window.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::sync::{Arc, Mutex};
+
+    const CLIENTS: &str = r#"[
+        {"address": "0x5581a1", "mapped": true, "class": "firefox", "initialClass": "firefox",
+         "title": "Mozilla Firefox", "pid": 1201, "workspace": {"id": 1, "name": "1"},
+         "focusHistoryID": 1},
+        {"address": "0x5581b2", "mapped": true, "class": "", "initialClass": "foot",
+         "title": "~", "pid": 1302, "workspace": {"id": 2, "name": "dev"},
+         "focusHistoryID": 0},
+        {"address": "0x5581c3", "mapped": false, "class": "hidden", "title": "",
+         "pid": 1, "workspace": {"id": -1, "name": ""}, "focusHistoryID": 2}
+    ]"#;
+
+    /// Fake Hyprland socket: answers each connection from `reply` and records requests.
+    fn fake_socket(
+        reply: fn(&str) -> String,
+    ) -> (tempfile::TempDir, PathBuf, Arc<Mutex<Vec<String>>>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".socket.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let _ = stream.write_all(reply(&request).as_bytes());
+                seen.lock().unwrap().push(request);
+            }
+        });
+        (dir, path, requests)
+    }
+
+    fn reply(request: &str) -> String {
+        match request {
+            "j/clients" => CLIENTS.to_string(),
+            "j/activeworkspace" => r#"{"id": 3, "name": "3"}"#.into(),
+            r if r.starts_with("dispatch ") && r.ends_with("address:0x5581a1") => "ok".into(),
+            _ => "No such window found".into(),
+        }
+    }
+
+    #[test]
+    fn lists_mapped_clients() {
+        let (_dir, path, _) = fake_socket(reply);
+        let windows = Hyprland::new(path).windows().unwrap();
+        assert_eq!(windows.len(), 2);
+        assert_eq!(windows[0].id, "0x5581a1");
+        assert_eq!(windows[0].class, "firefox");
+        assert_eq!(windows[0].pid, Some(1201));
+        assert!(!windows[0].focused);
+        // Falls back to initialClass
+        assert_eq!(windows[1].class, "foot");
+        assert_eq!(windows[1].workspace, "dev");
+        assert!(windows[1].focused);
+    }
+
+    #[test]
+    fn focus_dispatches_by_address() {
+        let (_dir, path, requests) = fake_socket(reply);
+        let wm = Hyprland::new(path);
+        let windows = wm.windows().unwrap();
+        wm.focus(&windows[0]).unwrap();
+        let err = wm.focus(&windows[1]).unwrap_err();
+        assert!(err.contains("No such window"), "{err}");
+        assert_eq!(
+            requests.lock().unwrap()[1],
+            "dispatch focuswindow address:0x5581a1"
+        );
+    }
+
+    #[test]
+    fn move_and_close_dispatch_by_address() {
+        let (_dir, path, requests) = fake_socket(reply);
+        let wm = Hyprland::new(path);
+        let firefox = super::super::tests::window("0x5581a1", "firefox", false);
+        wm.move_to_current_workspace(&firefox).unwrap();
+        wm.close(&firefox).unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                "j/activeworkspace",
+                "dispatch movetoworkspace 3,address:0x5581a1",
+                "dispatch focuswindow address:0x5581a1",
+                "dispatch closewindow address:0x5581a1",
+            ]
+        );
+    }
+
+    #[test]
+    fn focus_rejects_malformed_addresses() {
+        let (_dir, path, requests) = fake_socket(reply);
+        let wm = Hyprland::new(path);
+        let mut window = super::super::tests::window("0x1;dispatch exec rm", "x", false);
+        assert!(wm.focus(&window).is_err());
+        window.id = "12".into();
+        assert!(wm.focus(&window).is_err());
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn missing_socket_is_an_error() {
+        let wm = Hyprland::new(PathBuf::from("/nonexistent/hypr/.socket.sock"));
+        assert!(wm.windows().is_err());
+    }
+}
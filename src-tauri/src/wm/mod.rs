@@ -0,0 +1,270 @@
+//! Window-manager integration: list open windows and focus them.
+//!
+//! Backends talk to the compositor's IPC socket directly (Hyprland's `hyprctl`
+//! socket, Sway's i3 IPC). Elsewhere the fallback can only tell which apps are
+//! running (from `/proc`), not focus them.
+
+pub mod fallback;
+pub mod hyprland;
+pub mod sway;
+
+use crate::context::AppContext;
+use serde::Serialize;
+
+/// An open window as reported by the window manager.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Window {
+    /// Backend handle: Hyprland address, Sway con id, or pid for the fallback
+    pub id: String,
+    /// Wayland app_id or X11 class
+    pub class: String,
+    pub title: String,
+    pub pid: Option<u32>,
+    pub workspace: String,
+    pub focused: bool,
+}
+
+pub trait WindowManager: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn windows(&self) -> Result<Vec<Window>, String>;
+    /// Whether [`WindowManager::focus`] is supported.
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focus(&self, window: &Window) -> Result<(), String>;
+    /// Move the window to the active workspace and focus it.
+    fn move_to_current_workspace(&self, window: &Window) -> Result<(), String>;
+    /// Ask the window to close (like its close button, not a kill).
+    fn close(&self, window: &Window) -> Result<(), String>;
+}
+
+/// Pick the backend for the running session from its environment.
+pub fn detect() -> Box<dyn WindowManager> {
+    if let Some(wm) = hyprland::Hyprland::from_env() {
+        return Box::new(wm);
+    }
+    if let Some(wm) = sway::Sway::from_env() {
+        return Box::new(wm);
+    }
+    Box::new(fallback::ProcFallback::new())
+}
+
+/// `comm` names in `/proc` are cut to 15 bytes.
+const PROC_COMM_LEN: usize = 15;
+
+/// Whether a window belongs to an app, given the app's lowercase match keys
+/// (StartupWMClass, desktop id, executable name, ...).
+pub fn window_matches(window: &Window, keys: &[String]) -> bool {
+    let class = window.class.to_lowercase();
+    if class.is_empty() {
+        return false;
+    }
+    keys.iter()
+        .any(|key| *key == class || (class.len() == PROC_COMM_LEN && key.starts_with(&class)))
+}
+
+/// Ids of apps with at least one open window (or process, for the fallback),
+/// plus apps running in Burrow's systemd scopes.
+pub fn running_app_ids(wm: &dyn WindowManager, ctx: &AppContext) -> Vec<String> {
+    let windows = wm.windows().unwrap_or_else(|e| {
+        tracing::debug!(wm = wm.name(), error = %e, "listing windows failed");
+        Vec::new()
+    });
+    let scopes = crate::actions::launch::running_app_ids();
+    let mut ids: Vec<String> = ctx
+        .apps
+        .window_keys()
+        .into_iter()
+        .filter(|(id, keys)| {
+            scopes.contains(id) || windows.iter().any(|window| window_matches(window, keys))
+        })
+        .map(|(id, _)| id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// Focus an open window of `app_id`. Returns `Ok(false)` when there is nothing
+/// to focus (no window, or the backend cannot focus), so the caller launches instead.
+pub fn focus_app(wm: &dyn WindowManager, app_id: &str, ctx: &AppContext) -> Result<bool, String> {
+    if !wm.can_focus() {
+        return Ok(false);
+    }
+    let Some(keys) = ctx.apps.window_keys_for(app_id) else {
+        return Ok(false);
+    };
+    let windows = wm.windows()?;
+    // Prefer a window that is not already focused, so Enter cycles through instances
+    let Some(window) = windows
+        .iter()
+        .filter(|window| window_matches(window, &keys))
+        .min_by_key(|window| window.focused)
+    else {
+        return Ok(false);
+    };
+    if crate::actions::dry_run::is_enabled() {
+        return crate::actions::dry_run::focus_window(&window.class, &window.id);
+    }
+    tracing::info!(app_id, wm = wm.name(), window = %window.id, "focusing running window");
+    wm.focus(window).map(|()| true)
+}
+
+/// Tauri-free list of running app ids for the "running" badge.
+pub fn running_apps(ctx: &AppContext) -> Vec<String> {
+    running_app_ids(detect().as_ref(), ctx)
+}
+
+#[tauri::command]
+pub fn running_apps_cmd(app: tauri::AppHandle) -> Vec<String> {
+    use tauri::Manager;
+    let ctx = app.state::<AppContext>();
+    running_apps(&ctx)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::commands::apps::AppIndexState;
+    use crate::commands::history::DbState;
+    use crate::commands::vectors::VectorDbState;
+    use crate::indexer::IndexerState;
+    use crate::output_buffers::OutputBufferState;
+    use rusqlite::Connection;
+    use std::sync::{Arc, Mutex};
+
+    pub(crate) fn window(id: &str, class: &str, focused: bool) -> Window {
+        Window {
+            id: id.into(),
+            class: class.into(),
+            title: format!("{class} window"),
+            pid: None,
+            workspace: "1".into(),
+            focused,
+        }
+    }
+
+    /// In-memory window manager recording focus/move/close requests.
+    pub(crate) struct FakeWm {
+        pub(crate) windows: Vec<Window>,
+        pub(crate) can_focus: bool,
+        pub(crate) focused: Mutex<Vec<String>>,
+        pub(crate) moved: Mutex<Vec<String>>,
+        pub(crate) closed: Mutex<Vec<String>>,
+    }
+
+    impl WindowManager for FakeWm {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+        fn windows(&self) -> Result<Vec<Window>, String> {
+            Ok(self.windows.clone())
+        }
+        fn can_focus(&self) -> bool {
+            self.can_focus
+        }
+        fn focus(&self, window: &Window) -> Result<(), String> {
+            self.focused.lock().unwrap().push(window.id.clone());
+            Ok(())
+        }
+        fn move_to_current_workspace(&self, window: &Window) -> Result<(), String> {
+            self.moved.lock().unwrap().push(window.id.clone());
+            Ok(())
+        }
+        fn close(&self, window: &Window) -> Result<(), String> {
+            self.closed.lock().unwrap().push(window.id.clone());
+            Ok(())
+        }
+    }
+
+    pub(crate) fn fake(windows: Vec<Window>, can_focus: bool) -> FakeWm {
+        FakeWm {
+            windows,
+            can_focus,
+            focused: Mutex::new(Vec::new()),
+            moved: Mutex::new(Vec::new()),
+            closed: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn ctx_with_apps(entries: &[(&str, &str)]) -> (AppContext, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        for (file_name, extra) in entries {
+            std::fs::write(
+                dir.path().join(format!("{file_name}.desktop")),
+                format!("[Desktop Entry]\nType=Application\nName={file_name}\n{extra}\n"),
+            )
+            .unwrap();
+        }
+        let ctx = AppContext::from_arcs(
+            Arc::new(DbState::new(Connection::open_in_memory().unwrap())),
+            Arc::new(VectorDbState::new(Connection::open_in_memory().unwrap())),
+            Arc::new(IndexerState::new()),
+            Arc::new(OutputBufferState::new()),
+            Arc::new(AppIndexState::new_for_test(vec![dir.path().to_path_buf()])),
+        );
+        (ctx, dir)
+    }
+
+    #[test]
+    fn window_matching_uses_keys_and_truncated_comm() {
+        let keys = vec!["org.gnome.nautilus".to_string(), "nautilus".to_string()];
+        assert!(window_matches(
+            &window("1", "org.gnome.Nautilus", false),
+            &keys
+        ));
+        assert!(window_matches(&window("1", "nautilus", false), &keys));
+        assert!(!window_matches(&window("1", "firefox", false), &keys));
+        assert!(!window_matches(&window("1", "", false), &keys));
+
+        let long = vec!["gnome-system-monitor".to_string()];
+        assert!(window_matches(
+            &window("1", "gnome-system-mo", false),
+            &long
+        ));
+        assert!(!window_matches(&window("1", "gnome-sys", false), &long));
+    }
+
+    #[test]
+    fn running_ids_match_windows_to_apps() {
+        let (ctx, _dir) = ctx_with_apps(&[
+            ("org.mozilla.firefox", "Exec=firefox %u"),
+            ("foot", "Exec=foot"),
+            ("code", "Exec=/usr/bin/code\nStartupWMClass=Code"),
+        ]);
+        let wm = fake(
+            vec![window("a", "firefox", false), window("b", "Code", true)],
+            true,
+        );
+        assert_eq!(
+            running_app_ids(&wm, &ctx),
+            vec!["code", "org.mozilla.firefox"]
+        );
+    }
+
+    #[test]
+    fn focus_prefers_unfocused_window() {
+        let (ctx, _dir) = ctx_with_apps(&[("foot", "Exec=foot")]);
+        let wm = fake(
+            vec![
+                window("a", "foot", true),
+                window("b", "foot", false),
+                window("c", "firefox", false),
+            ],
+            true,
+        );
+        assert!(focus_app(&wm, "foot", &ctx).unwrap());
+        assert_eq!(*wm.focused.lock().unwrap(), vec!["b"]);
+    }
+
+    #[test]
+    fn focus_reports_nothing_to_focus() {
+        let (ctx, _dir) = ctx_with_apps(&[("foot", "Exec=foot")]);
+        let wm = fake(vec![window("a", "firefox", false)], true);
+        assert!(!focus_app(&wm, "foot", &ctx).unwrap());
+        assert!(!focus_app(&wm, "unknown-app", &ctx).unwrap());
+
+        let no_focus = fake(vec![window("a", "foot", false)], false);
+        assert!(!focus_app(&no_focus, "foot", &ctx).unwrap());
+        assert!(no_focus.focused.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+//! Sway backend over i3 IPC (`$SWAYSOCK`).
+//!
+//! Messages are `"i3-ipc"`, a native-endian u32 payload length, a u32 message
+//! type, then the payload; replies use the same framing.
+
+use super::{Window, WindowManager};
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const GET_TREE: u32 = 4;
+const IPC_TIMEOUT: Duration = Duration::from_secs(1);
+/// Trees are small; anything bigger is a protocol error, not a real reply.
+const MAX_REPLY_BYTES: usize = 64 * 1024 * 1024;
+
+pub struct Sway {
+    socket: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    id: i64,
+    #[serde(default, rename = "type")]
+    node_type: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    app_id: Option<String>,
+    #[serde(default)]
+    window_properties: Option<WindowProperties>,
+    #[serde(default)]
+    pid: Option<u32>,
+    #[serde(default)]
+    focused: bool,
+    #[serde(default)]
+    nodes: Vec<Node>,
+    #[serde(default)]
+    floating_nodes: Vec<Node>,
+}
+
+#[derive(Deserialize)]
+struct WindowProperties {
+    #[serde(default)]
+    class: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CommandReply {
+    success: bool,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl Sway {
+    pub fn new(socket: PathBuf) -> Self {
+        Self { socket }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let socket = PathBuf::from(std::env::var_os("SWAYSOCK")?);
+        socket.exists().then(|| Self::new(socket))
+    }
+
+    fn run_command(&self, command: &str) -> Result<(), String> {
+        let reply = self.request(RUN_COMMAND, command)?;
+        let results: Vec<CommandReply> =
+            serde_json::from_str(&reply).map_err(|e| format!("Invalid Sway reply: {e}"))?;
+        match results.into_iter().find(|r| !r.success) {
+            None => Ok(()),
+            Some(failed) => Err(format!(
+                "Sway command failed: {}",
+                failed.error.unwrap_or_else(|| "unknown error".into())
+            )),
+        }
+    }
+
+    fn request(&self, message_type: u32, payload: &str) -> Result<String, String> {
+        let mut stream = UnixStream::connect(&self.socket)
+            .map_err(|e| format!("Failed to connect to Sway socket: {e}"))?;
+        stream.set_read_timeout(Some(IPC_TIMEOUT)).ok();
+        stream.set_write_timeout(Some(IPC_TIMEOUT)).ok();
+        stream
+            .write_all(&encode_message(message_type, payload.as_bytes()))
+            .map_err(|e| format!("Sway request failed: {e}"))?;
+        let (reply_type, body) =
+            read_message(&mut stream).map_err(|e| format!("Sway reply failed: {e}"))?;
+        if reply_type != message_type {
+            return Err(format!(
+                "Unexpected Sway reply type {reply_type} for request {message_type}"
+            ));
+        }
+        String::from_utf8(body).map_err(|e| format!("Sway reply is not UTF-8: {e}"))
+    }
+}
+
+fn encode_message(message_type: u32, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(14 + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&message_type.to_ne_bytes());
+    message.extend_from_slice(payload);
+    message
+}
+
+fn read_message(stream: &mut impl Read) -> std::io::Result<(u32, Vec<u8>)> {
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "bad i3-ipc magic",
+        ));
+    }
+    let len = u32::from_ne_bytes(header[6..10].try_into().unwrap_or_default()) as usize;
+    let message_type = u32::from_ne_bytes(header[10..14].try_into().unwrap_or_default());
+    if len > MAX_REPLY_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("i3-ipc payload too large ({len} bytes)"),
+        ));
+    }
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body)?;
+    Ok((message_type, body))
+}
+
+/// Collect leaf windows from a `GET_TREE` reply, tagging each with its workspace.
+fn parse_tree(json: &str) -> Result<Vec<Window>, String> {
+    let root: Node =
+        serde_json::from_str(json).map_err(|e| format!("Invalid Sway tree JSON: {e}"))?;
+    let mut windows = Vec::new();
+    collect_windows(&root, "", &mut windows);
+    Ok(windows)
+}
+
+fn collect_windows(node: &Node, workspace: &str, out: &mut Vec<Window>) {
+    let workspace = if node.node_type == "workspace" {
+        node.name.as_deref().unwrap_or(workspace)
+    } else {
+        workspace
+    };
+    let class = node.app_id.clone().filter(|id| !id.is_empty()).or_else(|| {
+        node.window_properties
+            .as_ref()
+            .and_then(|p| p.class.clone())
+    });
+    if let (Some(class), true) = (class, node.pid.is_some()) {
+        out.push(Window {
+            id: node.id.to_string(),
+            class,
+            title: node.name.clone().unwrap_or_default(),
+            pid: node.pid,
+            workspace: workspace.to_string(),
+            focused: node.focused,
+        });
+    }
+    for child in node.nodes.iter().chain(&node.floating_nodes) {
+        collect_windows(child, workspace, out);
+    }
+}
+
+impl WindowManager for Sway {
+    fn name(&self) -> &'static str {
+        "sway"
+    }
+
+    fn windows(&self) -> Result<Vec<Window>, String> {
+        parse_tree(&self.request(GET_TREE, "")?)
+    }
+
+    fn focus(&self, window: &Window) -> Result<(), String> {
+        self.run_command(&format!("[con_id={}] focus", con_id(window)?))
+    }
+
+    fn move_to_current_workspace(&self, window: &Window) -> Result<(), String> {
+        let con_id = con_id(window)?;
+        let workspaces: Vec<WorkspaceInfo> =
+            serde_json::from_str(&self.request(GET_WORKSPACES, "")?)
+                .map_err(|e| format!("Invalid Sway workspaces JSON: {e}"))?;
+        let current = workspaces
+            .into_iter()
+            .find(|ws| ws.focused)
+            .ok_or("No focused Sway workspace")?;
+        let name = current.name.replace('\\', "\\\\").replace('"', "\\\"");
+        self.run_command(&format!(
+            "[con_id={con_id}] move container to workspace \"{name}\"; [con_id={con_id}] focus"
+        ))
+    }
+
+    fn close(&self, window: &Window) -> Result<(), String> {
+        self.run_command(&format!("[con_id={}] kill", con_id(window)?))
+    }
+}
+
+#[derive(Deserialize)]
+struct WorkspaceInfo {
+    name: String,
+    #[serde(default)]
+    focused: bool,
+}
+
+/// Container ids are numbers; parsing refuses ids that would inject criteria or commands.
+fn con_id(window: &Window) -> Result<i64, String> {
+    window
+        .id
+        .parse()
+        .map_err(|_| format!("Invalid Sway container id: {}", window.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::sync::{Arc, Mutex};
+
+    const TREE: &str = r#"{
+        "id": 1, "type": "root", "name": "root", "nodes": [
+            {"id": 2, "type": "output", "name": "eDP-1", "nodes": [
+                {"id": 3, "type": "workspace", "name": "1: web", "nodes": [
+                    {"id": 10, "type": "con", "name": "Mozilla Firefox", "app_id": "firefox",
+                     "pid": 4001, "focused": true, "nodes": []}
+                ], "floating_nodes": [
+                    {"id": 11, "type": "floating_con", "name": "Steam", "app_id": null,
+                     "window_properties": {"class": "steam"}, "pid": 4002, "nodes": []}
+                ]},
+                {"id": 4, "type": "workspace", "name": "2", "nodes": [
+                    {"id": 5, "type": "con", "name": null, "nodes": [
+                        {"id": 12, "type": "con", "name": "~", "app_id": "foot", "pid": 4003, "nodes": []}
+                    ]}
+                ]}
+            ]}
+        ]
+    }"#;
+
+    /// Fake i3-ipc socket: replies to GET_TREE with `TREE` and to RUN_COMMAND
+    /// with success for con 12 only; records command payloads.
+    fn fake_socket() -> (tempfile::TempDir, PathBuf, Arc<Mutex<Vec<String>>>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sway-ipc.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&commands);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let Ok((message_type, body)) = read_message(&mut stream) else {
+                    continue;
+                };
+                let payload = String::from_utf8_lossy(&body).to_string();
+                let reply = match message_type {
+                    GET_TREE => TREE.to_string(),
+                    GET_WORKSPACES => {
+                        r#"[{"name": "1: web", "focused": false}, {"name": "say \"hi\"", "focused": true}]"#
+                            .to_string()
+                    }
+                    RUN_COMMAND if payload.starts_with("[con_id=12]") => {
+                        r#"[{"success": true}, {"success": true}]"#.to_string()
+                    }
+                    _ => r#"[{"success": false, "error": "No matching node"}]"#.to_string(),
+                };
+                if message_type == RUN_COMMAND {
+                    seen.lock().unwrap().push(payload);
+                }
+                let _ = stream.write_all(&encode_message(message_type, reply.as_bytes()));
+            }
+        });
+        (dir, path, commands)
+    }
+
+    #[test]
+    fn lists_leaf_windows_with_workspaces() {
+        let (_dir, path, _) = fake_socket();
+        let windows = Sway::new(path).windows().unwrap();
+        let summary: Vec<(&str, &str, &str, bool)> = windows
+            .iter()
+            .map(|w| {
+                (
+                    w.id.as_str(),
+                    w.class.as_str(),
+                    w.workspace.as_str(),
+                    w.focused,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("10", "firefox", "1: web", true),
+                ("11", "steam", "1: web", false),
+                ("12", "foot", "2", false),
+            ]
+        );
+        assert_eq!(windows[0].title, "Mozilla Firefox");
+        assert_eq!(windows[2].pid, Some(4003));
+    }
+
+    #[test]
+    fn focus_runs_con_id_command() {
+        let (_dir, path, commands) = fake_socket();
+        let wm = Sway::new(path);
+        let windows = wm.windows().unwrap();
+        wm.focus(&windows[2]).unwrap();
+        let err = wm.focus(&windows[0]).unwrap_err();
+        assert!(err.contains("No matching node"), "{err}");
+        assert_eq!(
+            *commands.lock().unwrap(),
+            vec!["[con_id=12] focus", "[con_id=10] focus"]
+        );
+
+        let bogus = super::super::tests::window("12] kill; [con_id=1", "x", false);
+        assert!(wm.focus(&bogus).is_err());
+    }
+
+    #[test]
+    fn move_and_close_use_con_id_criteria() {
+        let (_dir, path, commands) = fake_socket();
+        let wm = Sway::new(path);
+        let foot = super::super::tests::window("12", "foot", false);
+        wm.move_to_current_workspace(&foot).unwrap();
+        wm.close(&foot).unwrap();
+        assert_eq!(
+            *commands.lock().unwrap(),
+            vec![
+                r#"[con_id=12] move container to workspace "say \"hi\""; [con_id=12] focus"#,
+                "[con_id=12] kill",
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bad: &[u8] = b"i4-ipc\0\0\0\0\0\0\0\0";
+        assert!(read_message(&mut bad).is_err());
+        let mut good: &[u8] = &encode_message(GET_TREE, b"{}");
+        assert_eq!(read_message(&mut good).unwrap(), (GET_TREE, b"{}".to_vec()));
+    }
+}
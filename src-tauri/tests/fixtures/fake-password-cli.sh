@@ -0,0 +1,99 @@
+#!/bin/sh
+# Stand-in for the password manager CLIs Burrow drives (op, bw, pass, gopass
+# and keepassxc-cli), answering with fixed test data. It dispatches on the
+# subcommand, so one script serves every backend in tests.
+
+# The entry or database is always the last argument
+for last; do :; done
+
+# Value following option $1 among the remaining arguments
+option() {
+  want=$1; shift
+  while [ $# -gt 0 ]; do
+    [ "$1" = "$want" ] && { echo "$2"; return; }
+    shift
+  done
+}
+
+case "$1" in
+  account)
+    # op account list --format=json
+    echo '[{"account_uuid":"ACC1"}]'
+    ;;
+  signin)
+    # op signin --account ACC1 --raw
+    echo 'fake-op-session'
+    ;;
+  item)
+    case "$last" in
+      --session=fake-op-session) ;;
+      *) echo "[ERROR] You are not currently signed in." >&2; exit 1 ;;
+    esac
+    case "$2" in
+      list) echo '[{"id":"opitem1","title":"GitHub","category":"LOGIN"}]' ;;
+      get) echo '{"id":"opitem1","title":"GitHub","category":"LOGIN","fields":[{"id":"username","purpose":"USERNAME","value":"octocat"},{"id":"password","purpose":"PASSWORD","value":"op-secret"},{"id":"otp1","label":"one-time password","type":"OTP","value":"otpauth://totp/GitHub?secret=GEZDGNBVGY3TQOJQ"},{"id":"rc","label":"recovery code","type":"CONCEALED","value":"r-123"}]}' ;;
+    esac
+    ;;
+  unlock)
+    # bw unlock --raw --nointeraction --passwordenv <var>
+    var=$(option --passwordenv "$@")
+    [ -n "$var" ] && eval "password=\${$var}"
+    if [ "$password" != "bw-master" ]; then
+      echo "Invalid master password." >&2
+      exit 1
+    fi
+    echo 'fake-bw-session'
+    ;;
+  list)
+    # bw list items
+    if [ "$BW_SESSION" != "fake-bw-session" ]; then
+      echo "Vault is locked." >&2
+      exit 1
+    fi
+    cat <<'JSON'
+[
+  {"id":"bw-1","name":"Forum","type":1,"login":{"username":"alice","password":"bw-secret","uris":[{"match":null,"uri":"https://forum.example.com/login"}],"totp":"GEZDGNBVGY3TQOJQ"},"fields":[{"name":"PIN","value":"4321","type":1},{"name":"Linked","value":null,"type":3,"linkedId":100}]},
+  {"id":"bw-2","name":"Door code","type":2,"login":null,"notes":"1234"}
+]
+JSON
+    ;;
+  ls)
+    # gopass ls --flat
+    printf 'email/gmail.com\nbank\n'
+    ;;
+  show)
+    # pass show -- <entry>, gopass show -f -- <entry>
+    case "$last" in
+      email/gmail.com) printf 'pass-secret\nlogin: alice@gmail.com\nurl: https://mail.google.com\notpauth://totp/Gmail?secret=GEZDGNBVGY3TQOJQ\nrecovery: abc-def\n' ;;
+      bank) printf 'bank-secret\n' ;;
+      *) echo "Error: $last is not in the password store." >&2; exit 1 ;;
+    esac
+    ;;
+  export)
+    # keepassxc-cli export --format csv [--key-file <file>] [--no-password] -- <database>
+    case " $* " in
+      *" --no-password "*)
+        key=$(option --key-file "$@")
+        [ -n "$key" ] || { echo "Error: no key file given" >&2; exit 1; }
+        ;;
+      *)
+        read -r password
+        if [ "$password" != "kp-master" ]; then
+          echo "Error while reading the database: Invalid credentials were provided" >&2
+          exit 1
+        fi
+        ;;
+    esac
+    cat <<'CSV'
+"Group","Title","Username","Password","URL","Notes","TOTP","Icon","Last Modified","Created"
+"Root/Work","VPN","jdoe","kp-secret","https://vpn.example.com","two
+lines","otpauth://totp/VPN?secret=GEZDGNBVGY3TQOJQ","0","2024-01-01T00:00:00Z","2024-01-01T00:00:00Z"
+"Root","Router","admin","say ""hi""","","","","0","",""
+"Root","Router","guest","guest-secret","","","","0","",""
+CSV
+    ;;
+  *)
+    echo "fake-password-cli: unknown command: $*" >&2
+    exit 2
+    ;;
+esac
@@ -8,6 +8,8 @@ import "./styles.css";
 interface InputSpec {
   placeholder: string;
   template: string;
+  optional?: boolean;
+  secret?: boolean;
 }
 
 interface SearchResult {
@@ -20,17 +22,128 @@ interface SearchResult {
   input_spec?: InputSpec;
 }
 
+// Side-effectful action proposed by a chat model; runs only after the user clicks it.
+type ChatAction =
+  | { kind: "launch_app"; app_id: string; name: string }
+  | { kind: "type_text"; text: string }
+  | { kind: "copy_text"; text: string }
+  | { kind: "run_block"; block_id: string }
+  | { kind: "save_block"; block_id: string; path: string };
+
+// Chat answer split into prose and fenced code blocks; block ids resolve server-side.
+type AnswerPart =
+  | { kind: "text"; text: string }
+  | { kind: "code"; id: string; language: string | null; code: string; runnable: boolean };
+
+interface OpenWithChoices {
+  path: string;
+  mime_type: string;
+  apps: SearchResult[];
+}
+
+interface ChatReply {
+  answer: string;
+  actions: ChatAction[];
+  parts: AnswerPart[];
+}
+
+function chatActionLabel(action: ChatAction): string {
+  switch (action.kind) {
+    case "launch_app":
+      return `Launch ${action.name}`;
+    case "type_text":
+      return action.text.length > 40 ? "Type answer" : `Type "${action.text}"`;
+    case "copy_text":
+      return action.text.length > 40 ? "Copy answer" : `Copy "${action.text}"`;
+    case "run_block":
+      return "Run";
+    case "save_block":
+      return `Save to ${action.path}`;
+  }
+}
+
+// Code block with copy / run (two-step confirm) / save-to-file controls.
+function ChatCodeBlock({
+  part,
+  onAction,
+}: {
+  part: Extract<AnswerPart, { kind: "code" }>;
+  onAction: (action: ChatAction) => Promise<boolean>;
+}) {
+  const [confirmRun, setConfirmRun] = useState(false);
+  const [savePath, setSavePath] = useState<string | null>(null);
+
+  const run = async () => {
+    if (!confirmRun) {
+      setConfirmRun(true);
+      return;
+    }
+    setConfirmRun(false);
+    await onAction({ kind: "run_block", block_id: part.id });
+  };
+
+  const save = async () => {
+    if (savePath === null) {
+      setSavePath("~/Downloads/");
+      return;
+    }
+    if (await onAction({ kind: "save_block", block_id: part.id, path: savePath })) {
+      setSavePath(null);
+    }
+  };
+
+  return (
+    <div className="chat-code">
+      <pre>
+        <code>{part.code}</code>
+      </pre>
+      <div className="chat-code-actions">
+        {part.language && <span className="chat-code-lang">{part.language}</span>}
+        <button className="chat-action" onClick={() => onAction({ kind: "copy_text", text: part.code })}>
+          Copy
+        </button>
+        {part.runnable && (
+          <button
+            className={confirmRun ? "chat-action chat-action-confirm" : "chat-action"}
+            onClick={run}
+            onBlur={() => setConfirmRun(false)}
+          >
+            {confirmRun ? "Confirm run" : "Run"}
+          </button>
+        )}
+        {savePath !== null && (
+          <input
+            className="chat-code-path"
+            value={savePath}
+            autoFocus
+            onChange={(e) => setSavePath(e.target.value)}
+            onKeyDown={(e) => {
+              e.stopPropagation();
+              if (e.key === "Enter") save();
+              if (e.key === "Escape") setSavePath(null);
+            }}
+          />
+        )}
+        <button className="chat-action" onClick={save}>
+          {savePath === null ? "Save" : "Save file"}
+        </button>
+      </div>
+    </div>
+  );
+}
+
 const CATEGORY_LABELS: Record<string, string> = {
   app: "App",
   history: "Recent",
   file: "File",
   ssh: "SSH",
-  onepass: "1Pass",
+  onepass: "Vault",
   math: "Calc",
   vector: "Content",
   chat: "Chat",
   info: "Info",
   special: "Special",
+  window: "Window",
 };
 
 type HealthState = "ok" | "indexing" | "error";
@@ -53,6 +166,11 @@ interface RefreshAppsResult {
   app_count: number;
 }
 
+interface MarkState {
+  pinned: boolean;
+  hidden: boolean;
+}
+
 function healthStateFrom(status: HealthStatus): HealthState {
   if (!status.ollama || !status.vector_db) return "error";
   if (status.indexing) return "indexing";
@@ -112,6 +230,8 @@ function App() {
   const [selectedIndex, setSelectedIndex] = useState(0);
   const [notification, setNotification] = useState("");
   const [chatAnswer, setChatAnswer] = useState("");
+  const [chatActions, setChatActions] = useState<ChatAction[]>([]);
+  const [chatParts, setChatParts] = useState<AnswerPart[]>([]);
   const [chatLoading, setChatLoading] = useState(false);
   const [health, setHealth] = useState<HealthState>("ok");
   // Secondary input mode state
@@ -121,6 +241,9 @@ function App() {
     previousQuery: string;
   }>({ active: false, result: null, previousQuery: "" });
   const [secondaryInput, setSecondaryInput] = useState("");
+  // "Open with" picker: results are replaced by the apps that can open `path`
+  const [openWith, setOpenWith] = useState<{ path: string; mimeType: string } | null>(null);
+  const [runningIds, setRunningIds] = useState<Set<string>>(new Set());
   const notificationTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
   const visibilityEpoch = useRef(0);
   const inputRef = useRef<HTMLInputElement>(null);
@@ -172,6 +295,8 @@ function App() {
 
   useEffect(() => {
     setChatAnswer("");
+    setChatActions([]);
+    setChatParts([]);
     setChatLoading(false);
     const timer = setTimeout(() => doSearch(query), query ? 80 : 0);
     return () => clearTimeout(timer);
@@ -202,6 +327,20 @@ function App() {
     selectedResultIdRef.current = results[selectedIndex]?.id ?? null;
   }, [results, selectedIndex]);
 
+  // "Running" badges: Enter focuses these apps instead of launching another instance
+  useEffect(() => {
+    if (!results.some((r) => r.category === "app" || r.category === "history")) return;
+    let cancelled = false;
+    invoke<string[]>("running_apps")
+      .then((ids) => {
+        if (!cancelled) setRunningIds(new Set(ids));
+      })
+      .catch((e) => console.error("running_apps failed:", e));
+    return () => {
+      cancelled = true;
+    };
+  }, [results]);
+
   useEffect(() => {
     let cancelled = false;
     const pageIsVisible = () => !document.hidden || navigator.webdriver;
@@ -283,9 +422,12 @@ function App() {
         setQuery("");
         setSelectedIndex(0);
         setChatAnswer("");
+        setChatActions([]);
+        setChatParts([]);
         setChatLoading(false);
         setSecondaryMode({ active: false, result: null, previousQuery: "" });
         setSecondaryInput("");
+        setOpenWith(null);
         mouseStateRef.current = { phase: "initial" };
       }
     };
@@ -345,13 +487,62 @@ function App() {
     child?.scrollIntoView({ block: "nearest" });
   }, [selectedIndex]);
 
+  const confirmChatAction = useCallback(async (action: ChatAction) => {
+    try {
+      await invoke("chat_confirm_action", { action });
+      setChatActions((prev) => prev.filter((a) => a !== action));
+      if (action.kind === "copy_text") showNotification("✓ Copied");
+      if (action.kind === "save_block") showNotification(`✓ Saved to ${action.path}`);
+      return true;
+    } catch (err) {
+      console.error("Chat action failed:", err);
+      const errMsg = err instanceof Error ? err.message : String(err);
+      showNotification(`✗ ${chatActionLabel(action)} failed: ${errMsg}`);
+      return false;
+    }
+  }, [showNotification]);
+
+  // Pins and hidden results live in the history DB and apply to app listings
+  const markResult = useCallback(async (item: SearchResult, action: "toggle_pin" | "hide") => {
+    try {
+      const state = await invoke<MarkState>("mark_result", { result: item, action });
+      if (action === "hide") {
+        showNotification(`Hid ${item.name} — burrow history unhide ${item.id} to restore`);
+      } else {
+        showNotification(state.pinned ? `Pinned ${item.name}` : `Unpinned ${item.name}`);
+      }
+      await doSearch(queryRef.current, { preserveSelection: action !== "hide" });
+    } catch (err) {
+      console.error("Mark result failed:", err);
+      const errMsg = err instanceof Error ? err.message : String(err);
+      showNotification(`✗ ${errMsg}`);
+    }
+  }, [doSearch, showNotification]);
+
   const executeAction = useCallback(async (e: React.KeyboardEvent | null, itemOverride?: SearchResult) => {
     // In secondary mode, use the stored result
     const item = secondaryMode.active ? secondaryMode.result : (itemOverride ?? results[selectedIndex]);
     if (!item) return;
 
-    // Check if we should enter secondary mode (result has input_spec and not already in secondary mode)
-    if (item.input_spec && !secondaryMode.active) {
+    // Shift+Ctrl+Enter pins or unpins the selected result instead of running it
+    if (
+      e &&
+      !secondaryMode.active &&
+      !openWith &&
+      parseModifier({
+        shift: e.shiftKey,
+        ctrl: e.ctrlKey,
+        alt: e.altKey,
+        altgr: e.getModifierState("AltGraph"),
+      }) === "shift_ctrl"
+    ) {
+      await markResult(item, "toggle_pin");
+      return;
+    }
+
+    // Check if we should enter secondary mode (result has input_spec and not already in secondary mode).
+    // Optional input (SSH commands, vault fields) is only asked for with Tab.
+    if (item.input_spec && !item.input_spec.optional && !secondaryMode.active) {
       setSecondaryMode({ active: true, result: item, previousQuery: query });
       setQuery("");
       setSecondaryInput("");
@@ -360,8 +551,8 @@ function App() {
 
     // Note: In secondary mode, modifiers are captured but have no effect on Special category
     // commands (which use input_spec). This is intentional - secondary mode is for collecting
-    // text input, not selecting action variants. Modifiers are still passed to allow future
-    // categories to use them if needed.
+    // text input, not selecting action variants. Modifiers are still passed: vault fields
+    // copy instead of typing on Shift.
     const modifier = e
       ? parseModifier({
           shift: e.shiftKey,
@@ -380,17 +571,53 @@ function App() {
       setSecondaryInput("");
     }
 
+    if (openWith) {
+      try {
+        await invoke("open_with", { path: openWith.path, appId: item.id });
+        setOpenWith(null);
+      } catch (err) {
+        console.error("Open with failed:", err);
+        const errMsg = err instanceof Error ? err.message : String(err);
+        showNotification(`✗ Open with failed: ${errMsg}`);
+      }
+      return;
+    }
+
+    // Alt+Enter on a file lists the apps that can open it
+    if (modifier === "alt" && (item.category === "file" || item.category === "vector")) {
+      try {
+        const choices = await invoke<OpenWithChoices>("open_with_choices", { path: item.id });
+        if (choices.apps.length === 0) {
+          showNotification(`No apps can open ${choices.mime_type}`);
+          return;
+        }
+        searchRequestIdRef.current += 1; // drop in-flight searches
+        setOpenWith({ path: choices.path, mimeType: choices.mime_type });
+        setResults(choices.apps);
+        setSelectedIndex(0);
+      } catch (err) {
+        console.error("Open with lookup failed:", err);
+        const errMsg = err instanceof Error ? err.message : String(err);
+        showNotification(`✗ Open with failed: ${errMsg}`);
+      }
+      return;
+    }
+
     if (item.category === "chat") {
       const epoch = visibilityEpoch.current;
       const chatQuery = query;
       setChatLoading(true);
       setChatAnswer("");
+      setChatActions([]);
+      setChatParts([]);
       try {
-        const answer = await invoke<string>("chat_ask", { query: chatQuery });
+        const reply = await invoke<ChatReply>("chat_ask", { query: chatQuery });
         if (visibilityEpoch.current !== epoch || document.hidden) return;
         // Discard if query changed while waiting for response
         if (queryRef.current !== chatQuery) return;
-        setChatAnswer(answer);
+        setChatAnswer(reply.answer);
+        setChatActions(reply.actions);
+        setChatParts(reply.parts ?? []);
       } catch (e) {
         if (visibilityEpoch.current !== epoch || document.hidden) return;
         if (queryRef.current !== chatQuery) return;
@@ -404,6 +631,38 @@ function App() {
       return;
     }
 
+    // Clipboard AI actions (#summarize, #translate, ...): Shift reads the primary selection
+    if (item.category === "special" && item.exec.startsWith("clipboard-ai:")) {
+      const epoch = visibilityEpoch.current;
+      const startQuery = queryRef.current;
+      setChatLoading(true);
+      setChatAnswer("");
+      setChatActions([]);
+      setChatParts([]);
+      try {
+        const reply = await invoke<ChatReply>("clipboard_ai", {
+          id: item.id,
+          primary: modifier === "shift",
+          language: currentSecondaryInput,
+        });
+        if (visibilityEpoch.current !== epoch || document.hidden) return;
+        if (queryRef.current !== startQuery) return;
+        setChatAnswer(reply.answer);
+        setChatActions(reply.actions);
+        setChatParts(reply.parts ?? []);
+      } catch (err) {
+        if (visibilityEpoch.current !== epoch || document.hidden) return;
+        if (queryRef.current !== startQuery) return;
+        const errMsg = err instanceof Error ? err.message : String(err);
+        setChatAnswer(`Error: ${errMsg}`);
+      } finally {
+        if (visibilityEpoch.current === epoch && !document.hidden && queryRef.current === startQuery) {
+          setChatLoading(false);
+        }
+      }
+      return;
+    }
+
     if (item.id === "special-refresh") {
       try {
         const refreshed = await invoke<RefreshAppsResult>("refresh_app_cache");
@@ -423,14 +682,14 @@ function App() {
     }
 
     // Record launch for non-ephemeral categories
-    if (!["math", "info"].includes(item.category)) {
+    if (!["math", "info", "window"].includes(item.category)) {
       try {
         await invoke("record_launch", {
-          id: item.id,
-          name: item.name,
-          exec: item.exec,
-          icon: item.icon,
-          description: item.description,
+          result: item,
+          // What was typed before picking an app, for learned ranking
+          query: ["app", "history"].includes(item.category)
+            ? queryRef.current
+            : null,
         });
       } catch (err) {
         console.error("Record launch failed:", err);
@@ -449,7 +708,7 @@ function App() {
       const errMsg = err instanceof Error ? err.message : String(err);
       showNotification(`✗ Action failed: ${errMsg}`);
     }
-  }, [results, selectedIndex, query, secondaryMode, secondaryInput, doSearch, showNotification]);
+  }, [results, selectedIndex, query, secondaryMode, secondaryInput, openWith, doSearch, showNotification, markResult]);
 
   const handleKeyDown = useCallback(
     (e: React.KeyboardEvent) => {
@@ -470,6 +729,22 @@ function App() {
           e.preventDefault();
           executeAction(e);
           break;
+        case "Tab":
+          // Tab asks for secondary input on results where Enter runs without it
+          if (!secondaryMode.active && !openWith && results[selectedIndex]?.input_spec) {
+            e.preventDefault();
+            setSecondaryMode({ active: true, result: results[selectedIndex], previousQuery: query });
+            setQuery("");
+            setSecondaryInput("");
+          }
+          break;
+        case "Delete":
+          // Shift+Ctrl+Delete hides the selected result for good
+          if (e.shiftKey && e.ctrlKey && !secondaryMode.active && !openWith && results[selectedIndex]) {
+            e.preventDefault();
+            markResult(results[selectedIndex], "hide");
+          }
+          break;
         case "Escape":
           e.preventDefault();
           if (secondaryMode.active) {
@@ -478,13 +753,16 @@ function App() {
             setSecondaryInput("");
             setQuery(restoredQuery);
             doSearch(restoredQuery);
+          } else if (openWith) {
+            setOpenWith(null);
+            doSearch(queryRef.current);
           } else {
             invoke("hide_window").catch((e) => console.error("hide_window failed:", e));
           }
           break;
       }
     },
-    [results.length, executeAction, secondaryMode.active, doSearch]
+    [results, selectedIndex, query, executeAction, markResult, secondaryMode.active, openWith, doSearch]
   );
 
   const categoryLabel = (cat: string): string =>
@@ -496,13 +774,16 @@ function App() {
         <input
           ref={inputRef}
           className={`search-input ${secondaryMode.active ? "secondary" : ""}`}
-          type="text"
+          type={secondaryMode.active && secondaryMode.result?.input_spec?.secret ? "password" : "text"}
           value={secondaryMode.active ? secondaryInput : query}
-          onChange={(e) =>
-            secondaryMode.active
-              ? setSecondaryInput(e.target.value)
-              : setQuery(e.target.value)
-          }
+          onChange={(e) => {
+            if (secondaryMode.active) {
+              setSecondaryInput(e.target.value);
+            } else {
+              setOpenWith(null);
+              setQuery(e.target.value);
+            }
+          }}
           placeholder={
             secondaryMode.active
               ? secondaryMode.result?.input_spec?.placeholder
@@ -547,7 +828,30 @@ function App() {
         <div className="chat-answer chat-loading">Thinking...</div>
       )}
       {chatAnswer && !chatLoading && (
-        <div className="chat-answer">{chatAnswer}</div>
+        <div className="chat-answer">
+          {chatParts.some((p) => p.kind === "code")
+            ? chatParts.map((part, i) =>
+                part.kind === "text" ? (
+                  <div key={i}>{part.text}</div>
+                ) : (
+                  <ChatCodeBlock key={part.id} part={part} onAction={confirmChatAction} />
+                )
+              )
+            : chatAnswer}
+        </div>
+      )}
+      {chatActions.length > 0 && !chatLoading && (
+        <div className="chat-actions">
+          {chatActions.map((action, i) => (
+            <button
+              key={i}
+              className="chat-action"
+              onClick={() => confirmChatAction(action)}
+            >
+              {chatActionLabel(action)}
+            </button>
+          ))}
+        </div>
       )}
       {secondaryMode.active ? (
         <div className="secondary-indicator">
@@ -555,28 +859,41 @@ function App() {
           <span className="secondary-hint">Press Enter to confirm, Escape to cancel</span>
         </div>
       ) : (
-        <ul ref={listRef} className="results-list">
-          {results.map((item, i) => (
-            <li
-              key={item.id}
-              className={`result-item ${i === selectedIndex ? "selected" : ""}`}
-              onMouseEnter={() => { if (mouseStateRef.current.phase === "enabled") setSelectedIndex(i); }}
-              onClick={() => executeAction(null, item)}
-            >
-              <ResultIcon icon={item.icon} category={item.category} />
-              <div className="result-content">
-                <span className="result-name">{item.name}</span>
-                {item.description && (
-                  <span className="result-desc">{item.description}</span>
-                )}
-              </div>
-              <span className="result-badge">{categoryLabel(item.category)}</span>
-            </li>
-          ))}
-          {results.length === 0 && query && (
-            <li className="result-item empty">No results</li>
+        <>
+          {openWith && (
+            <div className="secondary-indicator">
+              <span className="secondary-name">Open {openWith.path.split("/").pop()} with…</span>
+              <span className="secondary-hint">{openWith.mimeType} · Escape to cancel</span>
+            </div>
           )}
-        </ul>
+          <ul ref={listRef} className="results-list">
+            {results.map((item, i) => (
+              <li
+                key={item.id}
+                className={`result-item ${i === selectedIndex ? "selected" : ""}`}
+                onMouseEnter={() => { if (mouseStateRef.current.phase === "enabled") setSelectedIndex(i); }}
+                onClick={() => executeAction(null, item)}
+              >
+                <ResultIcon icon={item.icon} category={item.category} />
+                <div className="result-content">
+                  <span className="result-name">{item.name}</span>
+                  {item.description && (
+                    <span className="result-desc">{item.description}</span>
+                  )}
+                </div>
+                {!openWith && runningIds.has(item.id) && (
+                  <span className="result-running" title="Running — Enter focuses it, Shift+Enter opens a new window">
+                    running
+                  </span>
+                )}
+                <span className="result-badge">{categoryLabel(item.category)}</span>
+              </li>
+            ))}
+            {results.length === 0 && query && (
+              <li className="result-item empty">No results</li>
+            )}
+          </ul>
+        </>
       )}
     </div>
   );
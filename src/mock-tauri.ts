@@ -12,20 +12,32 @@ type TauriMappedCmd =
   | "search"
   | "health_check"
   | "chat_ask"
+  | "chat_confirm_action"
+  | "clipboard_ai"
   | "record_launch"
+  | "mark_result"
   | "execute_action"
   | "get_output"
   | "app_cache_status"
-  | "refresh_app_cache";
+  | "refresh_app_cache"
+  | "open_with_choices"
+  | "open_with"
+  | "running_apps";
 const TAURI_CMD: Record<TauriMappedCmd, `${TauriMappedCmd}_cmd`> = {
   search: "search_cmd",
   health_check: "health_check_cmd",
   chat_ask: "chat_ask_cmd",
+  chat_confirm_action: "chat_confirm_action_cmd",
+  clipboard_ai: "clipboard_ai_cmd",
   record_launch: "record_launch_cmd",
+  mark_result: "mark_result_cmd",
   execute_action: "execute_action_cmd",
   get_output: "get_output_cmd",
   app_cache_status: "app_cache_status_cmd",
   refresh_app_cache: "refresh_app_cache_cmd",
+  open_with_choices: "open_with_choices_cmd",
+  open_with: "open_with_cmd",
+  running_apps: "running_apps_cmd",
 };
 
 export async function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {